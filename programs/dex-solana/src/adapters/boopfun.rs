@@ -1,10 +1,10 @@
 use super::common::DexProcessor;
 use crate::adapters::common::{before_check, invoke_process};
 use crate::error::ErrorCode;
-use crate::utils::{close_token_account, sync_wsol_account, transfer_sol};
+use crate::utils::{close_ephemeral_wsol, sync_wsol_account, transfer_sol};
 use crate::{
     BOOPFUN_BUY_SELECTOR, BOOPFUN_SELL_SELECTOR, HopAccounts, MIN_SOL_ACCOUNT_RENT,
-    SA_AUTHORITY_SEED, SOL_DIFF_LIMIT, ZERO_ADDRESS, authority_pda, boopfun_program, wsol_sa,
+    SA_AUTHORITY_SEED, SOL_DIFF_LIMIT, ZERO_ADDRESS, authority_pda, boopfun_program,
 };
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::instruction::Instruction;
@@ -108,30 +108,7 @@ impl DexProcessor for BoopfunBuyProcessor {
         let token_program = account_infos.get(11).unwrap();
         let authority = account_infos.get(6).unwrap();
 
-        if authority.key() == authority_pda::ID {
-            let before_sa_authority_lamports = authority.lamports();
-            require!(
-                source_token_account.key() != wsol_sa::ID,
-                ErrorCode::InvalidSourceTokenAccount
-            );
-            close_token_account(
-                source_token_account.to_account_info(),
-                authority.to_account_info(),
-                authority.to_account_info(),
-                token_program.to_account_info(),
-                Some(SA_AUTHORITY_SEED),
-            )?;
-            Ok(before_sa_authority_lamports)
-        } else {
-            close_token_account(
-                source_token_account.to_account_info(),
-                authority.to_account_info(),
-                authority.to_account_info(),
-                token_program.to_account_info(),
-                None,
-            )?;
-            Ok(0)
-        }
+        close_ephemeral_wsol(source_token_account, authority, token_program, None)
     }
 
     fn after_invoke(
@@ -1,14 +1,12 @@
 use crate::constants::{ACTUAL_IN_LOWER_BOUND_DEN, ACTUAL_IN_LOWER_BOUND_NUM};
 use crate::error::ErrorCode;
+use crate::utils::inspect_token_account;
 use crate::{HopAccounts, SA_AUTHORITY_SEED, ZERO_ADDRESS, authority_pda};
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::program_pack::Pack;
 use anchor_lang::solana_program::{
     instruction::Instruction,
     program::{invoke, invoke_signed},
 };
-use anchor_spl::token::spl_token::state::Account as SplTokenAccount;
-use anchor_spl::token_2022::spl_token_2022::state::Account as SplToken2022Account;
 use anchor_spl::token_interface::TokenAccount;
 
 pub trait DexProcessor {
@@ -258,29 +256,9 @@ fn enforce_sa_token_allowlist(
 ) -> Result<()> {
     for ai in account_infos.iter() {
         // Only consider token accounts (spl-token or token-2022)
-        if ai.owner == &anchor_spl::token::Token::id() {
-            if let Ok(data) = ai.try_borrow_data() {
-                if data.len() >= 165 {
-                    if let Ok(ta) = SplTokenAccount::unpack(&data) {
-                        if ta.owner == authority_pda::ID
-                            && !allowed_sa_token_accounts.contains(ai.key)
-                        {
-                            return Err(ErrorCode::UnexpectedSaTokenAccount.into());
-                        }
-                    }
-                }
-            }
-        } else if ai.owner == &anchor_spl::token_2022::Token2022::id() {
-            if let Ok(data) = ai.try_borrow_data() {
-                if data.len() >= 165 {
-                    if let Ok(ta) = SplToken2022Account::unpack_from_slice(&data) {
-                        if ta.owner == authority_pda::ID
-                            && !allowed_sa_token_accounts.contains(ai.key)
-                        {
-                            return Err(ErrorCode::UnexpectedSaTokenAccount.into());
-                        }
-                    }
-                }
+        if let Some(ta) = inspect_token_account(ai) {
+            if ta.owner == authority_pda::ID && !allowed_sa_token_accounts.contains(ai.key) {
+                return Err(ErrorCode::UnexpectedSaTokenAccount.into());
             }
         }
     }
@@ -1,10 +1,10 @@
 use super::common::DexProcessor;
 use crate::adapters::common::{before_check, invoke_process};
 use crate::error::ErrorCode;
-use crate::utils::{close_token_account, sync_wsol_account, transfer_sol};
+use crate::utils::{close_ephemeral_wsol, sync_wsol_account, transfer_sol};
 use crate::{
-    HopAccounts, MOONIT_BUY_SELECTOR, MOONIT_SELL_SELECTOR, SA_AUTHORITY_SEED, TOKEN_ACCOUNT_RENT,
-    ZERO_ADDRESS, authority_pda, moonit_program, wsol_sa,
+    HopAccounts, MOONIT_BUY_SELECTOR, MOONIT_SELL_SELECTOR, SA_AUTHORITY_SEED, ZERO_ADDRESS,
+    authority_pda, moonit_program,
 };
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::instruction::Instruction;
@@ -83,33 +83,8 @@ impl DexProcessor for MoonitBuyProcessor {
         let token_program = account_infos.get(8).unwrap();
         let authority = account_infos.get(0).unwrap();
 
-        if authority.key() == authority_pda::ID {
-            require!(
-                source_token_account.key() != wsol_sa::ID,
-                ErrorCode::InvalidSourceTokenAccount
-            );
-            close_token_account(
-                source_token_account.to_account_info(),
-                authority.to_account_info(),
-                authority.to_account_info(),
-                token_program.to_account_info(),
-                Some(SA_AUTHORITY_SEED),
-            )?;
-            transfer_sol(
-                authority.to_account_info(),
-                payer.to_account_info(),
-                TOKEN_ACCOUNT_RENT,
-                Some(SA_AUTHORITY_SEED),
-            )?;
-        } else {
-            close_token_account(
-                source_token_account.to_account_info(),
-                authority.to_account_info(),
-                authority.to_account_info(),
-                token_program.to_account_info(),
-                None,
-            )?;
-        }
+        let rent_receiver = (authority.key() == authority_pda::ID).then_some(payer);
+        close_ephemeral_wsol(source_token_account, authority, token_program, rent_receiver)?;
         Ok(0)
     }
 }
@@ -1,10 +1,10 @@
 use super::common::DexProcessor;
 use crate::adapters::common::{before_check, invoke_process};
 use crate::error::ErrorCode;
-use crate::utils::{close_token_account, log_sa_lamports_info, sync_wsol_account, transfer_sol};
+use crate::utils::{close_ephemeral_wsol, log_sa_lamports_info, sync_wsol_account, transfer_sol};
 use crate::{
     HopAccounts, MIN_SOL_ACCOUNT_RENT, PUMPFUN_BUY_SELECTOR, PUMPFUN_SELL_SELECTOR,
-    SA_AUTHORITY_SEED, SOL_DIFF_LIMIT, ZERO_ADDRESS, authority_pda, pumpfun_program, wsol_sa,
+    SA_AUTHORITY_SEED, SOL_DIFF_LIMIT, ZERO_ADDRESS, authority_pda, pumpfun_program,
 };
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::instruction::Instruction;
@@ -105,30 +105,7 @@ impl DexProcessor for PumpfunBuyProcessor3 {
         let source_token_account = account_infos.last().unwrap();
         let token_program = account_infos.get(8).unwrap();
         let authority = account_infos.get(6).unwrap();
-        if authority.key() == authority_pda::ID {
-            let before_sa_authority_lamports = authority.lamports();
-            require!(
-                source_token_account.key() != wsol_sa::ID,
-                ErrorCode::InvalidSourceTokenAccount
-            );
-            close_token_account(
-                source_token_account.to_account_info(),
-                authority.to_account_info(),
-                authority.to_account_info(),
-                token_program.to_account_info(),
-                Some(SA_AUTHORITY_SEED),
-            )?;
-            Ok(before_sa_authority_lamports)
-        } else {
-            close_token_account(
-                source_token_account.to_account_info(),
-                authority.to_account_info(),
-                authority.to_account_info(),
-                token_program.to_account_info(),
-                None,
-            )?;
-            Ok(0)
-        }
+        close_ephemeral_wsol(source_token_account, authority, token_program, None)
     }
 
     fn after_invoke(
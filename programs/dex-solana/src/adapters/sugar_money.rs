@@ -1,11 +1,10 @@
 use super::common::DexProcessor;
 use crate::adapters::common::{before_check, invoke_process};
 use crate::error::ErrorCode;
-use crate::utils::{close_token_account, log_sa_lamports_info, sync_wsol_account, transfer_sol};
+use crate::utils::{close_ephemeral_wsol, log_sa_lamports_info, sync_wsol_account, transfer_sol};
 use crate::{
     BUY_EXACT_IN_SELECTOR, HopAccounts, MIN_SOL_ACCOUNT_RENT, SA_AUTHORITY_SEED,
     SELL_EXACT_IN_SELECTOR, SOL_DIFF_LIMIT, ZERO_ADDRESS, authority_pda, sugar_money_program,
-    wsol_sa,
 };
 
 use anchor_spl::token::Token;
@@ -59,30 +58,7 @@ impl DexProcessor for SugarMoneyBuyProcessor {
         let source_token_account = account_infos.last().unwrap();
         let token_program = account_infos.get(9).unwrap();
         let authority = account_infos.get(6).unwrap();
-        if authority.key() == authority_pda::ID {
-            let before_sa_authority_lamports = authority.lamports();
-            require!(
-                source_token_account.key() != wsol_sa::ID,
-                ErrorCode::InvalidSourceTokenAccount
-            );
-            close_token_account(
-                source_token_account.to_account_info(),
-                authority.to_account_info(),
-                authority.to_account_info(),
-                token_program.to_account_info(),
-                Some(SA_AUTHORITY_SEED),
-            )?;
-            Ok(before_sa_authority_lamports)
-        } else {
-            close_token_account(
-                source_token_account.to_account_info(),
-                authority.to_account_info(),
-                authority.to_account_info(),
-                token_program.to_account_info(),
-                None,
-            )?;
-            Ok(0)
-        }
+        close_ephemeral_wsol(source_token_account, authority, token_program, None)
     }
 
     fn after_invoke(
@@ -10,6 +10,9 @@ pub const COMMISSION_DENOMINATOR: u64 = 10_000;
 
 pub const COMMISSION_RATE_LIMIT_V2: u32 = 100_000_000; // 10%
 pub const COMMISSION_DENOMINATOR_V2: u64 = 1_000_000_000;
+pub const COMMISSION_FLAG_NATIVE_SOL: u32 = 1 << 29; // swap_v3: the payer sells native SOL
+pub const NATIVE_SOL_WSOL_SEED: u32 = 0; // swap_v3: the ephemeral wSOL account selling native SOL
+pub const MAX_COMMISSION_SPLITS: usize = 5;
 
 pub const PLATFORM_FEE_RATE_LIMIT_V2: u64 = 1_000_000_000; // 100%
 pub const PLATFORM_FEE_DENOMINATOR_V2: u64 = 1_000_000_000;
@@ -97,14 +100,46 @@ pub const BUY_EXACT_QUOTE_IN_SELECTOR: &[u8; 8] = &[198, 46, 21, 82, 180, 217, 2
 
 // ******************** Limit Order ******************** //
 pub const GLOBAL_CONFIG_SEED: &str = "global_config";
-pub const ORDER_V1_SEED: &str = "order_v1";
+pub const ORDER_V1_SEED: &str = "order_v1"; //shared by OrderV2, migrated orders keep their address
 pub const ESCROW_TOKEN_SEED: &str = "escrow_token";
 pub const MIN_DEADLINE: u64 = 300; //min order deadline: 5 minutes
 pub const SIGNATURE_FEE: u64 = 5000;
-pub const ORDER_MIN_RENT: u64 = 3563520; //needs to be changed when order account size is changed
 pub const DEFAULT_COMPUTE_UNIT_LIMIT: u32 = 200_000;
 pub const FEE_MULTIPLIER_DENOMINATOR: u64 = 10;
 
+// ******************** Integrator ******************** //
+pub const INTEGRATOR_SEED: &str = "integrator";
+pub const MAX_FEE_RECIPIENTS: usize = 5;
+pub const MAX_FEE_TIERS: usize = 4;
+pub const USER_VOLUME_SEED: &str = "user_volume";
+
+// ******************** Swap V4 ******************** //
+pub const SWAP_V4_VERSION: u8 = 4;
+pub const SWAP_V4_FLAG_ACC_CLOSE: u8 = 1 << 0;
+pub const SWAP_V4_FLAGS_MASK: u8 = SWAP_V4_FLAG_ACC_CLOSE;
+
+// ******************** Mint Policy ******************** //
+pub const ROUTER_CONFIG_SEED: &str = "router_config";
+pub const MAX_BASIS_POINTS: u16 = 10_000;
+pub const DEFAULT_MAX_TRANSFER_FEE_BPS: u16 = 100; // 1%
+pub const MINT_RISK_PERMANENT_DELEGATE: u8 = 1 << 0;
+pub const MINT_RISK_NON_TRANSFERABLE: u8 = 1 << 1;
+pub const MINT_RISK_DEFAULT_FROZEN: u8 = 1 << 2;
+pub const MINT_RISK_HIGH_TRANSFER_FEE: u8 = 1 << 3;
+
+// ******************** Claim ******************** //
+pub const MAX_CLAIM_RECEIVERS: usize = 5;
+pub const MAX_CLAIMERS: usize = 5;
+/// The window of the claimer max_amount
+pub const CLAIM_PERIOD_SECONDS: i64 = 86_400;
+/// (source SA token account, destination ATA, mint, token program, claim ledger) per claimed mint
+pub const CLAIM_BATCH_ACCOUNTS_LEN: usize = 5;
+pub const CLAIM_LEDGER_SEED: &str = "claim_ledger";
+
+// ******************** Fee Vault ******************** //
+pub const FEE_VAULT_SEED: &str = "fee_vault";
+pub const FEE_ACCRUAL_SEED: &str = "fee_accrual";
+
 #[cfg(feature = "staging")]
 pub mod authority_pda {
     use anchor_lang::declare_id;
@@ -178,6 +213,21 @@ pub mod wsol_program {
     declare_id!("So11111111111111111111111111111111111111112");
 }
 
+pub mod wsol_2022_program {
+    use anchor_lang::declare_id;
+    declare_id!("9pan9bMn5HatX4EJdBwg9VgCa7Uz5HL8N1m5D3NdXejP");
+}
+
+pub mod memo_program {
+    use anchor_lang::declare_id;
+    declare_id!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+}
+
+pub mod usdc_mint {
+    use anchor_lang::declare_id;
+    declare_id!("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v");
+}
+
 //system program address, backend uses this address to represent native sol
 pub mod system_program {
     use anchor_lang::declare_id;
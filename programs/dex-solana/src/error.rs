@@ -224,9 +224,11 @@ pub enum ErrorCode {
     #[msg("Insufficient balance for transfer")]
     InsufficientBalance,
 
+    // Unused, kept so the codes of the later variants do not change
     #[msg("SOL receiver requires acc_close_flag to be true")]
     SolReceiverRequiresAccCloseFlag,
 
+    // Unused, kept so the codes of the later variants do not change
     #[msg("Destination must be wSOL when sol_receiver is specified")]
     DestinationMustBeWsolForSolReceiver,
 
@@ -235,6 +237,132 @@ pub enum ErrorCode {
 
     #[msg("Invalid trim amount")]
     InvalidTrimAmount,
+
+    #[msg("Invalid integrator")]
+    InvalidIntegrator,
+
+    #[msg("Integrator is disabled")]
+    IntegratorDisabled,
+
+    #[msg("Commission rate exceeds integrator limit")]
+    CommissionRateExceedsIntegratorLimit,
+
+    #[msg("Platform fee share mismatch with integrator")]
+    PlatformFeeShareMismatch,
+
+    #[msg("Fee recipient not allowed by integrator")]
+    FeeRecipientNotAllowed,
+
+    #[msg("Fee recipient is exist")]
+    FeeRecipientIsExist,
+
+    #[msg("Fee recipient is not exist")]
+    FeeRecipientIsNotExist,
+
+    #[msg("Exceed fee recipient limit")]
+    ExceedFeeRecipientLimit,
+
+    #[msg("Invalid commission splits")]
+    InvalidCommissionSplits,
+
+    #[msg("Duplicate commission recipient")]
+    DuplicateCommissionRecipient,
+
+    #[msg("Invalid fee vault")]
+    InvalidFeeVault,
+
+    #[msg("Invalid fee accrual")]
+    InvalidFeeAccrual,
+
+    #[msg("No fees to withdraw")]
+    NoFeesToWithdraw,
+
+    #[msg("Invalid fee settlement mint")]
+    InvalidFeeSettlementMint,
+
+    #[msg("Invalid fee tier")]
+    InvalidFeeTier,
+
+    #[msg("Invalid user volume")]
+    InvalidUserVolume,
+
+    #[msg("Invalid user share rate")]
+    InvalidUserShareRate,
+
+    #[msg("Invalid swap args version")]
+    InvalidSwapArgsVersion,
+
+    #[msg("Invalid swap flags")]
+    InvalidSwapFlags,
+
+    #[msg("Swap deadline exceeded")]
+    SwapDeadlineExceeded,
+
+    #[msg("Invalid transfer hook accounts")]
+    InvalidHookAccounts,
+
+    #[msg("Invalid mint policy")]
+    InvalidMintPolicy,
+
+    #[msg("Mint rejected by the mint policy")]
+    MintRiskRejected,
+
+    #[msg("Memo program account is required by the destination account")]
+    MemoProgramRequired,
+
+    #[msg("Native SOL source requires a wSOL source mint")]
+    NativeSolSourceRequiresWsol,
+
+    #[msg("Invalid receiver token account")]
+    InvalidReceiverTokenAccount,
+
+    #[msg("System program is none")]
+    SystemProgramIsNone,
+
+    #[msg("Claim receiver is exist")]
+    ClaimReceiverIsExist,
+
+    #[msg("Claim receiver is not exist")]
+    ClaimReceiverIsNotExist,
+
+    #[msg("Exceed claim receiver limit")]
+    ExceedClaimReceiverLimit,
+
+    #[msg("Receiver is not allowed to claim")]
+    ClaimReceiverNotAllowed,
+
+    #[msg("Invalid claim batch accounts")]
+    InvalidClaimBatchAccounts,
+
+    #[msg("Claimer is not exist")]
+    ClaimerIsNotExist,
+
+    #[msg("Exceed claimer limit")]
+    ExceedClaimerLimit,
+
+    #[msg("Mint is not allowed for the claimer")]
+    ClaimMintNotAllowed,
+
+    #[msg("Trim rate exceeds integrator limit")]
+    TrimRateExceedsIntegratorLimit,
+
+    #[msg("Integrator is required for the fees")]
+    IntegratorRequired,
+
+    #[msg("Native SOL source is not supported by this swap")]
+    NativeSolSourceUnsupported,
+
+    #[msg("Claim amount exceeds the claimer limit")]
+    ClaimAmountExceedsLimit,
+
+    #[msg("Duplicate mint in the claim batch")]
+    DuplicateClaimMint,
+
+    #[msg("Invalid claim ledger")]
+    InvalidClaimLedger,
+
+    #[msg("Claimer limit requires a mint")]
+    ClaimerLimitRequiresMint,
 }
 
 #[error_code]
@@ -304,4 +432,34 @@ pub enum LimitOrderError {
 
     #[msg("Invalid input token account")]
     InvalidInputTokenAccount,
+
+    #[msg("Invalid commission rate")]
+    InvalidCommissionRate,
+
+    #[msg("Invalid commission token account")]
+    InvalidCommissionTokenAccount,
+
+    #[msg("Commission is locked by the order")]
+    CommissionLocked,
+
+    #[msg("Commission info mismatch")]
+    CommissionMismatch,
+
+    #[msg("Orders not matched")]
+    OrdersNotMatched,
+
+    #[msg("Order not started")]
+    OrderNotStarted,
+
+    #[msg("Post-only order can only be matched")]
+    PostOnlyOrder,
+
+    #[msg("Invalid order flags")]
+    InvalidOrderFlags,
+
+    #[msg("Invalid order version")]
+    InvalidOrderVersion,
+
+    #[msg("Invalid escrow token account")]
+    InvalidEscrowTokenAccount,
 }
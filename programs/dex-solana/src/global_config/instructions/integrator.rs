@@ -0,0 +1,187 @@
+use crate::constants::*;
+use crate::program::DexSolana;
+use crate::state::{event::*, integrator::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(integrator_id: u64)]
+pub struct InitIntegrator<'info> {
+    /// The program upgrade authority.
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// Integrator state account to store the commission agreement of a partner.
+    #[account(
+        init,
+        seeds = [
+            INTEGRATOR_SEED.as_bytes(),
+            &integrator_id.to_le_bytes(),
+        ],
+        bump,
+        payer = admin,
+        space = Integrator::LEN
+    )]
+    pub integrator: AccountLoader<'info, Integrator>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, DexSolana>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()))]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateIntegrator<'info> {
+    /// The program upgrade authority.
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            INTEGRATOR_SEED.as_bytes(),
+            &integrator.load()?.integrator_id.to_le_bytes(),
+        ],
+        bump = integrator.load()?.bump,
+    )]
+    pub integrator: AccountLoader<'info, Integrator>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, DexSolana>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()))]
+    pub program_data: Account<'info, ProgramData>,
+}
+
+pub fn init_integrator_handler(
+    ctx: Context<InitIntegrator>,
+    integrator_id: u64,
+    max_commission_rate: u32,
+    platform_fee_share: u16,
+) -> Result<()> {
+    let integrator = &mut ctx.accounts.integrator.load_init()?;
+    integrator.bump = ctx.bumps.integrator;
+    integrator.integrator_id = integrator_id;
+    integrator.enabled = true;
+    integrator.fee_recipients = [Pubkey::default(); MAX_FEE_RECIPIENTS];
+    integrator.reference_mint = Pubkey::default();
+    integrator.fee_tiers = [FeeTier::default(); MAX_FEE_TIERS];
+    integrator.platform_fee_recipient = Pubkey::default();
+    integrator.max_trim_rate = 0;
+    integrator.padding = [0u8; 15];
+    integrator.set_max_commission_rate(max_commission_rate)?;
+    integrator.set_platform_fee_share(platform_fee_share)?;
+    emit_update_integrator_event(integrator);
+    Ok(())
+}
+
+pub fn set_integrator_max_commission_rate_handler(
+    ctx: Context<UpdateIntegrator>,
+    max_commission_rate: u32,
+) -> Result<()> {
+    let integrator = &mut ctx.accounts.integrator.load_mut()?;
+    integrator.set_max_commission_rate(max_commission_rate)?;
+    emit_update_integrator_event(integrator);
+    Ok(())
+}
+
+pub fn set_integrator_platform_fee_share_handler(
+    ctx: Context<UpdateIntegrator>,
+    platform_fee_share: u16,
+) -> Result<()> {
+    let integrator = &mut ctx.accounts.integrator.load_mut()?;
+    integrator.set_platform_fee_share(platform_fee_share)?;
+    emit_update_integrator_event(integrator);
+    Ok(())
+}
+
+pub fn set_integrator_platform_fee_recipient_handler(
+    ctx: Context<UpdateIntegrator>,
+    platform_fee_recipient: Pubkey,
+) -> Result<()> {
+    let integrator = &mut ctx.accounts.integrator.load_mut()?;
+    integrator.set_platform_fee_recipient(platform_fee_recipient)?;
+    emit_update_integrator_event(integrator);
+    Ok(())
+}
+
+pub fn set_integrator_max_trim_rate_handler(
+    ctx: Context<UpdateIntegrator>,
+    max_trim_rate: u8,
+) -> Result<()> {
+    let integrator = &mut ctx.accounts.integrator.load_mut()?;
+    integrator.set_max_trim_rate(max_trim_rate)?;
+    emit_update_integrator_event(integrator);
+    Ok(())
+}
+
+pub fn set_integrator_enabled_handler(ctx: Context<UpdateIntegrator>, enabled: bool) -> Result<()> {
+    let integrator = &mut ctx.accounts.integrator.load_mut()?;
+    integrator.set_enabled(enabled)?;
+    emit_update_integrator_event(integrator);
+    Ok(())
+}
+
+pub fn add_integrator_fee_recipient_handler(
+    ctx: Context<UpdateIntegrator>,
+    recipient: Pubkey,
+) -> Result<()> {
+    let integrator = &mut ctx.accounts.integrator.load_mut()?;
+    integrator.add_fee_recipient(recipient)?;
+    emit_update_integrator_event(integrator);
+    Ok(())
+}
+
+pub fn remove_integrator_fee_recipient_handler(
+    ctx: Context<UpdateIntegrator>,
+    recipient: Pubkey,
+) -> Result<()> {
+    let integrator = &mut ctx.accounts.integrator.load_mut()?;
+    integrator.remove_fee_recipient(recipient)?;
+    emit_update_integrator_event(integrator);
+    Ok(())
+}
+
+pub fn set_integrator_reference_mint_handler(
+    ctx: Context<UpdateIntegrator>,
+    reference_mint: Pubkey,
+) -> Result<()> {
+    let integrator = &mut ctx.accounts.integrator.load_mut()?;
+    integrator.set_reference_mint(reference_mint)?;
+    emit_update_fee_tiers_event(integrator);
+    Ok(())
+}
+
+pub fn set_integrator_fee_tier_handler(
+    ctx: Context<UpdateIntegrator>,
+    index: u8,
+    min_volume: u64,
+    commission_rate: u32,
+) -> Result<()> {
+    let integrator = &mut ctx.accounts.integrator.load_mut()?;
+    integrator.set_fee_tier(index, min_volume, commission_rate)?;
+    emit_update_fee_tiers_event(integrator);
+    Ok(())
+}
+
+fn emit_update_integrator_event(integrator: &Integrator) {
+    emit!(UpdateIntegratorEvent {
+        integrator_id: integrator.integrator_id,
+        enabled: integrator.enabled,
+        max_commission_rate: integrator.max_commission_rate,
+        platform_fee_share: integrator.platform_fee_share,
+        fee_recipients: integrator.fee_recipients,
+        platform_fee_recipient: integrator.platform_fee_recipient,
+        max_trim_rate: integrator.max_trim_rate,
+    });
+}
+
+fn emit_update_fee_tiers_event(integrator: &Integrator) {
+    emit!(UpdateFeeTiersEvent {
+        integrator_id: integrator.integrator_id,
+        reference_mint: integrator.reference_mint,
+        min_volumes: integrator.fee_tiers.map(|tier| tier.min_volume),
+        commission_rates: integrator.fee_tiers.map(|tier| tier.commission_rate),
+    });
+}
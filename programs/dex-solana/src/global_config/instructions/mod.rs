@@ -1,2 +1,6 @@
 pub mod global_config;
+pub mod integrator;
+pub mod router_config;
 pub use global_config::*;
+pub use integrator::*;
+pub use router_config::*;
@@ -0,0 +1,130 @@
+use crate::constants::*;
+use crate::program::DexSolana;
+use crate::state::{event::*, router_config::*};
+use crate::utils::MintPolicy;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct InitRouterConfig<'info> {
+    /// The program upgrade authority.
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// Router config state account to store the swap defaults.
+    #[account(
+        init,
+        seeds = [
+            ROUTER_CONFIG_SEED.as_bytes(),
+        ],
+        bump,
+        payer = admin,
+        space = RouterConfig::LEN
+    )]
+    pub router_config: AccountLoader<'info, RouterConfig>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, DexSolana>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()))]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRouterConfig<'info> {
+    /// The program upgrade authority.
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            ROUTER_CONFIG_SEED.as_bytes(),
+        ],
+        bump = router_config.load()?.bump,
+    )]
+    pub router_config: AccountLoader<'info, RouterConfig>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, DexSolana>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()))]
+    pub program_data: Account<'info, ProgramData>,
+}
+
+pub fn init_router_config_handler(ctx: Context<InitRouterConfig>) -> Result<()> {
+    let router_config = &mut ctx.accounts.router_config.load_init()?;
+    **router_config = RouterConfig::default();
+    router_config.bump = ctx.bumps.router_config;
+    emit_update_router_config_event(&ctx.accounts.admin.key(), router_config);
+    Ok(())
+}
+
+pub fn set_mint_policy_handler(
+    ctx: Context<UpdateRouterConfig>,
+    mint_policy: MintPolicy,
+) -> Result<()> {
+    let router_config = &mut ctx.accounts.router_config.load_mut()?;
+    router_config.set_mint_policy(&mint_policy)?;
+    emit_update_router_config_event(&ctx.accounts.admin.key(), router_config);
+    Ok(())
+}
+
+pub fn set_integrator_required_handler(
+    ctx: Context<UpdateRouterConfig>,
+    integrator_required: bool,
+) -> Result<()> {
+    let router_config = &mut ctx.accounts.router_config.load_mut()?;
+    router_config.set_integrator_required(integrator_required)?;
+    emit_update_router_config_event(&ctx.accounts.admin.key(), router_config);
+    Ok(())
+}
+
+pub fn add_claim_receiver_handler(
+    ctx: Context<UpdateRouterConfig>,
+    receiver: Pubkey,
+) -> Result<()> {
+    let router_config = &mut ctx.accounts.router_config.load_mut()?;
+    router_config.add_claim_receiver(receiver)?;
+    emit!(AddClaimReceiverEvent { receiver });
+    Ok(())
+}
+
+pub fn remove_claim_receiver_handler(
+    ctx: Context<UpdateRouterConfig>,
+    receiver: Pubkey,
+) -> Result<()> {
+    let router_config = &mut ctx.accounts.router_config.load_mut()?;
+    router_config.remove_claim_receiver(receiver)?;
+    emit!(RemoveClaimReceiverEvent { receiver });
+    Ok(())
+}
+
+pub fn set_claimer_handler(
+    ctx: Context<UpdateRouterConfig>,
+    authority: Pubkey,
+    mint: Option<Pubkey>,
+    max_amount: u64,
+) -> Result<()> {
+    let mint = mint.unwrap_or_default();
+    let router_config = &mut ctx.accounts.router_config.load_mut()?;
+    router_config.set_claimer(authority, mint, max_amount)?;
+    emit!(SetClaimerEvent { authority, mint, max_amount });
+    Ok(())
+}
+
+pub fn remove_claimer_handler(ctx: Context<UpdateRouterConfig>, authority: Pubkey) -> Result<()> {
+    let router_config = &mut ctx.accounts.router_config.load_mut()?;
+    router_config.remove_claimer(authority)?;
+    emit!(RemoveClaimerEvent { authority });
+    Ok(())
+}
+
+fn emit_update_router_config_event(admin: &Pubkey, router_config: &RouterConfig) {
+    emit!(UpdateRouterConfigEvent {
+        admin: *admin,
+        mint_risk_action: router_config.mint_risk_action,
+        max_transfer_fee_bps: router_config.max_transfer_fee_bps,
+        integrator_required: router_config.integrator_required,
+    });
+}
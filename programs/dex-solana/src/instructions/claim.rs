@@ -1,18 +1,21 @@
 use crate::error::ErrorCode;
+use crate::state::{
+    claim_ledger::ClaimLedger,
+    event::ClaimEvent,
+    router_config::{Claimer, RouterConfig},
+};
 use crate::utils::{
-    associate_convert_token_account, create_ata_if_needed, is_ata, is_token_account_initialized,
-    log_claim_info_after, log_claim_info_before, transfer_sol, transfer_token,
+    create_ata_if_needed, is_ata, is_token_account_initialized, split_hook_accounts, transfer_sol,
+    transfer_token_with_hook, unpack_mint, unpack_token_account,
 };
 use crate::*;
-use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::associated_token::{AssociatedToken, get_associated_token_address_with_program_id};
 use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
 #[derive(Accounts)]
 pub struct ClaimAccounts<'info> {
-    #[account(
-        mut,
-        address = claim_authority::id() @ ErrorCode::InvalidSigner
-    )]
+    /// A claimer of the router config, or the legacy claim_authority
+    #[account(mut)]
     pub signer: Signer<'info>,
 
     /// CHECK: receiver
@@ -46,9 +49,23 @@ pub struct ClaimAccounts<'info> {
     pub system_program: Program<'info, System>,
 
     pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+
+    /// The router config holding the claimers, only the legacy claim_authority can claim if None
+    #[account(
+        mut,
+        seeds = [
+            ROUTER_CONFIG_SEED.as_bytes(),
+        ],
+        bump = router_config.load()?.bump,
+    )]
+    pub router_config: Option<AccountLoader<'info, RouterConfig>>,
 }
 
 pub fn claim_handler<'a>(ctx: Context<'_, '_, 'a, 'a, ClaimAccounts<'a>>) -> Result<()> {
+    let mut claimer = match &ctx.accounts.router_config {
+        Some(router_config) => router_config.load()?.claimer(ctx.accounts.signer.key)?,
+        None => Claimer::legacy(ctx.accounts.signer.key)?,
+    };
     let destination_account = ctx.accounts.destination_token_account.clone();
 
     if destination_account.is_some()
@@ -76,126 +93,302 @@ pub fn claim_handler<'a>(ctx: Context<'_, '_, 'a, 'a, ClaimAccounts<'a>>) -> Res
     if destination_account.is_some()
         && is_ata(&ctx.accounts.destination_token_account.as_ref().unwrap())
     {
-        handle_token_claim(&ctx)?;
+        handle_token_claim(&ctx, &mut claimer)?;
     } else {
-        handle_sol_claim(&ctx)?;
+        handle_sol_claim(&ctx, &mut claimer)?;
     }
 
+    if let Some(router_config) = &ctx.accounts.router_config {
+        router_config.load_mut()?.save_claimer(&claimer);
+    }
     Ok(())
 }
 
 /// Handle SOL claim logic
-fn handle_sol_claim<'a>(ctx: &Context<'_, '_, 'a, 'a, ClaimAccounts<'a>>) -> Result<()> {
+fn handle_sol_claim<'a>(
+    ctx: &Context<'_, '_, 'a, 'a, ClaimAccounts<'a>>,
+    claimer: &mut Claimer,
+) -> Result<()> {
+    claim_sol(claimer, &ctx.accounts.sa_authority, &ctx.accounts.receiver)?;
+    Ok(())
+}
+
+/// Handle token claim logic
+fn handle_token_claim<'a>(
+    ctx: &Context<'_, '_, 'a, 'a, ClaimAccounts<'a>>,
+    claimer: &mut Claimer,
+) -> Result<()> {
+    // Validate required accounts
+    require!(ctx.accounts.token_mint.is_some(), ErrorCode::InvalidTokenMint);
+    require!(ctx.accounts.source_token_account.is_some(), ErrorCode::InvalidSourceTokenAccount);
+    require!(ctx.accounts.token_program.is_some(), ErrorCode::InvalidTokenProgram);
+
+    // The remaining accounts resolve the transfer hook of the mint
+    claim_token(
+        claimer,
+        &ctx.accounts.sa_authority,
+        &ctx.accounts.receiver,
+        &ctx.accounts.source_token_account.as_ref().unwrap().to_account_info(),
+        ctx.accounts.destination_token_account.as_ref().unwrap(),
+        &ctx.accounts.token_mint.as_ref().unwrap().to_account_info(),
+        &ctx.accounts.token_program.as_ref().unwrap().to_account_info(),
+        ctx.remaining_accounts,
+    )?;
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(mint: Pubkey)]
+pub struct InitClaimLedger<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: the claim receiver
+    pub receiver: UncheckedAccount<'info>,
+
+    /// The claim ledger of the receiver for the mint
+    #[account(
+        init,
+        seeds = [CLAIM_LEDGER_SEED.as_bytes(), receiver.key().as_ref(), mint.as_ref()],
+        bump,
+        payer = payer,
+        space = ClaimLedger::LEN
+    )]
+    pub claim_ledger: AccountLoader<'info, ClaimLedger>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn init_claim_ledger_handler(ctx: Context<InitClaimLedger>, mint: Pubkey) -> Result<()> {
+    let claim_ledger = &mut ctx.accounts.claim_ledger.load_init()?;
+    claim_ledger.bump = ctx.bumps.claim_ledger;
+    claim_ledger.receiver = ctx.accounts.receiver.key();
+    claim_ledger.mint = mint;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ClaimBatchAccounts<'info> {
+    /// A claimer of the router config
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    /// CHECK: receiver, must be allowed by the router config
+    #[account(mut)]
+    pub receiver: AccountInfo<'info>,
+
+    /// CHECK: sa authority
+    #[account(
+        mut,
+        address = authority_pda::id() @ ErrorCode::InvalidAuthorityPda
+    )]
+    pub sa_authority: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            ROUTER_CONFIG_SEED.as_bytes(),
+        ],
+        bump = router_config.load()?.bump,
+    )]
+    pub router_config: AccountLoader<'info, RouterConfig>,
+
+    pub system_program: Program<'info, System>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// CHECK: the claim ledger of the receiver for the SOL of the SA, required to claim it,
+    /// checked in the handler
+    #[account(mut)]
+    pub sol_claim_ledger: Option<UncheckedAccount<'info>>,
+}
+
+pub fn claim_batch_handler<'a>(
+    ctx: Context<'_, '_, 'a, 'a, ClaimBatchAccounts<'a>>,
+    claim_sol_balance: bool,
+    hook_accounts_len: u8,
+) -> Result<()> {
+    let receiver = &ctx.accounts.receiver;
+    let mut claimer = {
+        let router_config = ctx.accounts.router_config.load()?;
+        require!(
+            router_config.is_claim_receiver(receiver.key()),
+            ErrorCode::ClaimReceiverNotAllowed
+        );
+        router_config.claimer(ctx.accounts.signer.key)?
+    };
+    let (claim_accounts, hook_accounts) =
+        split_hook_accounts(ctx.remaining_accounts, hook_accounts_len)?;
     require!(
-        ctx.accounts.sa_authority.lamports() > MIN_SOL_ACCOUNT_RENT,
-        ErrorCode::InsufficientFunds
+        claim_accounts.len() % CLAIM_BATCH_ACCOUNTS_LEN == 0
+            && (claim_sol_balance || !claim_accounts.is_empty()),
+        ErrorCode::InvalidClaimBatchAccounts
     );
 
-    let amount = ctx
-        .accounts
-        .sa_authority
+    let mut claimed_mints = Vec::with_capacity(claim_accounts.len() / CLAIM_BATCH_ACCOUNTS_LEN);
+    for accounts in claim_accounts.chunks(CLAIM_BATCH_ACCOUNTS_LEN) {
+        let [
+            source_token_account,
+            destination_token_account,
+            token_mint,
+            token_program,
+            claim_ledger,
+        ] = accounts
+        else {
+            return Err(ErrorCode::InvalidClaimBatchAccounts.into());
+        };
+        require!(!claimed_mints.contains(token_mint.key), ErrorCode::DuplicateClaimMint);
+        claimed_mints.push(token_mint.key());
+        require!(
+            token_program.key() == token_program::ID
+                || token_program.key() == token_2022_program::ID,
+            ErrorCode::InvalidTokenProgram
+        );
+        require_keys_eq!(
+            destination_token_account.key(),
+            get_associated_token_address_with_program_id(
+                receiver.key,
+                token_mint.key,
+                token_program.key
+            ),
+            ErrorCode::InvalidDestinationTokenAccount
+        );
+        create_ata_if_needed(
+            receiver,
+            &ctx.accounts.signer,
+            destination_token_account,
+            token_mint,
+            token_program,
+            &ctx.accounts.associated_token_program.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        let amount = claim_token(
+            &mut claimer,
+            &ctx.accounts.sa_authority,
+            receiver,
+            source_token_account,
+            destination_token_account,
+            token_mint,
+            token_program,
+            hook_accounts,
+        )?;
+        ClaimLedger::record_to(claim_ledger, receiver.key, token_mint.key, amount)?;
+    }
+
+    if claim_sol_balance {
+        let claim_ledger =
+            ctx.accounts.sol_claim_ledger.as_ref().ok_or(ErrorCode::InvalidClaimLedger)?;
+        let amount = claim_sol(&mut claimer, &ctx.accounts.sa_authority, receiver)?;
+        ClaimLedger::record_to(claim_ledger, receiver.key, &Pubkey::default(), amount)?;
+    }
+    ctx.accounts.router_config.load_mut()?.save_claimer(&claimer);
+    Ok(())
+}
+
+/// Claim the SOL of the SA above MIN_SOL_ACCOUNT_RENT to the receiver, up to the claimer limit,
+/// returns the claimed amount
+fn claim_sol<'a>(
+    claimer: &mut Claimer,
+    sa_authority: &AccountInfo<'a>,
+    receiver: &AccountInfo<'a>,
+) -> Result<u64> {
+    require!(sa_authority.lamports() > MIN_SOL_ACCOUNT_RENT, ErrorCode::InsufficientFunds);
+
+    let balance = sa_authority
         .lamports()
         .checked_sub(MIN_SOL_ACCOUNT_RENT)
         .ok_or(ErrorCode::InsufficientFunds)?;
+    let now = Clock::get()?.unix_timestamp;
+    let amount = claimer.claimable_amount(&wsol_program::ID, balance, now)?;
+    claimer.record_claim(amount, now)?;
 
     // Record balances before transfer
-    let before_authority_balance = ctx.accounts.sa_authority.lamports();
-    let before_receiver_balance = ctx.accounts.receiver.lamports();
-
-    log_claim_info_before(before_authority_balance, before_receiver_balance, amount);
+    let before_authority_balance = sa_authority.lamports();
+    let before_receiver_balance = receiver.lamports();
 
     // Execute SOL transfer
-    transfer_sol(
-        ctx.accounts.sa_authority.to_account_info(),
-        ctx.accounts.receiver.to_account_info(),
-        amount,
-        Some(SA_AUTHORITY_SEED),
-    )?;
-
-    let after_authority_balance = ctx.accounts.sa_authority.lamports();
-    let after_receiver_balance = ctx.accounts.receiver.lamports();
+    transfer_sol(sa_authority.clone(), receiver.clone(), amount, Some(SA_AUTHORITY_SEED))?;
 
     let authority_sol_change = before_authority_balance
-        .checked_sub(after_authority_balance)
+        .checked_sub(sa_authority.lamports())
         .ok_or(ErrorCode::CalculationError)?;
-    let receiver_sol_change = after_receiver_balance
+    let receiver_sol_change = receiver
+        .lamports()
         .checked_sub(before_receiver_balance)
         .ok_or(ErrorCode::CalculationError)?;
 
-    log_claim_info_after(
-        after_authority_balance,
-        after_receiver_balance,
-        authority_sol_change,
-        receiver_sol_change,
-    );
-
-    Ok(())
+    emit!(ClaimEvent {
+        claimer: claimer.authority,
+        receiver: receiver.key(),
+        mint: Pubkey::default(),
+        source: sa_authority.key(),
+        destination: receiver.key(),
+        amount,
+        source_change: authority_sol_change,
+        destination_change: receiver_sol_change,
+    });
+    Ok(amount)
 }
 
-/// Handle token claim logic
-fn handle_token_claim<'a>(ctx: &Context<'_, '_, 'a, 'a, ClaimAccounts<'a>>) -> Result<()> {
-    // Validate required accounts
-    require!(ctx.accounts.token_mint.is_some(), ErrorCode::InvalidTokenMint);
-    require!(ctx.accounts.source_token_account.is_some(), ErrorCode::InvalidSourceTokenAccount);
-    require!(ctx.accounts.token_program.is_some(), ErrorCode::InvalidTokenProgram);
-
-    // Extract accounts
-    let mut source_token_account = ctx.accounts.source_token_account.as_ref().unwrap().clone();
-    let mut destination_token_account =
-        associate_convert_token_account(&ctx.accounts.destination_token_account.as_ref().unwrap())
-            .map_err(|_| ErrorCode::InvalidTokenAccount)?;
-    let token_mint = ctx.accounts.token_mint.as_ref().unwrap();
-    let token_program = ctx.accounts.token_program.as_ref().unwrap();
-
+/// Claim the balance of an SA token account to the token account of the receiver, up to the
+/// claimer limit, returns the claimed amount
+fn claim_token<'a>(
+    claimer: &mut Claimer,
+    sa_authority: &AccountInfo<'a>,
+    receiver: &AccountInfo<'a>,
+    source_token_account: &AccountInfo<'a>,
+    destination_token_account: &AccountInfo<'a>,
+    token_mint: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    hook_accounts: &[AccountInfo<'a>],
+) -> Result<u64> {
+    let source_state = unpack_token_account(source_token_account)?;
     require!(
-        destination_token_account.owner == ctx.accounts.receiver.key(),
-        ErrorCode::InvalidDestinationTokenAccount
+        source_state.owner == sa_authority.key() && source_state.mint == token_mint.key(),
+        ErrorCode::InvalidSourceTokenAccount
     );
-    require!(source_token_account.amount > 0, ErrorCode::InsufficientFunds);
+    // The destination must be an unfrozen account of the receiver for the claimed mint
+    let destination_state = unpack_token_account(destination_token_account)?;
     require!(
-        source_token_account.mint == destination_token_account.mint,
-        ErrorCode::InvalidTokenMint
+        destination_state.owner == receiver.key() && !destination_state.is_frozen(),
+        ErrorCode::InvalidDestinationTokenAccount
     );
-
-    let amount = source_token_account.amount;
-
-    // Record balances before transfer
-    let before_source_balance = source_token_account.amount;
-    let before_destination_balance = destination_token_account.amount;
-
-    log_claim_info_before(before_source_balance, before_destination_balance, amount);
-
-    transfer_token(
-        ctx.accounts.sa_authority.to_account_info(),
-        source_token_account.to_account_info(),
-        destination_token_account.to_account_info(),
-        token_mint.to_account_info(),
-        token_program.to_account_info(),
+    require!(source_state.amount > 0, ErrorCode::InsufficientFunds);
+    require!(source_state.mint == destination_state.mint, ErrorCode::InvalidTokenMint);
+
+    let now = Clock::get()?.unix_timestamp;
+    let amount = claimer.claimable_amount(&source_state.mint, source_state.amount, now)?;
+    claimer.record_claim(amount, now)?;
+    transfer_token_with_hook(
+        sa_authority.clone(),
+        source_token_account.clone(),
+        destination_token_account.clone(),
+        token_mint.clone(),
+        token_program.clone(),
         amount,
-        token_mint.decimals,
+        unpack_mint(token_mint)?.decimals,
         Some(SA_AUTHORITY_SEED),
+        hook_accounts,
     )?;
 
-    source_token_account.reload()?;
-    destination_token_account.reload()?;
-
-    let after_source_balance = source_token_account.amount;
-    let after_destination_balance = destination_token_account.amount;
-
-    let source_token_change = before_source_balance
-        .checked_sub(after_source_balance)
+    let source_token_change = source_state
+        .amount
+        .checked_sub(unpack_token_account(source_token_account)?.amount)
         .ok_or(ErrorCode::CalculationError)?;
-    let destination_token_change = after_destination_balance
-        .checked_sub(before_destination_balance)
+    let destination_token_change = unpack_token_account(destination_token_account)?
+        .amount
+        .checked_sub(destination_state.amount)
         .ok_or(ErrorCode::CalculationError)?;
 
-    // Log transfer results
-    log_claim_info_after(
-        after_source_balance,
-        after_destination_balance,
-        source_token_change,
-        destination_token_change,
-    );
-
-    Ok(())
+    emit!(ClaimEvent {
+        claimer: claimer.authority,
+        receiver: receiver.key(),
+        mint: token_mint.key(),
+        source: source_token_account.key(),
+        destination: destination_token_account.key(),
+        amount,
+        source_change: source_token_change,
+        destination_change: destination_token_change,
+    });
+    Ok(amount)
 }
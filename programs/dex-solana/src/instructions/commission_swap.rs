@@ -1,16 +1,16 @@
 use crate::constants::*;
 use crate::error::ErrorCode;
 use crate::processor::swap_processor::SwapProcessor;
-use crate::utils::token::{close_token_account, transfer_sol, transfer_token};
+use crate::utils::fee_policy::{FeePolicy, FeeRecipients, FeeSource};
+use crate::utils::token::{TransferContext, close_token_account};
 use crate::{
-    COMMISSION_DENOMINATOR, COMMISSION_RATE_LIMIT, CommissionSwapArgs, CommonCommissionProcessor,
-    SwapArgs, common_commission_sol_swap,
+    COMMISSION_RATE_LIMIT, CommissionSwapArgs, CommonCommissionProcessor, SwapArgs,
+    common_commission_sol_swap,
 };
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
 use super::common_commission_token_swap;
-use crate::utils::logging::log_commission_info;
 
 pub struct CommissionProcessor;
 impl<'info> CommonCommissionProcessor<'info> for CommissionProcessor {
@@ -36,17 +36,14 @@ impl<'info> CommonCommissionProcessor<'info> for CommissionProcessor {
             commission_rate > 0 && commission_rate <= COMMISSION_RATE_LIMIT,
             ErrorCode::InvalidCommissionRate
         );
-        let commission_amount = if commission_direction {
+        let fee_policy = FeePolicy::v1(commission_rate, commission_direction);
+        let fees = if commission_direction {
             // Commission direction: true-fromToken
             require!(
                 source_mint.key() == wsol_program::ID,
                 ErrorCode::InvalidCommissionTokenAccount
             );
-            amount_in
-                .checked_mul(commission_rate as u64)
-                .ok_or(ErrorCode::CalculationError)?
-                .checked_div(COMMISSION_DENOMINATOR - commission_rate as u64)
-                .ok_or(ErrorCode::CalculationError)?
+            fee_policy.fee_amounts(amount_in)?
         } else {
             // Commission direction: false-toToken
             require!(
@@ -65,22 +62,22 @@ impl<'info> CommonCommissionProcessor<'info> for CommissionProcessor {
                 )?;
             }
 
-            amount_out
-                .checked_mul(commission_rate as u64)
-                .ok_or(ErrorCode::CalculationError)?
-                .checked_div(COMMISSION_DENOMINATOR)
-                .ok_or(ErrorCode::CalculationError)?
+            fee_policy.fee_amounts(amount_out)?
         };
 
         // Transfer commission_amount
-        transfer_sol(
-            payer.to_account_info(),
-            commission_account.to_account_info(),
-            commission_amount,
-            None,
-        )?;
-        log_commission_info(commission_direction, commission_amount, 0);
-        Ok(())
+        let source = FeeSource {
+            authority: payer,
+            token_account: None,
+            token_mint: source_mint,
+            token_program: None,
+            signer_seeds: None,
+            is_charge_sol: true,
+            legacy_transfer: true,
+            transfer_ctx: TransferContext::default(),
+        };
+        FeeRecipients { commission_account: Some(commission_account), ..Default::default() }
+            .pay_fees(&source, &fees, commission_direction)
     }
 
     fn commission_token_process(
@@ -107,55 +104,39 @@ impl<'info> CommonCommissionProcessor<'info> for CommissionProcessor {
             commission_rate > 0 && commission_rate <= COMMISSION_RATE_LIMIT,
             ErrorCode::InvalidCommissionRate
         );
-        let commission_amount = if commission_direction {
+        let fee_policy = FeePolicy::v1(commission_rate, commission_direction);
+        let (fees, token_account, token_mint) = if commission_direction {
             // Commission direction: true-fromToken
             require!(
                 commission_token_account.mint == source_mint.key(),
                 ErrorCode::InvalidCommissionTokenAccount
             );
-            let commission_amount = amount_in
-                .checked_mul(commission_rate as u64)
-                .ok_or(ErrorCode::CalculationError)?
-                .checked_div(COMMISSION_DENOMINATOR - commission_rate as u64)
-                .ok_or(ErrorCode::CalculationError)?;
-
-            transfer_token(
-                payer.to_account_info(),
-                source_token_account.to_account_info(),
-                commission_token_account.to_account_info(),
-                source_mint.to_account_info(),
-                commission_token_program,
-                commission_amount,
-                source_mint.decimals,
-                None,
-            )?;
-            commission_amount
+            (fee_policy.fee_amounts(amount_in)?, source_token_account, source_mint)
         } else {
             // Commission direction: false-toToken
             require!(
                 commission_token_account.mint == destination_mint.key(),
                 ErrorCode::InvalidCommissionTokenAccount
             );
-            let commission_amount = amount_out
-                .checked_mul(commission_rate as u64)
-                .ok_or(ErrorCode::CalculationError)?
-                .checked_div(COMMISSION_DENOMINATOR)
-                .ok_or(ErrorCode::CalculationError)?;
+            (fee_policy.fee_amounts(amount_out)?, destination_token_account, destination_mint)
+        };
 
-            transfer_token(
-                payer.to_account_info(),
-                destination_token_account.to_account_info(),
-                commission_token_account.to_account_info(),
-                destination_mint.to_account_info(),
-                commission_token_program,
-                commission_amount,
-                destination_mint.decimals,
-                None,
-            )?;
-            commission_amount
+        let token_account = token_account.to_account_info();
+        let source = FeeSource {
+            authority: payer,
+            token_account: Some(&token_account),
+            token_mint,
+            token_program: Some(&commission_token_program),
+            signer_seeds: None,
+            is_charge_sol: false,
+            legacy_transfer: true,
+            transfer_ctx: TransferContext::default(),
         };
-        log_commission_info(commission_direction, commission_amount, 0);
-        Ok(())
+        FeeRecipients {
+            commission_account: Some(&commission_token_account.to_account_info()),
+            ..Default::default()
+        }
+        .pay_fees(&source, &fees, commission_direction)
     }
 }
 
@@ -1,11 +1,10 @@
 use crate::error::ErrorCode;
-use crate::utils::logging::{
-    log_commission_info, log_swap_balance_before, log_swap_basic_info, log_swap_end,
-};
-use crate::utils::token::{close_token_account, sync_wsol_account, transfer_sol, transfer_token};
-use crate::{
-    COMMISSION_DENOMINATOR, COMMISSION_RATE_LIMIT, SEED_TEMP_WSOL, system_program, wsol_program,
+use crate::utils::fee_policy::{FeePolicy, FeeRecipients, FeeSource};
+use crate::utils::logging::{log_swap_balance_before, log_swap_basic_info, log_swap_end};
+use crate::utils::token::{
+    TransferContext, close_token_account, sync_wsol_account, transfer_sol, transfer_token,
 };
+use crate::{COMMISSION_RATE_LIMIT, SEED_TEMP_WSOL, system_program, wsol_program};
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
@@ -83,25 +82,16 @@ pub fn commission_wrap_unwrap_handler<'a>(
 
     let amount_out = args.amount_in;
 
-    let commission_amount: u64;
-    if args.commission_direction {
+    let fee_policy = FeePolicy::v1(args.commission_rate, args.commission_direction);
+    let fees = if args.commission_direction {
         // Commission direction: true-fromToken
         // Commission for fromToken
-        commission_amount = args
-            .amount_in
-            .checked_mul(args.commission_rate as u64)
-            .ok_or(ErrorCode::CalculationError)?
-            .checked_div(COMMISSION_DENOMINATOR - args.commission_rate as u64)
-            .ok_or(ErrorCode::CalculationError)?;
+        fee_policy.fee_amounts(args.amount_in)?
     } else {
         // Commission direction: false-toToken
         // Commission for toToken
-        commission_amount = amount_out
-            .checked_mul(args.commission_rate as u64)
-            .ok_or(ErrorCode::CalculationError)?
-            .checked_div(COMMISSION_DENOMINATOR)
-            .ok_or(ErrorCode::CalculationError)?;
-    }
+        fee_policy.fee_amounts(amount_out)?
+    };
 
     if args.wrap_direction {
         wrap_process(
@@ -133,31 +123,29 @@ pub fn commission_wrap_unwrap_handler<'a>(
         before_destination_balance,
     )?;
 
-    if (args.commission_direction && args.wrap_direction)
-        || (!args.commission_direction && !args.wrap_direction)
-    {
-        transfer_sol(
-            ctx.accounts.payer.to_account_info(),
-            ctx.accounts.commission_sol_account.to_account_info(),
-            commission_amount,
-            None,
-        )?;
+    // The commission is paid in SOL when taken from the SOL side of the wrap or unwrap
+    let is_charge_sol = args.commission_direction == args.wrap_direction;
+    let payer_wsol_account = ctx.accounts.payer_wsol_account.to_account_info();
+    let source = FeeSource {
+        authority: &ctx.accounts.payer.to_account_info(),
+        token_account: Some(&payer_wsol_account),
+        token_mint: &ctx.accounts.wsol_mint,
+        token_program: Some(&ctx.accounts.token_program),
+        signer_seeds: None,
+        is_charge_sol,
+        legacy_transfer: true,
+        transfer_ctx: TransferContext::default(),
+    };
+    let commission_account = if is_charge_sol {
+        ctx.accounts.commission_sol_account.to_account_info()
     } else {
-        transfer_token(
-            ctx.accounts.payer.to_account_info(),
-            ctx.accounts.payer_wsol_account.to_account_info(),
-            ctx.accounts.commission_wsol_account.to_account_info(),
-            ctx.accounts.wsol_mint.to_account_info(),
-            ctx.accounts.token_program.to_account_info(),
-            commission_amount,
-            ctx.accounts.wsol_mint.decimals,
-            None,
-        )?;
-    }
-
-    log_commission_info(args.commission_direction, commission_amount, 0);
-
-    Ok(())
+        ctx.accounts.commission_wsol_account.to_account_info()
+    };
+    FeeRecipients { commission_account: Some(&commission_account), ..Default::default() }.pay_fees(
+        &source,
+        &fees,
+        args.commission_direction,
+    )
 }
 
 pub fn wrap_process<'info>(
@@ -2,6 +2,7 @@ use crate::adapters::*;
 use crate::constants::*;
 use crate::error::ErrorCode;
 use crate::processor::*;
+use crate::state::{integrator::Integrator, router_config::RouterConfig, user_volume::UserVolume};
 use crate::utils::*;
 use anchor_lang::prelude::*;
 use anchor_spl::associated_token::AssociatedToken;
@@ -137,6 +138,22 @@ pub struct SwapArgs {
     pub routes: Vec<Vec<Route>>, // 2nd level split route
 }
 
+impl SwapArgs {
+    /// Takes the transfer fee deducted before the swap off the last route, if the amounts were
+    /// split from the gross amount in
+    pub fn net_amounts(&mut self, real_amount_in: u64) -> Result<()> {
+        let deducted_amount = self.amount_in.saturating_sub(real_amount_in);
+        let total_amounts = self.amounts.iter().try_fold(0u64, |acc, &x| acc.checked_add(x));
+        if deducted_amount == 0 || total_amounts != Some(self.amount_in) {
+            return Ok(());
+        }
+        let last_amount = self.amounts.last_mut().ok_or(ErrorCode::CalculationError)?;
+        *last_amount =
+            last_amount.checked_sub(deducted_amount).ok_or(ErrorCode::CalculationError)?;
+        Ok(())
+    }
+}
+
 #[event]
 pub struct SwapEvent {
     pub dex: Dex,
@@ -291,6 +308,22 @@ pub fn common_swap<'info, T: CommonSwapProcessor<'info>>(
     Ok(destination_token_change)
 }
 
+/// The per-swap policy inputs of common_swap_v3
+pub struct SwapPolicyContext<'a, 'info> {
+    /// The router config, the default policy applies if None
+    pub router_config: Option<&'a RouterConfig>,
+    /// The integrator agreement the fees are validated against
+    pub integrator: Option<&'a Integrator>,
+    /// The volume accumulator of the payer, applies the fee tiers of the integrator
+    pub user_volume: Option<&'a AccountLoader<'info, UserVolume>>,
+    /// The policy requested by the swap, can only tighten the configured default
+    pub mint_policy: Option<MintPolicy>,
+    /// The share of the positive slippage paid back to the user
+    pub user_share_rate: Option<u16>,
+    /// The accounts resolving the transfer hooks of the mints
+    pub hook_accounts: &'info [AccountInfo<'info>],
+}
+
 pub fn common_swap_v3<'info, T: PlatformFeeV3Processor<'info>>(
     swap_processor: &T,
     payer: &AccountInfo<'info>,
@@ -306,7 +339,7 @@ pub fn common_swap_v3<'info, T: PlatformFeeV3Processor<'info>>(
     associated_token_program: &Option<Program<'info, AssociatedToken>>,
     system_program: &Option<Program<'info, System>>,
     remaining_accounts: &'info [AccountInfo<'info>],
-    args: SwapArgs,
+    mut args: SwapArgs,
     order_id: u64,
     // COMMISSION
     commission_rate: u32,
@@ -321,7 +354,60 @@ pub fn common_swap_v3<'info, T: PlatformFeeV3Processor<'info>>(
     trim_account: Option<&AccountInfo<'info>>,
     charge_account: Option<&AccountInfo<'info>>,
     acc_close_flag: bool,
+    policy_ctx: SwapPolicyContext<'_, 'info>,
 ) -> Result<u64> {
+    let SwapPolicyContext {
+        router_config,
+        integrator,
+        user_volume,
+        mint_policy,
+        user_share_rate,
+        hook_accounts,
+    } = policy_ctx;
+    let default_router_config = RouterConfig::default();
+    let router_config = router_config.unwrap_or(&default_router_config);
+
+    // Apply the fee tier reached by the user volume, capped by the commission rate passed in
+    let commission_rate = match (integrator, user_volume) {
+        (Some(integrator), Some(user_volume)) => {
+            let user_volume = user_volume.load()?;
+            user_volume.validate(integrator.integrator_id, &payer.key())?;
+            let volume = user_volume.volume;
+            let tier_commission_rate = integrator.tier_commission_rate(volume, commission_rate);
+            msg!("volume: {}, tier commission_rate: {}", volume, tier_commission_rate);
+            tier_commission_rate
+        }
+        _ => commission_rate,
+    };
+
+    // Validate the fees against the integrator agreement, without one only fee free swaps pass
+    // once the router config requires integrators
+    let policy = FeePolicy::v3(commission_rate, commission_direction, platform_fee_rate)
+        .with_trim(trim_rate, charge_rate);
+    match integrator {
+        Some(integrator) => integrator.validate_swap(
+            &policy,
+            commission_account.as_ref(),
+            platform_fee_account.as_ref(),
+            trim_account,
+            charge_account,
+        )?,
+        None => router_config.validate_swap_without_integrator(&policy)?,
+    }
+
+    // Reject or flag the mints with risky extensions, the swap policy can only tighten the
+    // configured default
+    let mint_policy = router_config.swap_mint_policy(mint_policy)?;
+    mint_policy.enforce(&source_mint.to_account_info())?;
+    mint_policy.enforce(&destination_mint.to_account_info())?;
+
+    // The memo written before the transfers into accounts requiring incoming memos
+    let memo = TransferMemo {
+        order_id,
+        integrator_id: integrator.map(|integrator| integrator.integrator_id),
+    };
+    let transfer_ctx = TransferContext { hook_accounts, memo: Some(memo) };
+
     log_swap_basic_info(
         order_id,
         &source_mint.key(),
@@ -380,7 +466,9 @@ pub fn common_swap_v3<'info, T: PlatformFeeV3Processor<'info>>(
         commission_account,
         platform_fee_rate,
         platform_fee_account,
+        &transfer_ctx,
     )?;
+    args.net_amounts(real_amount_in)?;
 
     // Common swap
     let expected_amount_out = args.expect_amount_out;
@@ -413,9 +501,11 @@ pub fn common_swap_v3<'info, T: PlatformFeeV3Processor<'info>>(
         platform_fee_account,
         trim_rate,
         charge_rate,
+        user_share_rate,
         trim_account,
         charge_account,
         acc_close_flag,
+        &transfer_ctx,
     )?;
 
     // source token account has been closed in pumpfun buy
@@ -453,10 +543,22 @@ pub fn common_swap_v3<'info, T: PlatformFeeV3Processor<'info>>(
 
     // Check min return
     require!(destination_token_change >= min_return, ErrorCode::MinReturnNotReached);
+
+    // Accumulate the user volume in the reference mint, other pairs have no notional
+    if let (Some(integrator), Some(user_volume)) = (integrator, user_volume) {
+        let notional = if source_mint.key() == integrator.reference_mint {
+            source_token_change
+        } else if destination_mint.key() == integrator.reference_mint {
+            destination_token_change
+        } else {
+            0
+        };
+        user_volume.load_mut()?.accumulate(notional)?;
+    }
     Ok(destination_token_change)
 }
 
-fn execute_swap<'info>(
+pub(crate) fn execute_swap<'info>(
     source_account: &mut InterfaceAccount<'info, TokenAccount>,
     destination_account: &mut InterfaceAccount<'info, TokenAccount>,
     remaining_accounts: &'info [AccountInfo<'info>],
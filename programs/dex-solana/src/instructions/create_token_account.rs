@@ -1,16 +1,10 @@
 use crate::error::ErrorCode;
-use crate::utils::is_token_account_initialized;
-use crate::{token_program, wsol_program};
+use crate::utils::{is_native_mint, is_token_account_initialized, unpack_token_account};
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::program::invoke_signed;
-use anchor_lang::solana_program::program_pack::Pack;
 use anchor_lang::solana_program::{system_instruction, system_program};
 use anchor_spl::token::TokenAccount;
-use anchor_spl::token::spl_token::state::Account as SplTokenAccount;
-use anchor_spl::{
-    token::Token,
-    token_interface::{self, Mint},
-};
+use anchor_spl::token_interface::{self, Mint, TokenInterface};
 
 #[derive(Accounts)]
 pub struct CreateTokenAccountAccounts<'info> {
@@ -23,16 +17,19 @@ pub struct CreateTokenAccountAccounts<'info> {
     /// CHECK: This is the wsol token account to be created
     #[account(
         mut,
-        constraint = token_account.owner == &system_program::id() || token_account.owner == &token_program::id(),
+        constraint = token_account.owner == &system_program::id() || token_account.owner == &token_program.key(),
         seeds = [token_mint.key().as_ref(), owner.key().as_ref()],
         bump,
     )]
     pub token_account: AccountInfo<'info>,
 
-    #[account(address = wsol_program::id())]
+    #[account(
+        constraint = is_native_mint(&token_mint.key()) @ ErrorCode::InvalidMint,
+        mint::token_program = token_program,
+    )]
     pub token_mint: InterfaceAccount<'info, Mint>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 
     pub system_program: Program<'info, System>,
 }
@@ -55,9 +52,7 @@ pub fn create_token_account_handler<'a>(
 
     // before allocate, check if the account is already initialized
     if is_token_account_initialized(&token_account) {
-        let data = token_account.try_borrow_data()?;
-        let ta = SplTokenAccount::unpack(&data)?;
-
+        let ta = unpack_token_account(&token_account)?;
         require_keys_eq!(ta.owner, owner.key(), ErrorCode::InvalidTokenAccount);
 
         msg!("Token account already initialized");
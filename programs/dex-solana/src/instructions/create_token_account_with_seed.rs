@@ -1,16 +1,10 @@
 use crate::error::ErrorCode;
-use crate::utils::is_token_account_initialized;
-use crate::{token_program, wsol_program};
-use anchor_lang::prelude::*;
-use anchor_lang::solana_program::program::invoke_signed;
-use anchor_lang::solana_program::program_pack::Pack;
-use anchor_lang::solana_program::{system_instruction, system_program};
-use anchor_spl::token::TokenAccount;
-use anchor_spl::token::spl_token::state::Account as SplTokenAccount;
-use anchor_spl::{
-    token::Token,
-    token_interface::{self, Mint},
+use crate::utils::{
+    init_wsol_account_with_seed, is_native_mint, is_token_account_initialized, unpack_token_account,
 };
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::system_program;
+use anchor_spl::token_interface::{Mint, TokenInterface};
 
 #[derive(Accounts)]
 #[instruction(_bump: u8, seed: u32)]
@@ -24,16 +18,19 @@ pub struct CreateTokenAccountWithSeedAccounts<'info> {
     /// CHECK: This is the wsol token account to be created
     #[account(
         mut,
-        constraint = token_account.owner == &system_program::id() || token_account.owner == &token_program::id(),
+        constraint = token_account.owner == &system_program::id() || token_account.owner == &token_program.key(),
         seeds = [token_mint.key().as_ref(), owner.key().as_ref(), &seed.to_le_bytes()],
         bump,
     )]
     pub token_account: AccountInfo<'info>,
 
-    #[account(address = wsol_program::id())]
+    #[account(
+        constraint = is_native_mint(&token_mint.key()) @ ErrorCode::InvalidMint,
+        mint::token_program = token_program,
+    )]
     pub token_mint: InterfaceAccount<'info, Mint>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 
     pub system_program: Program<'info, System>,
 }
@@ -49,40 +46,13 @@ pub fn create_token_account_with_seed_handler<'a>(
     let token_program = ctx.accounts.token_program.to_account_info();
 
     // Allocate/assign/initialize only. Funding (lamports) is done by external instructions.
-    let space = TokenAccount::LEN as u64; // SPL Token account size
-
-    // PDA seeds for the token account
-    let seeds: &[&[u8]] =
-        &[token_mint.key.as_ref(), owner.key.as_ref(), &seed.to_le_bytes(), &[bump]];
-    let signer_seeds: &[&[&[u8]]] = &[seeds];
-
     // before allocate, check if the account is already initialized
     if is_token_account_initialized(&token_account) {
-        let data = token_account.try_borrow_data()?;
-        let ta = SplTokenAccount::unpack(&data)?;
-
+        let ta = unpack_token_account(&token_account)?;
         require_keys_eq!(ta.owner, owner.key(), ErrorCode::InvalidTokenAccount);
 
         msg!("Token account already initialized");
         return Ok(());
     }
-    // 1) allocate space for the account (allocate)
-    let ix_allocate = system_instruction::allocate(token_account.key, space);
-    invoke_signed(&ix_allocate, &[token_account.clone()], signer_seeds)?;
-
-    // 2) assign ownership to the token program (assign)
-    let ix_assign = system_instruction::assign(token_account.key, token_program.key);
-    invoke_signed(&ix_assign, &[token_account.clone()], signer_seeds)?;
-
-    // 3) initialize as token account (initializeAccount3)
-    token_interface::initialize_account3(CpiContext::new(
-        token_program,
-        token_interface::InitializeAccount3 {
-            account: token_account,
-            mint: token_mint,
-            authority: owner,
-        },
-    ))?;
-
-    Ok(())
+    init_wsol_account_with_seed(&token_account, &owner, &token_mint, &token_program, seed, bump)
 }
@@ -0,0 +1,133 @@
+use crate::constants::*;
+use crate::state::{event::*, fee_vault::*};
+use crate::utils::transfer_token_with_hook;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+pub struct InitFeeVault<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: the fee vault authority PDA
+    #[account(
+        seeds = [FEE_VAULT_SEED.as_bytes()],
+        bump,
+    )]
+    pub fee_vault_authority: UncheckedAccount<'info>,
+
+    /// The token account holding the accrued fees of a mint
+    #[account(
+        init,
+        seeds = [FEE_VAULT_SEED.as_bytes(), token_mint.key().as_ref()],
+        bump,
+        payer = payer,
+        token::mint = token_mint,
+        token::authority = fee_vault_authority,
+        token::token_program = token_program,
+    )]
+    pub fee_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_mint: Box<InterfaceAccount<'info, Mint>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitFeeAccrual<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: the fee recipient
+    pub recipient: UncheckedAccount<'info>,
+
+    /// The accrual account of the recipient for a mint
+    #[account(
+        init,
+        seeds = [FEE_ACCRUAL_SEED.as_bytes(), recipient.key().as_ref(), token_mint.key().as_ref()],
+        bump,
+        payer = payer,
+        space = FeeAccrual::LEN
+    )]
+    pub fee_accrual: AccountLoader<'info, FeeAccrual>,
+
+    pub token_mint: Box<InterfaceAccount<'info, Mint>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFees<'info> {
+    pub recipient: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [FEE_ACCRUAL_SEED.as_bytes(), recipient.key().as_ref(), token_mint.key().as_ref()],
+        bump = fee_accrual.load()?.bump,
+    )]
+    pub fee_accrual: AccountLoader<'info, FeeAccrual>,
+
+    /// CHECK: the fee vault authority PDA
+    #[account(
+        seeds = [FEE_VAULT_SEED.as_bytes()],
+        bump,
+    )]
+    pub fee_vault_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [FEE_VAULT_SEED.as_bytes(), token_mint.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = fee_vault_authority,
+        token::token_program = token_program,
+    )]
+    pub fee_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::token_program = token_program,
+    )]
+    pub destination_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_mint: Box<InterfaceAccount<'info, Mint>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn init_fee_vault_handler(_ctx: Context<InitFeeVault>) -> Result<()> {
+    Ok(())
+}
+
+pub fn init_fee_accrual_handler(ctx: Context<InitFeeAccrual>) -> Result<()> {
+    let fee_accrual = &mut ctx.accounts.fee_accrual.load_init()?;
+    fee_accrual.bump = ctx.bumps.fee_accrual;
+    fee_accrual.recipient = ctx.accounts.recipient.key();
+    fee_accrual.mint = ctx.accounts.token_mint.key();
+    Ok(())
+}
+
+pub fn withdraw_fees_handler<'a>(ctx: Context<'_, '_, 'a, 'a, WithdrawFees<'a>>) -> Result<()> {
+    let amount = ctx.accounts.fee_accrual.load_mut()?.withdraw()?;
+
+    let signer_seeds: &[&[&[u8]]] =
+        &[&[FEE_VAULT_SEED.as_bytes(), &[ctx.bumps.fee_vault_authority]]];
+    transfer_token_with_hook(
+        ctx.accounts.fee_vault_authority.to_account_info(),
+        ctx.accounts.fee_vault.to_account_info(),
+        ctx.accounts.destination_token_account.to_account_info(),
+        ctx.accounts.token_mint.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        amount,
+        ctx.accounts.token_mint.decimals,
+        Some(signer_seeds),
+        ctx.remaining_accounts,
+    )?;
+
+    emit!(WithdrawFeesEvent {
+        recipient: ctx.accounts.recipient.key(),
+        mint: ctx.accounts.token_mint.key(),
+        destination: ctx.accounts.destination_token_account.key(),
+        amount,
+    });
+    Ok(())
+}
@@ -9,12 +9,19 @@ pub mod common_commission_v2;
 pub mod common_swap;
 pub mod create_token_account;
 pub mod create_token_account_with_seed;
+pub mod fee_vault;
 pub mod from_swap;
 pub mod platform_fee_proxy_swap_v2;
 pub mod platform_fee_wrap_unwrap_v2;
 pub mod proxy_swap;
+pub mod quote_fees;
 pub mod swap;
+pub mod swap_and_claim;
 pub mod swap_v3;
+pub mod swap_v3_fee_vault;
+pub mod swap_v3_splits;
+pub mod swap_v4;
+pub mod user_volume;
 pub mod wrap_unwrap_v3;
 
 pub use claim::*;
@@ -28,10 +35,17 @@ pub use common_commission_v2::*;
 pub use common_swap::*;
 pub use create_token_account::*;
 pub use create_token_account_with_seed::*;
+pub use fee_vault::*;
 pub use from_swap::*;
 pub use platform_fee_proxy_swap_v2::*;
 pub use platform_fee_wrap_unwrap_v2::*;
 pub use proxy_swap::*;
+pub use quote_fees::*;
 pub use swap::*;
+pub use swap_and_claim::*;
 pub use swap_v3::*;
+pub use swap_v3_fee_vault::*;
+pub use swap_v3_splits::*;
+pub use swap_v4::*;
+pub use user_volume::*;
 pub use wrap_unwrap_v3::*;
@@ -45,21 +45,16 @@ impl<'info> CommonCommissionProcessorV2<'info> for PlatformFeeProcessorV2 {
                 && platform_fee_rate.unwrap() as u64 <= PLATFORM_FEE_RATE_LIMIT_V2,
             ErrorCode::InvalidPlatformFeeRate
         );
+        let fee_policy =
+            FeePolicy::v2(commission_rate, commission_direction, platform_fee_rate.unwrap());
 
-        let commission_amount = if commission_direction {
+        let fees = if commission_direction {
             // Commission direction: true-fromToken
             require!(
                 source_mint.key() == wsol_program::ID,
                 ErrorCode::InvalidCommissionTokenAccount
             );
-            u64::try_from(
-                u128::from(amount_in)
-                    .checked_mul(commission_rate as u128)
-                    .ok_or(ErrorCode::CalculationError)?
-                    .checked_div(COMMISSION_DENOMINATOR_V2 as u128 - commission_rate as u128)
-                    .ok_or(ErrorCode::CalculationError)?,
-            )
-            .unwrap()
+            fee_policy.fee_amounts(amount_in)?
         } else {
             // Commission direction: false-toToken
             require!(
@@ -78,68 +73,28 @@ impl<'info> CommonCommissionProcessorV2<'info> for PlatformFeeProcessorV2 {
                 )?;
             }
 
-            u64::try_from(
-                u128::from(amount_out)
-                    .checked_mul(commission_rate as u128)
-                    .ok_or(ErrorCode::CalculationError)?
-                    .checked_div(COMMISSION_DENOMINATOR_V2 as u128)
-                    .ok_or(ErrorCode::CalculationError)?,
-            )
-            .unwrap()
+            fee_policy.fee_amounts(amount_out)?
         };
 
-        let mut platform_fee_amount: u64 = 0;
-
-        if platform_fee_rate.is_some() && platform_fee_rate.unwrap() > 0 {
-            // Platform fee for fromToken
-            platform_fee_amount = u64::try_from(
-                u128::from(commission_amount)
-                    .checked_mul(platform_fee_rate.unwrap() as u128)
-                    .ok_or(ErrorCode::CalculationError)?
-                    .checked_div(PLATFORM_FEE_DENOMINATOR_V2 as u128)
-                    .ok_or(ErrorCode::CalculationError)?,
-            )
-            .unwrap();
-
-            let sa_account = if commission_direction {
-                source_token_sa.clone()
-            } else {
-                destination_token_sa.clone()
-            };
-
-            let sa_account_key = if sa_account.as_ref().unwrap().owner == &crate::token_program::ID
-            {
-                let sa_account_box =
-                    Box::leak(Box::new(sa_account.as_ref().unwrap().to_account_info()));
-                InterfaceAccount::<TokenAccount>::try_from(sa_account_box).unwrap().owner
-            } else {
-                sa_account.as_ref().unwrap().key()
-            };
-
-            // Transfer platform_fee_amount
-            transfer_sol(
-                payer.to_account_info(),
-                sa_account.unwrap().to_account_info(),
-                platform_fee_amount,
-                None,
-            )?;
-            log_platform_fee_info(platform_fee_amount, 0, &sa_account_key);
+        // The platform fee is paid to the SA account of the commission side
+        let sa_account = if commission_direction { source_token_sa } else { destination_token_sa };
+        let platform_fee_account = sa_account.as_ref().map(|sa| sa.to_account_info());
+        let sol_source = FeeSource {
+            authority: payer,
+            token_account: None,
+            token_mint: destination_mint,
+            token_program: None,
+            signer_seeds: None,
+            is_charge_sol: true,
+            legacy_transfer: true,
+            transfer_ctx: TransferContext::default(),
+        };
+        FeeRecipients {
+            commission_account: Some(commission_account),
+            platform_fee_account: platform_fee_account.as_ref(),
+            ..Default::default()
         }
-
-        // Transfer commission_amount
-        transfer_sol(
-            payer.to_account_info(),
-            commission_account.to_account_info(),
-            commission_amount.checked_sub(platform_fee_amount).unwrap(),
-            None,
-        )?;
-
-        log_commission_info(
-            commission_direction,
-            commission_amount.checked_sub(platform_fee_amount).unwrap(),
-            0,
-        );
-        commission_account.key().log();
+        .pay_fees(&sol_source, &fees, commission_direction)?;
 
         // Trim destionation token
         if trim_account.is_some()
@@ -148,47 +103,25 @@ impl<'info> CommonCommissionProcessorV2<'info> for PlatformFeeProcessorV2 {
             && trim_rate.unwrap() > 0
         {
             require!(trim_rate.unwrap() <= TRIM_RATE_LIMIT_V2, ErrorCode::InvalidTrimRate);
-            let trim_limit = u64::try_from(
-                u128::from(amount_out)
-                    .saturating_mul(trim_rate.unwrap() as u128)
-                    .saturating_div(TRIM_DENOMINATOR_V2 as u128),
-            )
-            .unwrap();
-
-            let trim_amount = if commission_direction {
-                (amount_out.saturating_sub(expected_amount_out)).min(trim_limit)
-            } else {
-                (amount_out.saturating_sub(commission_amount).saturating_sub(expected_amount_out))
-                    .min(trim_limit)
-            };
+            let trim = fee_policy.with_trim(trim_rate, None).trim_breakdown(
+                amount_out,
+                expected_amount_out,
+                &fees,
+            )?;
             // Transfer trim_amount
-            if trim_amount > 0 {
-                if destination_mint.key() == wsol_program::ID {
-                    transfer_sol(
-                        payer.to_account_info(),
-                        trim_account.unwrap().to_account_info(),
-                        trim_amount,
-                        None,
-                    )?;
-                } else {
-                    require!(
-                        destination_token_program.is_some(),
-                        ErrorCode::DestinationTokenProgramIsNone
-                    );
-                    transfer_token(
-                        payer.to_account_info(),
-                        destination_token_account.to_account_info(),
-                        trim_account.unwrap().to_account_info(),
-                        destination_mint.to_account_info(),
-                        destination_token_program.as_ref().unwrap().to_account_info(),
-                        trim_amount,
-                        destination_mint.decimals,
-                        None,
-                    )?;
-                }
-                msg!("trim_amount: {:?}", trim_amount);
-                trim_account.unwrap().key().log();
-            }
+            let is_charge_sol = destination_mint.key() == wsol_program::ID;
+            require!(
+                is_charge_sol || trim.trim_amount == 0 || destination_token_program.is_some(),
+                ErrorCode::DestinationTokenProgramIsNone
+            );
+            let destination_token_account = destination_token_account.to_account_info();
+            let source = FeeSource {
+                token_account: Some(&destination_token_account),
+                token_program: destination_token_program.as_deref(),
+                is_charge_sol,
+                ..sol_source
+            };
+            FeeRecipients { trim_account, ..Default::default() }.pay_trim(&source, &trim)?;
         }
         Ok(())
     }
@@ -227,131 +160,48 @@ impl<'info> CommonCommissionProcessorV2<'info> for PlatformFeeProcessorV2 {
             ErrorCode::InvalidPlatformFeeRate
         );
         require!(commission_rate <= COMMISSION_RATE_LIMIT_V2, ErrorCode::InvalidCommissionRate);
-        let (commission_amount, platform_fee_amount) = if commission_direction {
+        let fee_policy =
+            FeePolicy::v2(commission_rate, commission_direction, platform_fee_rate.unwrap());
+        let (fees, token_account, token_mint, sa_account) = if commission_direction {
             // Commission direction: true-fromToken
             require!(
                 commission_token_account.mint == source_mint.key(),
                 ErrorCode::InvalidCommissionTokenAccount
             );
-            let commission_amount = u64::try_from(
-                u128::from(amount_in)
-                    .checked_mul(commission_rate as u128)
-                    .ok_or(ErrorCode::CalculationError)?
-                    .checked_div(COMMISSION_DENOMINATOR_V2 as u128 - commission_rate as u128)
-                    .ok_or(ErrorCode::CalculationError)?,
-            )
-            .unwrap();
-
-            let platform_fee_amount = u64::try_from(
-                u128::from(commission_amount)
-                    .checked_mul(platform_fee_rate.unwrap() as u128)
-                    .ok_or(ErrorCode::CalculationError)?
-                    .checked_div(PLATFORM_FEE_DENOMINATOR_V2 as u128)
-                    .ok_or(ErrorCode::CalculationError)?,
-            )
-            .unwrap();
-
-            if platform_fee_amount > 0 {
-                transfer_token(
-                    payer.to_account_info(),
-                    source_token_account.to_account_info(),
-                    source_token_sa.as_ref().unwrap().to_account_info(),
-                    source_mint.to_account_info(),
-                    commission_token_program.clone(),
-                    platform_fee_amount,
-                    source_mint.decimals,
-                    None,
-                )?;
-                let sa_account_key = if source_token_sa.as_ref().unwrap().owner
-                    == &crate::token_program::ID
-                {
-                    let sa_account_box =
-                        Box::leak(Box::new(source_token_sa.as_ref().unwrap().to_account_info()));
-                    InterfaceAccount::<TokenAccount>::try_from(sa_account_box).unwrap().owner
-                } else {
-                    source_token_sa.as_ref().unwrap().key()
-                };
-                log_platform_fee_info(platform_fee_amount, 0, &sa_account_key);
-            }
-
-            transfer_token(
-                payer.to_account_info(),
-                source_token_account.to_account_info(),
-                commission_token_account.to_account_info(),
-                source_mint.to_account_info(),
-                commission_token_program,
-                commission_amount.checked_sub(platform_fee_amount).unwrap(),
-                source_mint.decimals,
-                None,
-            )?;
-            (commission_amount, platform_fee_amount)
+            (fee_policy.fee_amounts(amount_in)?, source_token_account, source_mint, source_token_sa)
         } else {
             // Commission direction: false-toToken
             require!(
                 commission_token_account.mint == destination_mint.key(),
                 ErrorCode::InvalidCommissionTokenAccount
             );
-            let commission_amount = u64::try_from(
-                u128::from(amount_out)
-                    .checked_mul(commission_rate as u128)
-                    .ok_or(ErrorCode::CalculationError)?
-                    .checked_div(COMMISSION_DENOMINATOR_V2 as u128)
-                    .ok_or(ErrorCode::CalculationError)?,
-            )
-            .unwrap();
-
-            let platform_fee_amount = u64::try_from(
-                u128::from(commission_amount)
-                    .checked_mul(platform_fee_rate.unwrap() as u128)
-                    .ok_or(ErrorCode::CalculationError)?
-                    .checked_div(PLATFORM_FEE_DENOMINATOR_V2 as u128)
-                    .ok_or(ErrorCode::CalculationError)?,
+            (
+                fee_policy.fee_amounts(amount_out)?,
+                destination_token_account,
+                destination_mint,
+                destination_token_sa,
             )
-            .unwrap();
-
-            if platform_fee_amount > 0 {
-                transfer_token(
-                    payer.to_account_info(),
-                    destination_token_account.to_account_info(),
-                    destination_token_sa.as_ref().unwrap().to_account_info(),
-                    destination_mint.to_account_info(),
-                    commission_token_program.clone(),
-                    platform_fee_amount,
-                    destination_mint.decimals,
-                    None,
-                )?;
-                let sa_account_key =
-                    if destination_token_sa.as_ref().unwrap().owner == &crate::token_program::ID {
-                        let sa_account_box = Box::leak(Box::new(
-                            destination_token_sa.as_ref().unwrap().to_account_info(),
-                        ));
-                        InterfaceAccount::<TokenAccount>::try_from(sa_account_box).unwrap().owner
-                    } else {
-                        destination_token_sa.as_ref().unwrap().key()
-                    };
-                log_platform_fee_info(platform_fee_amount, 0, &sa_account_key);
-            }
-
-            transfer_token(
-                payer.to_account_info(),
-                destination_token_account.to_account_info(),
-                commission_token_account.to_account_info(),
-                destination_mint.to_account_info(),
-                commission_token_program,
-                commission_amount.checked_sub(platform_fee_amount).unwrap(),
-                destination_mint.decimals,
-                None,
-            )?;
-
-            (commission_amount, platform_fee_amount)
         };
 
-        log_commission_info(
-            commission_direction,
-            commission_amount.checked_sub(platform_fee_amount).unwrap(),
-            0,
-        );
-        commission_token_account.key().log();
+        // The platform fee is paid to the SA account of the commission side
+        let token_account = token_account.to_account_info();
+        let platform_fee_account = sa_account.as_ref().map(|sa| sa.to_account_info());
+        let source = FeeSource {
+            authority: payer,
+            token_account: Some(&token_account),
+            token_mint,
+            token_program: Some(&commission_token_program),
+            signer_seeds: None,
+            is_charge_sol: false,
+            legacy_transfer: true,
+            transfer_ctx: TransferContext::default(),
+        };
+        FeeRecipients {
+            commission_account: Some(&commission_token_account.to_account_info()),
+            platform_fee_account: platform_fee_account.as_ref(),
+            ..Default::default()
+        }
+        .pay_fees(&source, &fees, commission_direction)?;
 
         // Trim token
         if trim_token_account.is_some()
@@ -360,34 +210,20 @@ impl<'info> CommonCommissionProcessorV2<'info> for PlatformFeeProcessorV2 {
             && trim_rate.unwrap() > 0
         {
             require!(trim_rate.unwrap() <= TRIM_RATE_LIMIT_V2, ErrorCode::InvalidTrimRate);
-            let trim_limit = u64::try_from(
-                u128::from(amount_out)
-                    .saturating_mul(trim_rate.unwrap() as u128)
-                    .saturating_div(TRIM_DENOMINATOR_V2 as u128),
-            )
-            .unwrap();
-
-            let trim_amount = if commission_direction {
-                (amount_out.saturating_sub(expected_amount_out)).min(trim_limit)
-            } else {
-                (amount_out.saturating_sub(commission_amount).saturating_sub(expected_amount_out))
-                    .min(trim_limit)
+            let trim = fee_policy.with_trim(trim_rate, None).trim_breakdown(
+                amount_out,
+                expected_amount_out,
+                &fees,
+            )?;
+            let destination_token_account = destination_token_account.to_account_info();
+            let source = FeeSource {
+                token_account: Some(&destination_token_account),
+                token_mint: destination_mint,
+                token_program: trim_token_program.as_ref(),
+                ..source
             };
-
-            if trim_amount > 0 {
-                transfer_token(
-                    payer.to_account_info(),
-                    destination_token_account.to_account_info(),
-                    trim_token_account.unwrap().to_account_info(),
-                    destination_mint.to_account_info(),
-                    trim_token_program.unwrap(),
-                    trim_amount,
-                    destination_mint.decimals,
-                    None,
-                )?;
-                msg!("trim_amount: {:?}", trim_amount);
-                trim_token_account.unwrap().to_account_info().key().log();
-            }
+            FeeRecipients { trim_account: trim_token_account, ..Default::default() }
+                .pay_trim(&source, &trim)?;
         }
         Ok(())
     }
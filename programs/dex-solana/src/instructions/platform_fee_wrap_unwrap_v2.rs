@@ -2,13 +2,12 @@ use crate::error::ErrorCode;
 use crate::instructions::commission_wrap_unwrap::{
     log_wrap_unwrap_final_info, log_wrap_unwrap_initial_info,
 };
+use crate::utils::fee_policy::{FeePolicy, FeeRecipients, FeeSource};
 use crate::utils::log_rate_info;
-use crate::utils::logging::{log_commission_info, log_platform_fee_info};
-use crate::utils::token::{sync_wsol_account, transfer_sol, transfer_token};
+use crate::utils::token::{TransferContext, sync_wsol_account};
 use crate::{
-    COMMISSION_DENOMINATOR_V2, COMMISSION_RATE_LIMIT_V2, PLATFORM_FEE_DENOMINATOR_V2,
-    PLATFORM_FEE_RATE_LIMIT_V2, SA_AUTHORITY_SEED, SEED_TEMP_WSOL, unwrap_process, wrap_process,
-    wsol_program,
+    COMMISSION_RATE_LIMIT_V2, PLATFORM_FEE_RATE_LIMIT_V2, SA_AUTHORITY_SEED, SEED_TEMP_WSOL,
+    unwrap_process, wrap_process, wsol_program,
 };
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
@@ -92,6 +91,7 @@ pub fn platform_fee_wrap_unwrap_handler_v2<'a>(
     );
     // CHECK: CommissionSwapArgs
     require!(commission_rate <= COMMISSION_RATE_LIMIT_V2, ErrorCode::InvalidCommissionRate);
+    let fee_policy = FeePolicy::v2(commission_rate, commission_direction, args.platform_fee_rate);
 
     require!(
         ctx.accounts.wsol_mint.key() == wsol_program::id(),
@@ -109,43 +109,15 @@ pub fn platform_fee_wrap_unwrap_handler_v2<'a>(
 
     let amount_out = args.amount_in;
 
-    let commission_amount: u64;
-    let mut platform_fee_amount: u64 = 0;
-
-    if commission_direction {
+    let fees = if commission_direction {
         // Commission direction: true-fromToken
         // Commission for fromToken
-        commission_amount = u64::try_from(
-            u128::from(args.amount_in)
-                .checked_mul(commission_rate as u128)
-                .ok_or(ErrorCode::CalculationError)?
-                .checked_div(COMMISSION_DENOMINATOR_V2 as u128 - commission_rate as u128)
-                .ok_or(ErrorCode::CalculationError)?,
-        )
-        .unwrap();
+        fee_policy.fee_amounts(args.amount_in)?
     } else {
         // Commission direction: false-toToken
         // Commission for toToken
-        commission_amount = u64::try_from(
-            u128::from(amount_out)
-                .checked_mul(commission_rate as u128)
-                .ok_or(ErrorCode::CalculationError)?
-                .checked_div(COMMISSION_DENOMINATOR_V2 as u128)
-                .ok_or(ErrorCode::CalculationError)?,
-        )
-        .unwrap();
-    }
-
-    if args.platform_fee_rate > 0 {
-        platform_fee_amount = u64::try_from(
-            u128::from(commission_amount)
-                .checked_mul(args.platform_fee_rate as u128)
-                .ok_or(ErrorCode::CalculationError)?
-                .checked_div(PLATFORM_FEE_DENOMINATOR_V2 as u128)
-                .ok_or(ErrorCode::CalculationError)?,
-        )
-        .unwrap();
-    }
+        fee_policy.fee_amounts(amount_out)?
+    };
 
     if wrap_direction {
         wrap_process(
@@ -177,85 +149,48 @@ pub fn platform_fee_wrap_unwrap_handler_v2<'a>(
         before_destination_balance,
     )?;
 
+    // The platform fee is paid to the SA account of the commission side
     let sa_account = if commission_direction {
-        ctx.accounts.source_token_sa.as_ref().unwrap()
+        ctx.accounts.source_token_sa.as_ref()
     } else {
-        ctx.accounts.destination_token_sa.as_ref().unwrap()
-    };
+        ctx.accounts.destination_token_sa.as_ref()
+    }
+    .ok_or(ErrorCode::MissingSaAccount)?
+    .to_account_info();
 
-    let sa_account_key = if sa_account.owner == &crate::token_program::ID {
-        let sa_account_box = Box::leak(Box::new(sa_account.to_account_info()));
-        InterfaceAccount::<TokenAccount>::try_from(sa_account_box).unwrap().owner
+    // The commission is paid in SOL when taken from the SOL side of the wrap or unwrap
+    let is_charge_sol = commission_direction == wrap_direction;
+    let commission_account = if is_charge_sol {
+        ctx.accounts.commission_sol_account.to_account_info()
     } else {
-        sa_account.key()
+        ctx.accounts.commission_wsol_account.to_account_info()
     };
+    let payer_wsol_account = ctx.accounts.payer_wsol_account.to_account_info();
+    let source = FeeSource {
+        authority: &ctx.accounts.payer.to_account_info(),
+        token_account: Some(&payer_wsol_account),
+        token_mint: &ctx.accounts.wsol_mint,
+        token_program: Some(&ctx.accounts.token_program),
+        signer_seeds: None,
+        is_charge_sol,
+        legacy_transfer: true,
+        transfer_ctx: TransferContext::default(),
+    };
+    FeeRecipients {
+        commission_account: Some(&commission_account),
+        platform_fee_account: Some(&sa_account),
+        ..Default::default()
+    }
+    .pay_fees(&source, &fees, commission_direction)?;
 
-    let commission_account_info =
-        if (commission_direction && wrap_direction) || (!commission_direction && !wrap_direction) {
-            ctx.accounts.commission_sol_account.to_account_info()
-        } else {
-            ctx.accounts.commission_wsol_account.to_account_info()
-        };
-
-    if (commission_direction && wrap_direction) || (!commission_direction && !wrap_direction) {
-        // decide sa according to the direction
-        // Transfer platform fee to sa
-        if args.platform_fee_rate > 0 && platform_fee_amount > 0 {
-            transfer_sol(
-                ctx.accounts.payer.to_account_info(),
-                sa_account.to_account_info(),
-                platform_fee_amount,
-                None,
-            )?;
-
-            // Sync source token sa
-            sync_wsol_account(
-                sa_account.to_account_info(),
-                ctx.accounts.token_program.to_account_info(),
-                Some(SA_AUTHORITY_SEED),
-            )?;
-            log_platform_fee_info(platform_fee_amount, 0, &sa_account_key);
-        }
-        transfer_sol(
-            ctx.accounts.payer.to_account_info(),
-            ctx.accounts.commission_sol_account.to_account_info(),
-            commission_amount.checked_sub(platform_fee_amount).unwrap(),
-            None,
-        )?;
-    } else {
-        if args.platform_fee_rate > 0 && platform_fee_amount > 0 {
-            transfer_token(
-                ctx.accounts.payer.to_account_info(),
-                ctx.accounts.payer_wsol_account.to_account_info(),
-                sa_account.to_account_info(),
-                ctx.accounts.wsol_mint.to_account_info(),
-                ctx.accounts.token_program.to_account_info(),
-                platform_fee_amount,
-                ctx.accounts.wsol_mint.decimals,
-                None,
-            )?;
-
-            log_platform_fee_info(platform_fee_amount, 0, &sa_account_key);
-        }
-
-        transfer_token(
-            ctx.accounts.payer.to_account_info(),
-            ctx.accounts.payer_wsol_account.to_account_info(),
-            ctx.accounts.commission_wsol_account.to_account_info(),
-            ctx.accounts.wsol_mint.to_account_info(),
+    // Sync the wsol SA paid in SOL
+    if is_charge_sol && fees.platform_fee_amount > 0 {
+        sync_wsol_account(
+            sa_account,
             ctx.accounts.token_program.to_account_info(),
-            commission_amount.checked_sub(platform_fee_amount).unwrap(),
-            ctx.accounts.wsol_mint.decimals,
-            None,
+            Some(SA_AUTHORITY_SEED),
         )?;
     }
 
-    log_commission_info(
-        commission_direction,
-        commission_amount.checked_sub(platform_fee_amount).unwrap(),
-        0,
-    );
-    commission_account_info.key().log();
-
     Ok(())
 }
@@ -0,0 +1,9 @@
+use crate::utils::{FeeQuote, QuoteFeesArgs, quote_fees};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct QuoteFees {}
+
+pub fn quote_fees_handler(_ctx: Context<QuoteFees>, args: QuoteFeesArgs) -> Result<FeeQuote> {
+    quote_fees(&args)
+}
@@ -0,0 +1,152 @@
+use super::common_swap::execute_swap;
+use crate::error::ErrorCode;
+use crate::state::{event::ClaimEvent, router_config::RouterConfig};
+use crate::utils::{split_hook_accounts, transfer_token_with_hook};
+use crate::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Sweeps the fees accrued in an SA token account into the settlement mint (USDC or wSOL),
+/// then claims the settlement balance of the SA to the receiver.
+#[derive(Accounts)]
+pub struct SwapAndClaimAccounts<'info> {
+    /// A claimer of the router config
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    /// CHECK: receiver, must be allowed by the router config
+    pub receiver: AccountInfo<'info>,
+
+    /// CHECK: sa authority
+    #[account(
+        address = authority_pda::id() @ ErrorCode::InvalidAuthorityPda
+    )]
+    pub sa_authority: AccountInfo<'info>,
+
+    /// The SA token account holding the accrued fees
+    #[account(
+        mut,
+        token::mint = source_mint,
+        token::authority = sa_authority,
+    )]
+    pub source_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The SA token account of the settlement mint
+    #[account(
+        mut,
+        token::mint = settlement_mint,
+        token::authority = sa_authority,
+    )]
+    pub settlement_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = settlement_mint,
+        token::authority = receiver,
+    )]
+    pub receiver_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub source_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        constraint = settlement_mint.key() == usdc_mint::id()
+            || settlement_mint.key() == wsol_program::id() @ ErrorCode::InvalidFeeSettlementMint
+    )]
+    pub settlement_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub settlement_token_program: Interface<'info, TokenInterface>,
+
+    #[account(
+        mut,
+        seeds = [
+            ROUTER_CONFIG_SEED.as_bytes(),
+        ],
+        bump = router_config.load()?.bump,
+    )]
+    pub router_config: AccountLoader<'info, RouterConfig>,
+}
+
+pub fn swap_and_claim_handler<'a>(
+    ctx: Context<'_, '_, 'a, 'a, SwapAndClaimAccounts<'a>>,
+    args: SwapArgs,
+    hook_accounts_len: u8,
+) -> Result<()> {
+    let (route_accounts, hook_accounts) =
+        split_hook_accounts(ctx.remaining_accounts, hook_accounts_len)?;
+    let mut claimer = {
+        let router_config = ctx.accounts.router_config.load()?;
+        require!(
+            router_config.is_claim_receiver(ctx.accounts.receiver.key()),
+            ErrorCode::ClaimReceiverNotAllowed
+        );
+        router_config.claimer(ctx.accounts.signer.key)?
+    };
+    require!(
+        ctx.accounts.source_mint.key() != ctx.accounts.settlement_mint.key(),
+        ErrorCode::InvalidFeeSettlementMint
+    );
+    require!(
+        args.amount_in <= ctx.accounts.source_token_account.amount,
+        ErrorCode::InsufficientFunds
+    );
+
+    // The claimer limit applies to the claimed settlement mint
+    let now = Clock::get()?.unix_timestamp;
+    let claimable = claimer.claimable_amount(&ctx.accounts.settlement_mint.key(), u64::MAX, now)?;
+
+    // Swap the accrued fees into the settlement mint, signed by the SA
+    let amount_in = args.amount_in;
+    let min_return = args.min_return;
+    let amount_out = execute_swap(
+        &mut ctx.accounts.source_token_account,
+        &mut ctx.accounts.settlement_token_account,
+        route_accounts,
+        args,
+        amount_in,
+        0,
+        true,
+        None,
+        Some(&ctx.accounts.signer.to_account_info()),
+    )?;
+    msg!("swap_and_claim amount_in: {:?}, amount_out: {:?}", amount_in, amount_out);
+    require!(amount_out >= min_return, ErrorCode::MinReturnNotReached);
+
+    // Claim the settlement balance of the SA, up to the claimer limit
+    let settlement_token_account = &mut ctx.accounts.settlement_token_account;
+    let receiver_token_account = &mut ctx.accounts.receiver_token_account;
+    let amount = settlement_token_account.amount.min(claimable);
+    claimer.record_claim(amount, now)?;
+    ctx.accounts.router_config.load_mut()?.save_claimer(&claimer);
+    let before_source_balance = settlement_token_account.amount;
+    let before_destination_balance = receiver_token_account.amount;
+
+    transfer_token_with_hook(
+        ctx.accounts.sa_authority.to_account_info(),
+        settlement_token_account.to_account_info(),
+        receiver_token_account.to_account_info(),
+        ctx.accounts.settlement_mint.to_account_info(),
+        ctx.accounts.settlement_token_program.to_account_info(),
+        amount,
+        ctx.accounts.settlement_mint.decimals,
+        Some(SA_AUTHORITY_SEED),
+        hook_accounts,
+    )?;
+
+    settlement_token_account.reload()?;
+    receiver_token_account.reload()?;
+    emit!(ClaimEvent {
+        claimer: claimer.authority,
+        receiver: ctx.accounts.receiver.key(),
+        mint: ctx.accounts.settlement_mint.key(),
+        source: settlement_token_account.key(),
+        destination: receiver_token_account.key(),
+        amount,
+        source_change: before_source_balance
+            .checked_sub(settlement_token_account.amount)
+            .ok_or(ErrorCode::CalculationError)?,
+        destination_change: receiver_token_account
+            .amount
+            .checked_sub(before_destination_balance)
+            .ok_or(ErrorCode::CalculationError)?,
+    });
+    Ok(())
+}
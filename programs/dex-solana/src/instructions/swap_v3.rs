@@ -1,12 +1,13 @@
-use super::{SwapArgs, common_swap_v3};
+use super::{SwapArgs, SwapPolicyContext, common_swap_v3};
+use crate::constants::{COMMISSION_FLAG_NATIVE_SOL, NATIVE_SOL_WSOL_SEED, ROUTER_CONFIG_SEED};
 use crate::error::ErrorCode;
 use crate::processor::*;
+use crate::state::{integrator::Integrator, router_config::RouterConfig, user_volume::UserVolume};
 use crate::utils::transfer_sol_with_rent_exemption;
 use crate::utils::*;
-use crate::wsol_program;
 use anchor_lang::prelude::*;
 use anchor_spl::{
-    associated_token::AssociatedToken,
+    associated_token::{AssociatedToken, get_associated_token_address_with_program_id},
     token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
@@ -15,12 +16,10 @@ pub struct CommissionProxySwapAccountsV3<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
 
-    #[account(
-        mut,
-        token::mint = source_mint,
-        token::authority = payer,
-    )]
-    pub source_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: source token account of the payer, the ephemeral wSOL account created by the swap
+    /// when selling native SOL, checked in the handler
+    #[account(mut)]
+    pub source_token_account: UncheckedAccount<'info>,
 
     #[account(
         mut,
@@ -54,6 +53,21 @@ pub struct CommissionProxySwapAccountsV3<'info> {
     pub destination_token_program: Option<Interface<'info, TokenInterface>>,
     pub associated_token_program: Option<Program<'info, AssociatedToken>>,
     pub system_program: Option<Program<'info, System>>,
+
+    /// The integrator agreement the fees are validated against
+    pub integrator: Option<AccountLoader<'info, Integrator>>,
+
+    /// The volume accumulator of the payer, applies the fee tiers of the integrator
+    #[account(mut)]
+    pub user_volume: Option<AccountLoader<'info, UserVolume>>,
+
+    /// The router config, holds the default mint policy and the integrator requirement, the
+    /// default policy applies if None
+    #[account(
+        seeds = [ROUTER_CONFIG_SEED.as_bytes()],
+        bump = router_config.load()?.bump,
+    )]
+    pub router_config: Option<AccountLoader<'info, RouterConfig>>,
 }
 
 pub fn swap_tob_handler<'a>(
@@ -64,9 +78,11 @@ pub fn swap_tob_handler<'a>(
     trim_rate: Option<u8>,
     platform_fee_rate: Option<u16>,
 ) -> Result<()> {
+    let route_accounts = ctx.remaining_accounts;
     let commission_direction = commission_info >> 31 == 1;
     let acc_close_flag = ((commission_info & (1 << 30)) >> 30) == 1;
-    let commission_rate = commission_info & ((1 << 30) - 1);
+    let native_sol_flag = commission_info & COMMISSION_FLAG_NATIVE_SOL != 0;
+    let commission_rate = commission_info & (COMMISSION_FLAG_NATIVE_SOL - 1);
     log_rate_info_v3(
         commission_rate,
         platform_fee_rate,
@@ -74,27 +90,41 @@ pub fn swap_tob_handler<'a>(
         commission_direction,
         acc_close_flag,
     );
+    if native_sol_flag {
+        wrap_native_source(
+            ctx.accounts,
+            &args,
+            commission_rate,
+            commission_direction,
+            platform_fee_rate,
+        )?;
+    }
+    let mut source_token_account = load_source_token_account(ctx.accounts)?;
 
     let trim_account = if trim_rate.is_some() && trim_rate.unwrap() > 0 {
-        Some(&ctx.remaining_accounts[ctx.remaining_accounts.len() - 1])
+        Some(&route_accounts[route_accounts.len() - 1])
     } else {
         None
     };
+    let router_config =
+        ctx.accounts.router_config.as_ref().map(|config| config.load()).transpose()?;
+    let integrator =
+        ctx.accounts.integrator.as_ref().map(|integrator| integrator.load()).transpose()?;
     common_swap_v3(
         &SwapToBProcessor,
         &ctx.accounts.payer,
-        &mut ctx.accounts.source_token_account,
+        &mut source_token_account,
         &mut ctx.accounts.destination_token_account,
         &ctx.accounts.source_mint,
         &ctx.accounts.destination_mint,
-        &mut ctx.accounts.sa_authority,
+        &ctx.accounts.sa_authority,
         &mut ctx.accounts.source_token_sa,
         &mut ctx.accounts.destination_token_sa,
         &ctx.accounts.source_token_program,
         &ctx.accounts.destination_token_program,
         &ctx.accounts.associated_token_program,
         &ctx.accounts.system_program,
-        ctx.remaining_accounts,
+        route_accounts,
         args,
         order_id,
         commission_rate,
@@ -107,7 +137,18 @@ pub fn swap_tob_handler<'a>(
         trim_account,
         None,
         acc_close_flag,
+        SwapPolicyContext {
+            router_config: router_config.as_deref(),
+            integrator: integrator.as_deref(),
+            user_volume: ctx.accounts.user_volume.as_ref(),
+            mint_policy: None,
+            user_share_rate: None,
+            hook_accounts: &[],
+        },
     )?;
+    if native_sol_flag {
+        close_native_source(ctx.accounts)?;
+    }
     Ok(())
 }
 
@@ -118,25 +159,41 @@ pub fn swap_toc_handler<'a>(
     order_id: u64,
     platform_fee_rate: Option<u16>,
 ) -> Result<()> {
+    let route_accounts = ctx.remaining_accounts;
     let commission_direction = commission_info >> 31 == 1;
-    let commission_rate = commission_info & ((1 << 30) - 1);
+    let native_sol_flag = commission_info & COMMISSION_FLAG_NATIVE_SOL != 0;
+    let commission_rate = commission_info & (COMMISSION_FLAG_NATIVE_SOL - 1);
     log_rate_info_v3(commission_rate, platform_fee_rate, None, commission_direction, false);
+    if native_sol_flag {
+        wrap_native_source(
+            ctx.accounts,
+            &args,
+            commission_rate,
+            commission_direction,
+            platform_fee_rate,
+        )?;
+    }
+    let mut source_token_account = load_source_token_account(ctx.accounts)?;
 
+    let router_config =
+        ctx.accounts.router_config.as_ref().map(|config| config.load()).transpose()?;
+    let integrator =
+        ctx.accounts.integrator.as_ref().map(|integrator| integrator.load()).transpose()?;
     common_swap_v3(
-        &SwapToCProcessor,
+        &SwapToCProcessor(FeeAccountsPayout),
         &ctx.accounts.payer,
-        &mut ctx.accounts.source_token_account,
+        &mut source_token_account,
         &mut ctx.accounts.destination_token_account,
         &ctx.accounts.source_mint,
         &ctx.accounts.destination_mint,
-        &mut ctx.accounts.sa_authority,
+        &ctx.accounts.sa_authority,
         &mut ctx.accounts.source_token_sa,
         &mut ctx.accounts.destination_token_sa,
         &ctx.accounts.source_token_program,
         &ctx.accounts.destination_token_program,
         &ctx.accounts.associated_token_program,
         &ctx.accounts.system_program,
-        ctx.remaining_accounts,
+        route_accounts,
         args,
         order_id,
         commission_rate,
@@ -149,6 +206,84 @@ pub fn swap_toc_handler<'a>(
         None,
         None,
         false,
+        SwapPolicyContext {
+            router_config: router_config.as_deref(),
+            integrator: integrator.as_deref(),
+            user_volume: ctx.accounts.user_volume.as_ref(),
+            mint_policy: None,
+            user_share_rate: None,
+            hook_accounts: &[],
+        },
+    )?;
+    if native_sol_flag {
+        close_native_source(ctx.accounts)?;
+    }
+    Ok(())
+}
+
+/// The source token account of the payer, checked here as the ephemeral wSOL account of a native
+/// SOL swap does not exist before the swap
+pub fn load_source_token_account<'a>(
+    accounts: &CommissionProxySwapAccountsV3<'a>,
+) -> Result<InterfaceAccount<'a, TokenAccount>> {
+    let source_token_account = associate_convert_token_account(&accounts.source_token_account)?;
+    require_keys_eq!(
+        source_token_account.mint,
+        accounts.source_mint.key(),
+        anchor_lang::error::ErrorCode::ConstraintTokenMint
+    );
+    require_keys_eq!(
+        source_token_account.owner,
+        accounts.payer.key(),
+        anchor_lang::error::ErrorCode::ConstraintTokenOwner
+    );
+    Ok(source_token_account)
+}
+
+/// Wrap the lamports of the payer into its ephemeral wSOL account, covering the amount in and the
+/// from fees, so native SOL can be sold without a wSOL account of the payer
+pub fn wrap_native_source(
+    accounts: &CommissionProxySwapAccountsV3<'_>,
+    args: &SwapArgs,
+    commission_rate: u32,
+    commission_direction: bool,
+    platform_fee_rate: Option<u16>,
+) -> Result<()> {
+    let source_mint = accounts.source_mint.key();
+    require!(is_native_mint(&source_mint), ErrorCode::NativeSolSourceRequiresWsol);
+    let source_token_program =
+        accounts.source_token_program.as_ref().ok_or(ErrorCode::SourceTokenProgramIsNone)?;
+
+    let policy = FeePolicy::v3(commission_rate, commission_direction, platform_fee_rate);
+    let lamports = args
+        .amount_in
+        .checked_add(quote_from_fees(&policy, args.amount_in)?.total()?)
+        .ok_or(ErrorCode::CalculationError)?;
+    let (_, bump) = Pubkey::find_program_address(
+        &[source_mint.as_ref(), accounts.payer.key.as_ref(), &NATIVE_SOL_WSOL_SEED.to_le_bytes()],
+        &crate::ID,
+    );
+    msg!("native sol wrap: {}", lamports);
+    open_ephemeral_wsol(
+        &accounts.payer.to_account_info(),
+        &accounts.source_token_account,
+        &accounts.payer.to_account_info(),
+        &accounts.source_mint.to_account_info(),
+        &source_token_program.to_account_info(),
+        &EphemeralWsolArgs { seed: NATIVE_SOL_WSOL_SEED, bump, lamports },
+    )
+}
+
+/// Close the ephemeral wSOL account of a native SOL swap, its rent and the unused lamports go back
+/// to the payer
+pub fn close_native_source(accounts: &CommissionProxySwapAccountsV3<'_>) -> Result<()> {
+    let source_token_program =
+        accounts.source_token_program.as_ref().ok_or(ErrorCode::SourceTokenProgramIsNone)?;
+    close_ephemeral_wsol(
+        &accounts.source_token_account,
+        &accounts.payer.to_account_info(),
+        &source_token_program.to_account_info(),
+        None,
     )?;
     Ok(())
 }
@@ -166,11 +301,10 @@ pub struct CommissionProxySwapAccountsV3WithReceiver<'info> {
     )]
     pub source_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
-    #[account(
-        mut,
-        token::mint = destination_mint,
-    )]
-    pub destination_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: destination token account, the associated token account of sol_receiver when the
+    /// tokens are delivered to a receiver, created if needed, checked in the handler
+    #[account(mut)]
+    pub destination_token_account: UncheckedAccount<'info>,
 
     pub source_mint: Box<InterfaceAccount<'info, Mint>>,
     pub destination_mint: Box<InterfaceAccount<'info, Mint>>,
@@ -198,45 +332,103 @@ pub struct CommissionProxySwapAccountsV3WithReceiver<'info> {
     pub associated_token_program: Option<Program<'info, AssociatedToken>>,
     pub system_program: Option<Program<'info, System>>,
 
-    /// Optional SOL receiver account
+    /// Optional receiver account
     /// - None: normal swap or SOL stays with payer
-    /// - Some: SOL receiver when converting wSOL -> SOL
+    /// - Some: SOL receiver when converting wSOL -> SOL with acc_close_flag, otherwise the wallet
+    ///   owning destination_token_account
     #[account(mut)]
     pub sol_receiver: Option<AccountInfo<'info>>,
+
+    /// The integrator agreement the fees are validated against
+    pub integrator: Option<AccountLoader<'info, Integrator>>,
+
+    /// The volume accumulator of the payer, applies the fee tiers of the integrator
+    #[account(mut)]
+    pub user_volume: Option<AccountLoader<'info, UserVolume>>,
+
+    /// The router config, holds the default mint policy and the integrator requirement, the
+    /// default policy applies if None
+    #[account(
+        seeds = [ROUTER_CONFIG_SEED.as_bytes()],
+        bump = router_config.load()?.bump,
+    )]
+    pub router_config: Option<AccountLoader<'info, RouterConfig>>,
 }
 
-/// Validate sol_receiver based on acc_close_flag
+/// Where the swap output is delivered
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ReceiverMode {
+    /// The destination token account of the payer
+    Payer,
+    /// Unwrapped and sent as SOL to sol_receiver
+    Sol,
+    /// Delivered to the associated token account of sol_receiver
+    Token,
+}
+
+/// Validate sol_receiver based on acc_close_flag and the destination mint
 fn validate_sol_receiver_and_flags<'info>(
     sol_receiver: &Option<AccountInfo<'info>>,
     acc_close_flag: bool,
     destination_mint: &Pubkey,
-) -> Result<()> {
-    match (sol_receiver, acc_close_flag) {
-        (Some(receiver), true) => {
-            // Valid case: has receiver and acc_close_flag is true
-            // Must be system account
+) -> Result<ReceiverMode> {
+    match sol_receiver {
+        // Unwrapping wSOL sends SOL, the receiver must be a system account
+        Some(receiver) if acc_close_flag && is_native_mint(destination_mint) => {
             require!(
                 receiver.owner == &anchor_lang::system_program::ID,
                 ErrorCode::SolReceiverMustBeSystemAccount
             );
-            // Must be wSOL destination
-            require!(
-                *destination_mint == wsol_program::ID,
-                ErrorCode::DestinationMustBeWsolForSolReceiver
-            );
-            Ok(())
-        }
-        (Some(_), false) => {
-            // Invalid: has receiver but acc_close_flag is false
-            Err(ErrorCode::SolReceiverRequiresAccCloseFlag.into())
-        }
-        (None, _) => {
-            // Valid: no receiver, any acc_close_flag is fine
-            Ok(())
+            Ok(ReceiverMode::Sol)
         }
+        // Any other mint is sent to the receiver's associated token account
+        Some(_) => Ok(ReceiverMode::Token),
+        None => Ok(ReceiverMode::Payer),
     }
 }
 
+/// Check the associated token account of sol_receiver, creating it if needed
+fn prepare_receiver_token_account(
+    accounts: &CommissionProxySwapAccountsV3WithReceiver,
+) -> Result<()> {
+    let receiver = accounts.sol_receiver.as_ref().ok_or(ErrorCode::InvalidReceiverTokenAccount)?;
+    let receiver_token_account = &accounts.destination_token_account;
+    let destination_token_program = accounts
+        .destination_token_program
+        .as_ref()
+        .ok_or(ErrorCode::DestinationTokenProgramIsNone)?;
+    let associated_token_program = accounts
+        .associated_token_program
+        .as_ref()
+        .ok_or(ErrorCode::InvalidAssociatedTokenProgram)?;
+    let system_program = accounts.system_program.as_ref().ok_or(ErrorCode::SystemProgramIsNone)?;
+
+    require_keys_eq!(
+        receiver_token_account.key(),
+        get_associated_token_address_with_program_id(
+            &receiver.key(),
+            &accounts.destination_mint.key(),
+            &destination_token_program.key(),
+        ),
+        ErrorCode::InvalidReceiverTokenAccount
+    );
+    create_ata_if_needed(
+        receiver,
+        &accounts.payer,
+        receiver_token_account,
+        &accounts.destination_mint.to_account_info(),
+        &destination_token_program.to_account_info(),
+        &associated_token_program.to_account_info(),
+        &system_program.to_account_info(),
+    )?;
+    let state = unpack_token_account(receiver_token_account)?;
+    require!(
+        state.is_held_by(&receiver.key(), &accounts.destination_mint.key()),
+        ErrorCode::InvalidReceiverTokenAccount
+    );
+    Ok(())
+}
+
 /// ToB swap handler with optional specified receiver
 /// Supports both normal swaps and wSOL -> SOL conversion with custom receiver
 pub fn swap_tob_specified_receiver_handler<'a>(
@@ -247,17 +439,32 @@ pub fn swap_tob_specified_receiver_handler<'a>(
     trim_rate: Option<u8>,
     platform_fee_rate: Option<u16>,
 ) -> Result<()> {
+    let route_accounts = ctx.remaining_accounts;
     // Parse commission info
     let commission_direction = commission_info >> 31 == 1;
     let acc_close_flag = ((commission_info & (1 << 30)) >> 30) == 1;
-    let commission_rate = commission_info & ((1 << 30) - 1);
+    let commission_rate = commission_info & (COMMISSION_FLAG_NATIVE_SOL - 1);
+    require!(
+        commission_info & COMMISSION_FLAG_NATIVE_SOL == 0,
+        ErrorCode::NativeSolSourceUnsupported
+    );
 
     // Validate sol_receiver and acc_close_flag combination
-    validate_sol_receiver_and_flags(
+    let receiver_mode = validate_sol_receiver_and_flags(
         &ctx.accounts.sol_receiver,
         acc_close_flag,
         &ctx.accounts.destination_mint.key(),
     )?;
+    if receiver_mode == ReceiverMode::Token {
+        prepare_receiver_token_account(ctx.accounts)?;
+    }
+    let mut destination_token_account =
+        associate_convert_token_account(&ctx.accounts.destination_token_account)?;
+    require_keys_eq!(
+        destination_token_account.mint,
+        ctx.accounts.destination_mint.key(),
+        anchor_lang::error::ErrorCode::ConstraintTokenMint
+    );
 
     log_rate_info_v3(
         commission_rate,
@@ -268,27 +475,31 @@ pub fn swap_tob_specified_receiver_handler<'a>(
     );
 
     let trim_account = if trim_rate.is_some() && trim_rate.unwrap() > 0 {
-        Some(&ctx.remaining_accounts[ctx.remaining_accounts.len() - 1])
+        Some(&route_accounts[route_accounts.len() - 1])
     } else {
         None
     };
 
     // Execute swap and get actual amount out
+    let router_config =
+        ctx.accounts.router_config.as_ref().map(|config| config.load()).transpose()?;
+    let integrator =
+        ctx.accounts.integrator.as_ref().map(|integrator| integrator.load()).transpose()?;
     let actual_amount_out = common_swap_v3(
         &SwapToBProcessor,
         &ctx.accounts.payer,
         &mut ctx.accounts.source_token_account,
-        &mut ctx.accounts.destination_token_account,
+        &mut destination_token_account,
         &ctx.accounts.source_mint,
         &ctx.accounts.destination_mint,
-        &mut ctx.accounts.sa_authority,
+        &ctx.accounts.sa_authority,
         &mut ctx.accounts.source_token_sa,
         &mut ctx.accounts.destination_token_sa,
         &ctx.accounts.source_token_program,
         &ctx.accounts.destination_token_program,
         &ctx.accounts.associated_token_program,
         &ctx.accounts.system_program,
-        ctx.remaining_accounts,
+        route_accounts,
         args,
         order_id,
         commission_rate,
@@ -301,20 +512,26 @@ pub fn swap_tob_specified_receiver_handler<'a>(
         trim_account,
         None,
         acc_close_flag,
+        SwapPolicyContext {
+            router_config: router_config.as_deref(),
+            integrator: integrator.as_deref(),
+            user_volume: ctx.accounts.user_volume.as_ref(),
+            mint_policy: None,
+            user_share_rate: None,
+            hook_accounts: &[],
+        },
     )?;
 
-    // Transfer SOL to specified receiver if applicable
-    if let Some(sol_receiver) = &ctx.accounts.sol_receiver {
-        // Only transfer if acc_close_flag is true (already validated)
-        // and destination was wSOL (already validated)
-        if acc_close_flag && actual_amount_out > 0 {
-            transfer_sol_with_rent_exemption(
-                &ctx.accounts.payer,
-                sol_receiver,
-                actual_amount_out,
-                None, // No seeds needed for payer
-            )?;
-        }
+    // Transfer SOL to the specified receiver if applicable, tokens were already delivered to the
+    // receiver's associated token account by the swap
+    if receiver_mode == ReceiverMode::Sol && actual_amount_out > 0 {
+        // The destination wSOL was unwrapped to the payer (already validated)
+        transfer_sol_with_rent_exemption(
+            &ctx.accounts.payer,
+            ctx.accounts.sol_receiver.as_ref().unwrap(),
+            actual_amount_out,
+            None, // No seeds needed for payer
+        )?;
     }
 
     Ok(())
@@ -329,9 +546,11 @@ pub fn swap_tob_enhanced_handler<'a>(
     charge_rate: u16,
     platform_fee_rate: Option<u16>,
 ) -> Result<()> {
+    let route_accounts = ctx.remaining_accounts;
     let commission_direction = commission_info >> 31 == 1;
     let acc_close_flag = ((commission_info & (1 << 30)) >> 30) == 1;
-    let commission_rate = commission_info & ((1 << 30) - 1);
+    let native_sol_flag = commission_info & COMMISSION_FLAG_NATIVE_SOL != 0;
+    let commission_rate = commission_info & (COMMISSION_FLAG_NATIVE_SOL - 1);
     log_rate_info_v3_enhanced(
         commission_rate,
         platform_fee_rate,
@@ -342,24 +561,38 @@ pub fn swap_tob_enhanced_handler<'a>(
     );
     require!(trim_rate > 0 && charge_rate > 0, ErrorCode::InvalidTrimRate);
 
-    let trim_account = &ctx.remaining_accounts[ctx.remaining_accounts.len() - 2];
-    let charge_account = &ctx.remaining_accounts[ctx.remaining_accounts.len() - 1];
-
+    let trim_account = &route_accounts[route_accounts.len() - 2];
+    let charge_account = &route_accounts[route_accounts.len() - 1];
+
+    if native_sol_flag {
+        wrap_native_source(
+            ctx.accounts,
+            &args,
+            commission_rate,
+            commission_direction,
+            platform_fee_rate,
+        )?;
+    }
+    let mut source_token_account = load_source_token_account(ctx.accounts)?;
+    let router_config =
+        ctx.accounts.router_config.as_ref().map(|config| config.load()).transpose()?;
+    let integrator =
+        ctx.accounts.integrator.as_ref().map(|integrator| integrator.load()).transpose()?;
     common_swap_v3(
         &SwapToBProcessor,
         &ctx.accounts.payer,
-        &mut ctx.accounts.source_token_account,
+        &mut source_token_account,
         &mut ctx.accounts.destination_token_account,
         &ctx.accounts.source_mint,
         &ctx.accounts.destination_mint,
-        &mut ctx.accounts.sa_authority,
+        &ctx.accounts.sa_authority,
         &mut ctx.accounts.source_token_sa,
         &mut ctx.accounts.destination_token_sa,
         &ctx.accounts.source_token_program,
         &ctx.accounts.destination_token_program,
         &ctx.accounts.associated_token_program,
         &ctx.accounts.system_program,
-        ctx.remaining_accounts,
+        route_accounts,
         args,
         order_id,
         commission_rate,
@@ -372,6 +605,107 @@ pub fn swap_tob_enhanced_handler<'a>(
         Some(trim_account),
         Some(charge_account),
         acc_close_flag,
+        SwapPolicyContext {
+            router_config: router_config.as_deref(),
+            integrator: integrator.as_deref(),
+            user_volume: ctx.accounts.user_volume.as_ref(),
+            mint_policy: None,
+            user_share_rate: None,
+            hook_accounts: &[],
+        },
     )?;
+    if native_sol_flag {
+        close_native_source(ctx.accounts)?;
+    }
+    Ok(())
+}
+
+pub fn swap_tob_user_share_handler<'a>(
+    ctx: Context<'_, '_, 'a, 'a, CommissionProxySwapAccountsV3<'a>>,
+    args: SwapArgs,
+    commission_info: u32,
+    order_id: u64,
+    trim_rate: u8,
+    charge_rate: u16,
+    user_share_rate: u16,
+    platform_fee_rate: Option<u16>,
+    hook_accounts_len: u8,
+) -> Result<()> {
+    let (route_accounts, hook_accounts) =
+        split_hook_accounts(ctx.remaining_accounts, hook_accounts_len)?;
+    let commission_direction = commission_info >> 31 == 1;
+    let acc_close_flag = ((commission_info & (1 << 30)) >> 30) == 1;
+    let native_sol_flag = commission_info & COMMISSION_FLAG_NATIVE_SOL != 0;
+    let commission_rate = commission_info & (COMMISSION_FLAG_NATIVE_SOL - 1);
+    log_rate_info_v3_enhanced(
+        commission_rate,
+        platform_fee_rate,
+        trim_rate,
+        charge_rate,
+        commission_direction,
+        acc_close_flag,
+    );
+    msg!("user_share_rate: {:?}", user_share_rate);
+    require!(trim_rate > 0, ErrorCode::InvalidTrimRate);
+
+    let (trim_account, charge_account) = if charge_rate > 0 {
+        (&route_accounts[route_accounts.len() - 2], Some(&route_accounts[route_accounts.len() - 1]))
+    } else {
+        (&route_accounts[route_accounts.len() - 1], None)
+    };
+
+    if native_sol_flag {
+        wrap_native_source(
+            ctx.accounts,
+            &args,
+            commission_rate,
+            commission_direction,
+            platform_fee_rate,
+        )?;
+    }
+    let mut source_token_account = load_source_token_account(ctx.accounts)?;
+    let router_config =
+        ctx.accounts.router_config.as_ref().map(|config| config.load()).transpose()?;
+    let integrator =
+        ctx.accounts.integrator.as_ref().map(|integrator| integrator.load()).transpose()?;
+    common_swap_v3(
+        &SwapToBProcessor,
+        &ctx.accounts.payer,
+        &mut source_token_account,
+        &mut ctx.accounts.destination_token_account,
+        &ctx.accounts.source_mint,
+        &ctx.accounts.destination_mint,
+        &ctx.accounts.sa_authority,
+        &mut ctx.accounts.source_token_sa,
+        &mut ctx.accounts.destination_token_sa,
+        &ctx.accounts.source_token_program,
+        &ctx.accounts.destination_token_program,
+        &ctx.accounts.associated_token_program,
+        &ctx.accounts.system_program,
+        route_accounts,
+        args,
+        order_id,
+        commission_rate,
+        commission_direction,
+        &ctx.accounts.commission_account,
+        platform_fee_rate,
+        &ctx.accounts.platform_fee_account,
+        Some(trim_rate),
+        Some(charge_rate),
+        Some(trim_account),
+        charge_account,
+        acc_close_flag,
+        SwapPolicyContext {
+            router_config: router_config.as_deref(),
+            integrator: integrator.as_deref(),
+            user_volume: ctx.accounts.user_volume.as_ref(),
+            mint_policy: None,
+            user_share_rate: Some(user_share_rate),
+            hook_accounts,
+        },
+    )?;
+    if native_sol_flag {
+        close_native_source(ctx.accounts)?;
+    }
     Ok(())
 }
@@ -0,0 +1,150 @@
+use super::{SwapArgs, SwapPolicyContext, common_swap_v3};
+use crate::constants::*;
+use crate::error::ErrorCode;
+use crate::processor::*;
+use crate::state::{integrator::Integrator, router_config::RouterConfig, user_volume::UserVolume};
+use crate::utils::*;
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+/// Account structure for swap accruing the fees into the fee vault,
+/// commission_account and platform_fee_account are the recipients' fee accrual accounts
+#[derive(Accounts)]
+pub struct CommissionProxySwapAccountsV3WithFeeVault<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        token::mint = source_mint,
+        token::authority = payer,
+    )]
+    pub source_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = destination_mint,
+    )]
+    pub destination_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub source_mint: Box<InterfaceAccount<'info, Mint>>,
+    pub destination_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: commission fee accrual account
+    #[account(mut)]
+    pub commission_account: Option<AccountInfo<'info>>,
+
+    /// CHECK: platform fee accrual account
+    #[account(mut)]
+    pub platform_fee_account: Option<AccountInfo<'info>>,
+
+    /// CHECK: sa_authority
+    #[account(mut)]
+    pub sa_authority: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    pub source_token_sa: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    pub destination_token_sa: Option<UncheckedAccount<'info>>,
+
+    pub source_token_program: Option<Interface<'info, TokenInterface>>,
+    pub destination_token_program: Option<Interface<'info, TokenInterface>>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+    pub system_program: Option<Program<'info, System>>,
+
+    /// The fee vault of the commission mint
+    #[account(mut)]
+    pub fee_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The integrator agreement the fees are validated against
+    pub integrator: Option<AccountLoader<'info, Integrator>>,
+
+    /// The volume accumulator of the payer, applies the fee tiers of the integrator
+    #[account(mut)]
+    pub user_volume: Option<AccountLoader<'info, UserVolume>>,
+
+    /// The router config, holds the default mint policy and the integrator requirement, the
+    /// default policy applies if None
+    #[account(
+        seeds = [ROUTER_CONFIG_SEED.as_bytes()],
+        bump = router_config.load()?.bump,
+    )]
+    pub router_config: Option<AccountLoader<'info, RouterConfig>>,
+}
+
+pub fn swap_toc_fee_vault_handler<'a>(
+    ctx: Context<'_, '_, 'a, 'a, CommissionProxySwapAccountsV3WithFeeVault<'a>>,
+    args: SwapArgs,
+    commission_info: u32,
+    order_id: u64,
+    platform_fee_rate: Option<u16>,
+    hook_accounts_len: u8,
+) -> Result<()> {
+    let (route_accounts, hook_accounts) =
+        split_hook_accounts(ctx.remaining_accounts, hook_accounts_len)?;
+    let commission_direction = commission_info >> 31 == 1;
+    let commission_rate = commission_info & (COMMISSION_FLAG_NATIVE_SOL - 1);
+    require!(
+        commission_info & COMMISSION_FLAG_NATIVE_SOL == 0,
+        ErrorCode::NativeSolSourceUnsupported
+    );
+    log_rate_info_v3(commission_rate, platform_fee_rate, None, commission_direction, false);
+
+    // Check the fee vault of the commission mint
+    let fee_mint = if commission_direction {
+        ctx.accounts.source_mint.key()
+    } else {
+        ctx.accounts.destination_mint.key()
+    };
+    let (fee_vault, _) =
+        Pubkey::find_program_address(&[FEE_VAULT_SEED.as_bytes(), fee_mint.as_ref()], &crate::ID);
+    require_keys_eq!(ctx.accounts.fee_vault.key(), fee_vault, ErrorCode::InvalidFeeVault);
+
+    let processor =
+        SwapToCProcessor(FeeVaultPayout { fee_vault: ctx.accounts.fee_vault.to_account_info() });
+    let router_config =
+        ctx.accounts.router_config.as_ref().map(|config| config.load()).transpose()?;
+    let integrator =
+        ctx.accounts.integrator.as_ref().map(|integrator| integrator.load()).transpose()?;
+    common_swap_v3(
+        &processor,
+        &ctx.accounts.payer,
+        &mut ctx.accounts.source_token_account,
+        &mut ctx.accounts.destination_token_account,
+        &ctx.accounts.source_mint,
+        &ctx.accounts.destination_mint,
+        &ctx.accounts.sa_authority,
+        &mut ctx.accounts.source_token_sa,
+        &mut ctx.accounts.destination_token_sa,
+        &ctx.accounts.source_token_program,
+        &ctx.accounts.destination_token_program,
+        &ctx.accounts.associated_token_program,
+        &ctx.accounts.system_program,
+        route_accounts,
+        args,
+        order_id,
+        commission_rate,
+        commission_direction,
+        &ctx.accounts.commission_account,
+        platform_fee_rate,
+        &ctx.accounts.platform_fee_account,
+        None,
+        None,
+        None,
+        None,
+        false,
+        SwapPolicyContext {
+            router_config: router_config.as_deref(),
+            integrator: integrator.as_deref(),
+            user_volume: ctx.accounts.user_volume.as_ref(),
+            mint_policy: None,
+            user_share_rate: None,
+            hook_accounts,
+        },
+    )?;
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use super::{
+    CommissionProxySwapAccountsV3, SwapArgs, SwapPolicyContext, close_native_source,
+    common_swap_v3, load_source_token_account, wrap_native_source,
+};
+use crate::constants::*;
+use crate::error::ErrorCode;
+use crate::processor::*;
+use crate::utils::*;
+use anchor_lang::prelude::*;
+
+/// Swap ToC with the commission split across multiple recipients.
+/// The recipients are the last `commission_rates.len()` remaining accounts before the
+/// `hook_accounts_len` transfer hook accounts, in the same order.
+pub fn swap_toc_splits_handler<'a>(
+    ctx: Context<'_, '_, 'a, 'a, CommissionProxySwapAccountsV3<'a>>,
+    args: SwapArgs,
+    commission_info: u32,
+    commission_rates: Vec<u32>,
+    order_id: u64,
+    platform_fee_rate: Option<u16>,
+    hook_accounts_len: u8,
+) -> Result<()> {
+    let (route_accounts, hook_accounts) =
+        split_hook_accounts(ctx.remaining_accounts, hook_accounts_len)?;
+    let commission_direction = commission_info >> 31 == 1;
+    let native_sol_flag = commission_info & COMMISSION_FLAG_NATIVE_SOL != 0;
+    let commission_rate = commission_info & (COMMISSION_FLAG_NATIVE_SOL - 1);
+    log_rate_info_v3(commission_rate, platform_fee_rate, None, commission_direction, false);
+
+    // Check the commission splits, the total rate must match the commission info
+    let split_count = commission_rates.len();
+    require!(
+        split_count > 0
+            && split_count <= MAX_COMMISSION_SPLITS
+            && split_count < route_accounts.len(),
+        ErrorCode::InvalidCommissionSplits
+    );
+    require!(commission_rates.iter().all(|rate| *rate > 0), ErrorCode::InvalidCommissionSplits);
+    require!(
+        commission_rates.iter().map(|rate| *rate as u64).sum::<u64>() == commission_rate as u64,
+        ErrorCode::InvalidCommissionSplits
+    );
+    require!(ctx.accounts.commission_account.is_none(), ErrorCode::InvalidCommissionSplits);
+
+    let (swap_accounts, recipients) = route_accounts.split_at(route_accounts.len() - split_count);
+    for (i, recipient) in recipients.iter().enumerate() {
+        require!(recipient.is_writable, ErrorCode::InvalidFeeAccount);
+        require!(
+            recipients[..i].iter().all(|other| other.key() != recipient.key()),
+            ErrorCode::DuplicateCommissionRecipient
+        );
+    }
+
+    if native_sol_flag {
+        wrap_native_source(
+            ctx.accounts,
+            &args,
+            commission_rate,
+            commission_direction,
+            platform_fee_rate,
+        )?;
+    }
+    let mut source_token_account = load_source_token_account(ctx.accounts)?;
+    let processor = SwapToCProcessor(CommissionSplitsPayout { recipients, commission_rates });
+    let router_config =
+        ctx.accounts.router_config.as_ref().map(|config| config.load()).transpose()?;
+    let integrator =
+        ctx.accounts.integrator.as_ref().map(|integrator| integrator.load()).transpose()?;
+    common_swap_v3(
+        &processor,
+        &ctx.accounts.payer,
+        &mut source_token_account,
+        &mut ctx.accounts.destination_token_account,
+        &ctx.accounts.source_mint,
+        &ctx.accounts.destination_mint,
+        &ctx.accounts.sa_authority,
+        &mut ctx.accounts.source_token_sa,
+        &mut ctx.accounts.destination_token_sa,
+        &ctx.accounts.source_token_program,
+        &ctx.accounts.destination_token_program,
+        &ctx.accounts.associated_token_program,
+        &ctx.accounts.system_program,
+        swap_accounts,
+        args,
+        order_id,
+        commission_rate,
+        commission_direction,
+        &ctx.accounts.commission_account,
+        platform_fee_rate,
+        &ctx.accounts.platform_fee_account,
+        None,
+        None,
+        None,
+        None,
+        false,
+        SwapPolicyContext {
+            router_config: router_config.as_deref(),
+            integrator: integrator.as_deref(),
+            user_volume: ctx.accounts.user_volume.as_ref(),
+            mint_policy: None,
+            user_share_rate: None,
+            hook_accounts,
+        },
+    )?;
+    if native_sol_flag {
+        close_native_source(ctx.accounts)?;
+    }
+    Ok(())
+}
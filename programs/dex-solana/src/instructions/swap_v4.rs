@@ -0,0 +1,365 @@
+use super::{SwapArgs, SwapPolicyContext, common_swap_v3};
+use crate::constants::*;
+use crate::error::ErrorCode;
+use crate::processor::*;
+use crate::state::{integrator::Integrator, router_config::RouterConfig, user_volume::UserVolume};
+use crate::utils::*;
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenInterface},
+};
+
+/// The fee settings of a v4 swap, the rates use the v3 denominators
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct FeeConfig {
+    pub mode: SwapMode,
+    pub commission_rate: u32,
+    /// true: commission on the from amount, false: commission on the to amount
+    pub commission_direction: bool,
+    pub platform_fee_rate: Option<u16>,
+    pub trim_rate: Option<u8>,
+    pub charge_rate: Option<u16>,
+    pub user_share_rate: Option<u16>,
+}
+
+/// The fields after order_id are versioned, a client encodes the fields of its version only:
+/// v2 adds hook_accounts_len, v3 adds mint_policy and v4 adds ephemeral_wsol
+#[derive(AnchorSerialize, Clone)]
+pub struct SwapV4Args {
+    pub version: u8,
+    pub swap: SwapArgs,
+    pub fee_config: Option<FeeConfig>,
+    /// The owner of the destination token account, if it must be checked
+    pub receiver: Option<Pubkey>,
+    /// Unix timestamp after which the swap is rejected
+    pub deadline: Option<i64>,
+    /// SWAP_V4_FLAG_*
+    pub flags: u8,
+    pub order_id: u64,
+    /// The number of trailing remaining accounts resolving the transfer hooks of the mints,
+    /// including the memo program if a destination requires incoming memos
+    pub hook_accounts_len: u8,
+    /// Reject or flag the mints with risky extensions, merged with the configured default
+    pub mint_policy: Option<MintPolicy>,
+    /// Use the ephemeral wSOL account of the payer as the source or destination token account,
+    /// it is created and funded before the swap and closed after it
+    pub ephemeral_wsol: Option<EphemeralWsolArgs>,
+}
+
+impl AnchorDeserialize for SwapV4Args {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        let version = u8::deserialize_reader(reader)?;
+        if version == 0 || version > SWAP_V4_VERSION {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "invalid swap args version",
+            ));
+        }
+        Ok(SwapV4Args {
+            version,
+            swap: SwapArgs::deserialize_reader(reader)?,
+            fee_config: Option::<FeeConfig>::deserialize_reader(reader)?,
+            receiver: Option::<Pubkey>::deserialize_reader(reader)?,
+            deadline: Option::<i64>::deserialize_reader(reader)?,
+            flags: u8::deserialize_reader(reader)?,
+            order_id: u64::deserialize_reader(reader)?,
+            hook_accounts_len: if version >= 2 { u8::deserialize_reader(reader)? } else { 0 },
+            mint_policy: if version >= 3 {
+                Option::<MintPolicy>::deserialize_reader(reader)?
+            } else {
+                None
+            },
+            ephemeral_wsol: if version >= 4 {
+                Option::<EphemeralWsolArgs>::deserialize_reader(reader)?
+            } else {
+                None
+            },
+        })
+    }
+}
+
+#[derive(Accounts)]
+pub struct SwapV4Accounts<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: source token account of the payer, may be an ephemeral wSOL account created by the
+    /// swap, checked in the handler
+    #[account(mut)]
+    pub source_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: destination token account, may be an ephemeral wSOL account created by the swap,
+    /// checked in the handler
+    #[account(mut)]
+    pub destination_token_account: UncheckedAccount<'info>,
+
+    pub source_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub destination_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: commission account
+    #[account(mut)]
+    pub commission_account: Option<AccountInfo<'info>>,
+
+    /// CHECK: platform fee account
+    #[account(mut)]
+    pub platform_fee_account: Option<AccountInfo<'info>>,
+
+    /// CHECK: trim account
+    #[account(mut)]
+    pub trim_account: Option<AccountInfo<'info>>,
+
+    /// CHECK: charge account
+    #[account(mut)]
+    pub charge_account: Option<AccountInfo<'info>>,
+
+    /// CHECK: sa_authority
+    #[account(mut)]
+    pub sa_authority: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    pub source_token_sa: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    pub destination_token_sa: Option<UncheckedAccount<'info>>,
+
+    pub source_token_program: Option<Interface<'info, TokenInterface>>,
+    pub destination_token_program: Option<Interface<'info, TokenInterface>>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+    pub system_program: Option<Program<'info, System>>,
+
+    /// CHECK: receives the rent of the ephemeral wSOL account, the payer if None
+    #[account(mut)]
+    pub rent_receiver: Option<UncheckedAccount<'info>>,
+
+    /// The integrator agreement the fees are validated against
+    pub integrator: Option<AccountLoader<'info, Integrator>>,
+
+    /// The volume accumulator of the payer, applies the fee tiers of the integrator
+    #[account(mut)]
+    pub user_volume: Option<AccountLoader<'info, UserVolume>>,
+
+    /// The router config, holds the default mint policy and the integrator requirement, the
+    /// default policy applies if None
+    #[account(
+        seeds = [ROUTER_CONFIG_SEED.as_bytes()],
+        bump = router_config.load()?.bump,
+    )]
+    pub router_config: Option<AccountLoader<'info, RouterConfig>>,
+}
+
+/// Open the ephemeral wSOL account standing in for the source or destination token account,
+/// returns it with its token program
+fn open_swap_ephemeral_wsol<'a>(
+    accounts: &SwapV4Accounts<'a>,
+    args: &EphemeralWsolArgs,
+) -> Result<(AccountInfo<'a>, AccountInfo<'a>)> {
+    let payer = accounts.payer.key();
+    let is_ephemeral = |mint: &Pubkey, token_account: &Pubkey| {
+        is_native_mint(mint) && ephemeral_wsol_address(mint, &payer, args) == Some(*token_account)
+    };
+    let (wsol_account, wsol_mint, token_program) =
+        if is_ephemeral(&accounts.source_mint.key(), &accounts.source_token_account.key()) {
+            (
+                accounts.source_token_account.to_account_info(),
+                accounts.source_mint.to_account_info(),
+                accounts
+                    .source_token_program
+                    .as_ref()
+                    .ok_or(ErrorCode::SourceTokenProgramIsNone)?,
+            )
+        } else if is_ephemeral(
+            &accounts.destination_mint.key(),
+            &accounts.destination_token_account.key(),
+        ) {
+            (
+                accounts.destination_token_account.to_account_info(),
+                accounts.destination_mint.to_account_info(),
+                accounts
+                    .destination_token_program
+                    .as_ref()
+                    .ok_or(ErrorCode::DestinationTokenProgramIsNone)?,
+            )
+        } else {
+            return Err(ErrorCode::InvalidTokenAccount.into());
+        };
+    let token_program = token_program.to_account_info();
+    open_ephemeral_wsol(
+        &accounts.payer.to_account_info(),
+        &wsol_account,
+        &accounts.payer.to_account_info(),
+        &wsol_mint,
+        &token_program,
+        args,
+    )?;
+    Ok((wsol_account, token_program))
+}
+
+pub fn swap_v4_handler<'a>(
+    ctx: Context<'_, '_, 'a, 'a, SwapV4Accounts<'a>>,
+    args: SwapV4Args,
+) -> Result<()> {
+    require!(
+        args.version > 0 && args.version <= SWAP_V4_VERSION,
+        ErrorCode::InvalidSwapArgsVersion
+    );
+    require!(args.flags & !SWAP_V4_FLAGS_MASK == 0, ErrorCode::InvalidSwapFlags);
+    if let Some(deadline) = args.deadline {
+        require!(Clock::get()?.unix_timestamp <= deadline, ErrorCode::SwapDeadlineExceeded);
+    }
+    let ephemeral_wsol = match &args.ephemeral_wsol {
+        Some(ephemeral_wsol) => Some(open_swap_ephemeral_wsol(ctx.accounts, ephemeral_wsol)?),
+        None => None,
+    };
+    let mut source_token_account =
+        associate_convert_token_account(&ctx.accounts.source_token_account)?;
+    let mut destination_token_account =
+        associate_convert_token_account(&ctx.accounts.destination_token_account)?;
+    require_keys_eq!(
+        source_token_account.mint,
+        ctx.accounts.source_mint.key(),
+        anchor_lang::error::ErrorCode::ConstraintTokenMint
+    );
+    require_keys_eq!(
+        source_token_account.owner,
+        ctx.accounts.payer.key(),
+        anchor_lang::error::ErrorCode::ConstraintTokenOwner
+    );
+    require_keys_eq!(
+        destination_token_account.mint,
+        ctx.accounts.destination_mint.key(),
+        anchor_lang::error::ErrorCode::ConstraintTokenMint
+    );
+    if let Some(receiver) = args.receiver {
+        require_keys_eq!(
+            destination_token_account.owner,
+            receiver,
+            ErrorCode::InvalidDestinationTokenAccount
+        );
+    }
+    let acc_close_flag = args.flags & SWAP_V4_FLAG_ACC_CLOSE != 0;
+
+    let fee_config = args.fee_config.unwrap_or(FeeConfig {
+        mode: SwapMode::ToC,
+        commission_rate: 0,
+        commission_direction: false,
+        platform_fee_rate: None,
+        trim_rate: None,
+        charge_rate: None,
+        user_share_rate: None,
+    });
+    log_rate_info_v3_enhanced(
+        fee_config.commission_rate,
+        fee_config.platform_fee_rate,
+        fee_config.trim_rate.unwrap_or(0),
+        fee_config.charge_rate.unwrap_or(0),
+        fee_config.commission_direction,
+        acc_close_flag,
+    );
+    let router_config =
+        ctx.accounts.router_config.as_ref().map(|config| config.load()).transpose()?;
+    let integrator =
+        ctx.accounts.integrator.as_ref().map(|integrator| integrator.load()).transpose()?;
+    let (route_accounts, hook_accounts) =
+        split_hook_accounts(ctx.remaining_accounts, args.hook_accounts_len)?;
+    let trim_account = ctx.accounts.trim_account.as_ref();
+    let charge_account = ctx.accounts.charge_account.as_ref();
+
+    match fee_config.mode {
+        SwapMode::ToC => {
+            // Trim, charge, user share and closing the wSOL account are only handled through the SA
+            require!(fee_config.trim_rate.unwrap_or(0) == 0, ErrorCode::InvalidTrimRate);
+            require!(fee_config.charge_rate.unwrap_or(0) == 0, ErrorCode::InvalidChargeRate);
+            require!(fee_config.user_share_rate.unwrap_or(0) == 0, ErrorCode::InvalidUserShareRate);
+            require!(!acc_close_flag, ErrorCode::InvalidSwapFlags);
+            common_swap_v3(
+                &SwapToCProcessor(FeeAccountsPayout),
+                &ctx.accounts.payer,
+                &mut source_token_account,
+                &mut destination_token_account,
+                &ctx.accounts.source_mint,
+                &ctx.accounts.destination_mint,
+                &ctx.accounts.sa_authority,
+                &mut ctx.accounts.source_token_sa,
+                &mut ctx.accounts.destination_token_sa,
+                &ctx.accounts.source_token_program,
+                &ctx.accounts.destination_token_program,
+                &ctx.accounts.associated_token_program,
+                &ctx.accounts.system_program,
+                route_accounts,
+                args.swap,
+                args.order_id,
+                fee_config.commission_rate,
+                fee_config.commission_direction,
+                &ctx.accounts.commission_account,
+                fee_config.platform_fee_rate,
+                &ctx.accounts.platform_fee_account,
+                None,
+                None,
+                None,
+                None,
+                false,
+                SwapPolicyContext {
+                    router_config: router_config.as_deref(),
+                    integrator: integrator.as_deref(),
+                    user_volume: ctx.accounts.user_volume.as_ref(),
+                    mint_policy: args.mint_policy,
+                    user_share_rate: None,
+                    hook_accounts,
+                },
+            )?;
+        }
+        SwapMode::ToB => {
+            common_swap_v3(
+                &SwapToBProcessor,
+                &ctx.accounts.payer,
+                &mut source_token_account,
+                &mut destination_token_account,
+                &ctx.accounts.source_mint,
+                &ctx.accounts.destination_mint,
+                &ctx.accounts.sa_authority,
+                &mut ctx.accounts.source_token_sa,
+                &mut ctx.accounts.destination_token_sa,
+                &ctx.accounts.source_token_program,
+                &ctx.accounts.destination_token_program,
+                &ctx.accounts.associated_token_program,
+                &ctx.accounts.system_program,
+                route_accounts,
+                args.swap,
+                args.order_id,
+                fee_config.commission_rate,
+                fee_config.commission_direction,
+                &ctx.accounts.commission_account,
+                fee_config.platform_fee_rate,
+                &ctx.accounts.platform_fee_account,
+                fee_config.trim_rate,
+                fee_config.charge_rate,
+                trim_account,
+                charge_account,
+                acc_close_flag,
+                SwapPolicyContext {
+                    router_config: router_config.as_deref(),
+                    integrator: integrator.as_deref(),
+                    user_volume: ctx.accounts.user_volume.as_ref(),
+                    mint_policy: args.mint_policy,
+                    user_share_rate: fee_config.user_share_rate,
+                    hook_accounts,
+                },
+            )?;
+        }
+    }
+
+    if let Some((wsol_account, token_program)) = ephemeral_wsol {
+        let payer = ctx.accounts.payer.to_account_info();
+        let rent_receiver =
+            ctx.accounts.rent_receiver.as_ref().map(|account| account.to_account_info());
+        close_ephemeral_wsol(
+            &wsol_account,
+            &payer,
+            &token_program,
+            Some(rent_receiver.as_ref().unwrap_or(&payer)),
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use crate::constants::*;
+use crate::state::{integrator::Integrator, user_volume::UserVolume};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct InitUserVolume<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: the user whose swaps are accumulated
+    pub user: UncheckedAccount<'info>,
+
+    pub integrator: AccountLoader<'info, Integrator>,
+
+    /// The volume accumulator of the user under the integrator
+    #[account(
+        init,
+        seeds = [
+            USER_VOLUME_SEED.as_bytes(),
+            &integrator.load()?.integrator_id.to_le_bytes(),
+            user.key().as_ref(),
+        ],
+        bump,
+        payer = payer,
+        space = UserVolume::LEN
+    )]
+    pub user_volume: AccountLoader<'info, UserVolume>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn init_user_volume_handler(ctx: Context<InitUserVolume>) -> Result<()> {
+    let user_volume = &mut ctx.accounts.user_volume.load_init()?;
+    user_volume.bump = ctx.bumps.user_volume;
+    user_volume.integrator_id = ctx.accounts.integrator.load()?.integrator_id;
+    user_volume.user = ctx.accounts.user.key();
+    Ok(())
+}
@@ -26,7 +26,10 @@ pub struct PlatformFeeWrapUnwrapAccounts<'info> {
     )]
     pub payer_wsol_account: InterfaceAccount<'info, TokenAccount>,
 
-    #[account(address = wsol_program::id())]
+    #[account(
+        constraint = is_native_mint(&wsol_mint.key()) @ ErrorCode::InvalidMint,
+        mint::token_program = token_program,
+    )]
     pub wsol_mint: InterfaceAccount<'info, Mint>,
 
     #[account(
@@ -129,7 +132,14 @@ pub fn platform_fee_wrap_unwrap_handler_v3<'a>(
     )?;
 
     // Execute wrap/unwrap process
-    execute_wrap_unwrap_process_v3(&ctx.accounts, commission_info.wrap_direction, amount_in)?;
+    // The remaining accounts resolve the transfer hook of the wSOL mint
+    let hook_accounts = ctx.remaining_accounts;
+    execute_wrap_unwrap_process_v3(
+        &ctx.accounts,
+        commission_info.wrap_direction,
+        amount_in,
+        hook_accounts,
+    )?;
 
     // Transfer fees if any
     if commission_amount > 0 || platform_fee_amount > 0 {
@@ -139,6 +149,8 @@ pub fn platform_fee_wrap_unwrap_handler_v3<'a>(
             commission_info.is_charge_sol(),
             &ctx.accounts.authority_pda,
             &ctx.accounts.wsol_sa,
+            &ctx.accounts.wsol_mint.key(),
+            &ctx.accounts.token_program.key(),
         )?;
 
         transfer_wrap_unwrap_fees_and_log(
@@ -154,6 +166,7 @@ pub fn platform_fee_wrap_unwrap_handler_v3<'a>(
             tob,                         // Pass tob flag
             &ctx.accounts.authority_pda, // Pass authority_pda
             &ctx.accounts.wsol_sa,       // Pass wsol_sa
+            hook_accounts,
         )?;
     }
 
@@ -175,6 +188,7 @@ fn execute_wrap_unwrap_process_v3<'info>(
     accounts: &PlatformFeeWrapUnwrapAccounts<'info>,
     wrap_direction: bool,
     amount: u64,
+    hook_accounts: &[AccountInfo<'info>],
 ) -> Result<()> {
     if wrap_direction {
         wrap_process_v3(
@@ -196,6 +210,7 @@ fn execute_wrap_unwrap_process_v3<'info>(
             (**temp_wsol_account).clone(),
             amount,
             accounts.token_program.clone(),
+            hook_accounts,
         )
     }
 }
@@ -224,9 +239,10 @@ pub fn unwrap_process_v3<'info>(
     temp_wsol_account: InterfaceAccount<'info, TokenAccount>,
     amount: u64,
     token_program: Interface<'info, TokenInterface>,
+    hook_accounts: &[AccountInfo<'info>],
 ) -> Result<()> {
     // Transfer WSOL to temporary account
-    transfer_token(
+    transfer_token_with_hook(
         payer.to_account_info(),
         wsol_account.to_account_info(),
         temp_wsol_account.to_account_info(),
@@ -235,6 +251,7 @@ pub fn unwrap_process_v3<'info>(
         amount,
         wsol_mint.decimals,
         None,
+        hook_accounts,
     )?;
 
     // Close temporary account to unwrap WSOL to SOL
@@ -250,9 +267,8 @@ pub fn unwrap_process_v3<'info>(
 }
 
 /// Get wrap/unwrap mint addresses
-pub fn get_wrap_unwrap_mints(wrap_direction: bool) -> (Pubkey, Pubkey) {
+pub fn get_wrap_unwrap_mints(wrap_direction: bool, wsol_mint_id: Pubkey) -> (Pubkey, Pubkey) {
     let sol_mint_id = system_program::id();
-    let wsol_mint_id = wsol_program::id();
 
     if wrap_direction {
         // Wrap: SOL -> WSOL
@@ -271,7 +287,8 @@ fn log_wrap_unwrap_initial_info<'info>(
     amount_in: u64,
     order_id: u64,
 ) -> Result<(Pubkey, Pubkey, u64, u64)> {
-    let (source_mint, destination_mint) = get_wrap_unwrap_mints(wrap_direction);
+    let (source_mint, destination_mint) =
+        get_wrap_unwrap_mints(wrap_direction, payer_wsol_account.mint);
 
     log_swap_basic_info(order_id, &source_mint, &destination_mint, &payer.key(), &payer.key());
 
@@ -350,7 +367,9 @@ fn transfer_wrap_unwrap_fees_and_log<'info>(
     tob: bool,                                          // New parameter
     authority_pda_account: &Option<AccountInfo<'info>>, // New parameter
     wsol_sa_account: &Option<AccountInfo<'info>>,       // New parameter
+    hook_accounts: &[AccountInfo<'info>],
 ) -> Result<()> {
+    let transfer_ctx = TransferContext { hook_accounts, memo: None };
     // Validate accounts before transfer
     if commission_amount > 0 {
         require!(commission_account.is_some(), ErrorCode::CommissionAccountIsNone);
@@ -359,212 +378,69 @@ fn transfer_wrap_unwrap_fees_and_log<'info>(
         require!(platform_fee_account.is_some(), ErrorCode::PlatformFeeAccountIsNone);
     }
 
-    if commission_info.is_charge_sol() {
-        // Transfer SOL fees
-        transfer_sol_fees(
-            payer,
-            commission_amount,
-            platform_fee_amount,
-            commission_account,
-            platform_fee_account,
-            commission_info.commission_direction,
-            tob,                   // Pass tob flag
-            authority_pda_account, // Pass authority_pda for TOB mode
-        )?;
-    } else {
-        // Transfer token fees
-        transfer_token_fees(
-            payer,
-            payer_wsol_account,
-            wsol_mint,
-            token_program,
-            commission_amount,
-            platform_fee_amount,
-            commission_account,
-            platform_fee_account,
-            commission_info.commission_direction,
-            tob,                   // Pass tob flag
-            authority_pda_account, // Pass for signing
-            wsol_sa_account,       // Pass for WSOL transfers
-        )?;
-    }
-
-    Ok(())
-}
-
-/// Transfer SOL fees
-fn transfer_sol_fees<'info>(
-    payer: &AccountInfo<'info>,
-    commission_amount: u64,
-    platform_fee_amount: u64,
-    commission_account: &Option<AccountInfo<'info>>,
-    platform_fee_account: &Option<AccountInfo<'info>>,
-    commission_direction: bool,
-    tob: bool,                                          // New parameter
-    authority_pda_account: &Option<AccountInfo<'info>>, // New parameter
-) -> Result<()> {
-    if tob {
-        // TOB mode: require authority_pda
+    let total_amount =
+        commission_amount.checked_add(platform_fee_amount).ok_or(ErrorCode::CalculationError)?;
+    let is_charge_sol = commission_info.is_charge_sol();
+    let payer_wsol_account = payer_wsol_account.to_account_info();
+    let source = if tob {
+        // TOB mode: two-step transfer, the fees are first moved to the SA by the payer, then paid
+        // out of the SA
         let authority_pda =
             authority_pda_account.as_ref().ok_or(ErrorCode::TobAuthorityPdaRequired)?;
-
-        // TOB mode: two-step transfer
-        // Step 1: payer → authority_pda (normal transfer, not fee)
-        let total_amount = commission_amount
-            .checked_add(platform_fee_amount)
-            .ok_or(ErrorCode::CalculationError)?;
-
-        transfer_sol(
-            payer.to_account_info(),
-            authority_pda.to_account_info(),
-            total_amount,
-            None, // User signs
-        )?;
-
-        // Step 2: authority_pda → commission/platform accounts (actual fee payment)
-        if commission_amount > 0 {
-            let commission_account = commission_account.as_ref().unwrap();
-            let adjust_amount = transfer_sol_fee(
-                authority_pda,
-                commission_account,
-                commission_amount,
-                Some(SA_AUTHORITY_SEED),
+        if is_charge_sol {
+            transfer_sol(
+                payer.to_account_info(),
+                authority_pda.to_account_info(),
+                total_amount,
+                None, // User signs
             )?;
-            log_commission_info(commission_direction, commission_amount, adjust_amount);
-            commission_account.key().log();
-        }
-
-        if platform_fee_amount > 0 {
-            let platform_fee_account = platform_fee_account.as_ref().unwrap();
-            let adjust_amount = transfer_sol_fee(
-                authority_pda,
-                platform_fee_account,
-                platform_fee_amount,
-                Some(SA_AUTHORITY_SEED),
+        } else {
+            let wsol_sa = wsol_sa_account.as_ref().ok_or(ErrorCode::TobWsolSaRequired)?;
+            transfer_token_with_hook(
+                payer.to_account_info(),
+                payer_wsol_account.to_account_info(),
+                wsol_sa.to_account_info(),
+                wsol_mint.to_account_info(),
+                token_program.to_account_info(),
+                total_amount,
+                wsol_mint.decimals,
+                None, // User signs
+                hook_accounts,
             )?;
-            log_platform_fee_info(platform_fee_amount, adjust_amount, &platform_fee_account.key());
         }
-    } else {
-        // TOC mode: direct transfer (existing logic)
-        if commission_amount > 0 {
-            let commission_account = commission_account.as_ref().unwrap();
-            let adjust_amount =
-                transfer_sol_fee(payer, commission_account, commission_amount, None)?;
-            log_commission_info(commission_direction, commission_amount, adjust_amount);
-            commission_account.key().log();
-        }
-
-        if platform_fee_amount > 0 {
-            let platform_fee_account = platform_fee_account.as_ref().unwrap();
-            let adjust_amount =
-                transfer_sol_fee(payer, platform_fee_account, platform_fee_amount, None)?;
-            log_platform_fee_info(platform_fee_amount, adjust_amount, &platform_fee_account.key());
-        }
-    }
-
-    Ok(())
-}
-
-/// Transfer token fees
-fn transfer_token_fees<'info>(
-    payer: &AccountInfo<'info>,
-    payer_wsol_account: &InterfaceAccount<'info, TokenAccount>,
-    wsol_mint: &InterfaceAccount<'info, Mint>,
-    token_program: &Interface<'info, TokenInterface>,
-    commission_amount: u64,
-    platform_fee_amount: u64,
-    commission_account: &Option<AccountInfo<'info>>,
-    platform_fee_account: &Option<AccountInfo<'info>>,
-    commission_direction: bool,
-    tob: bool,                                          // New parameter
-    authority_pda_account: &Option<AccountInfo<'info>>, // New parameter
-    wsol_sa_account: &Option<AccountInfo<'info>>,       // New parameter
-) -> Result<()> {
-    if tob {
-        // TOB mode: require both accounts
-        let authority_pda =
-            authority_pda_account.as_ref().ok_or(ErrorCode::TobAuthorityPdaRequired)?;
-        let wsol_sa = wsol_sa_account.as_ref().ok_or(ErrorCode::TobWsolSaRequired)?;
-
-        // TOB mode: two-step transfer
-        // Step 1: payer_wsol_account → wsol_sa (normal transfer, not fee)
-        let total_amount = commission_amount
-            .checked_add(platform_fee_amount)
-            .ok_or(ErrorCode::CalculationError)?;
-
-        transfer_token(
-            payer.to_account_info(),              // authority (payer is the owner)
-            payer_wsol_account.to_account_info(), // from
-            wsol_sa.to_account_info(),            // to
-            wsol_mint.to_account_info(),          // mint
-            token_program.to_account_info(),      // token_program
-            total_amount,
-            wsol_mint.decimals,
-            None, // User signs
-        )?;
-
-        // Step 2: wsol_sa → commission/platform accounts (actual fee payment)
-        if commission_amount > 0 {
-            let commission_account = commission_account.as_ref().unwrap();
-            transfer_token_fee(
-                authority_pda, // authority for wsol_sa
-                wsol_sa,       // from account
-                wsol_mint,
-                token_program,
-                commission_account,
-                commission_amount,
-                Some(SA_AUTHORITY_SEED),
-            )?;
-            log_commission_info(commission_direction, commission_amount, 0);
-            commission_account.key().log();
-        }
-
-        if platform_fee_amount > 0 {
-            let platform_fee_account = platform_fee_account.as_ref().unwrap();
-            transfer_token_fee(
-                authority_pda, // authority for wsol_sa
-                wsol_sa,       // from account
-                wsol_mint,
-                token_program,
-                platform_fee_account,
-                platform_fee_amount,
-                Some(SA_AUTHORITY_SEED),
-            )?;
-            log_platform_fee_info(platform_fee_amount, 0, &platform_fee_account.key());
+        FeeSource {
+            authority: authority_pda,
+            token_account: wsol_sa_account.as_ref(),
+            token_mint: wsol_mint,
+            token_program: Some(token_program),
+            signer_seeds: Some(SA_AUTHORITY_SEED),
+            is_charge_sol,
+            legacy_transfer: false,
+            transfer_ctx,
         }
     } else {
-        // TOC mode: direct transfer (existing logic)
-        if commission_amount > 0 {
-            let commission_account = commission_account.as_ref().unwrap();
-            transfer_token_fee(
-                payer,
-                &payer_wsol_account.to_account_info(),
-                wsol_mint,
-                token_program,
-                commission_account,
-                commission_amount,
-                None,
-            )?;
-            log_commission_info(commission_direction, commission_amount, 0);
-            commission_account.key().log();
-        }
-
-        if platform_fee_amount > 0 {
-            let platform_fee_account = platform_fee_account.as_ref().unwrap();
-            transfer_token_fee(
-                payer,
-                &payer_wsol_account.to_account_info(),
-                wsol_mint,
-                token_program,
-                platform_fee_account,
-                platform_fee_amount,
-                None,
-            )?;
-            log_platform_fee_info(platform_fee_amount, 0, &platform_fee_account.key());
+        // TOC mode: direct transfer
+        FeeSource {
+            authority: payer,
+            token_account: Some(&payer_wsol_account),
+            token_mint: wsol_mint,
+            token_program: Some(token_program),
+            signer_seeds: None,
+            is_charge_sol,
+            legacy_transfer: false,
+            transfer_ctx,
         }
+    };
+    FeeRecipients {
+        commission_account: commission_account.as_ref(),
+        platform_fee_account: platform_fee_account.as_ref(),
+        ..Default::default()
     }
-
-    Ok(())
+    .pay_fees(
+        &source,
+        &FeeAmounts { commission_amount, platform_fee_amount },
+        commission_info.commission_direction,
+    )
 }
 
 /// Validate TOB accounts
@@ -573,6 +449,8 @@ fn validate_tob_accounts<'info>(
     is_charge_sol: bool,
     authority_pda_account: &Option<AccountInfo<'info>>,
     wsol_sa_account: &Option<AccountInfo<'info>>,
+    wsol_mint: &Pubkey,
+    token_program: &Pubkey,
 ) -> Result<()> {
     if !tob {
         return Ok(());
@@ -587,7 +465,11 @@ fn validate_tob_accounts<'info>(
     if !is_charge_sol {
         let wsol_sa = wsol_sa_account.as_ref().ok_or(ErrorCode::TobWsolSaRequired)?;
 
-        require_keys_eq!(wsol_sa.key(), wsol_sa::id(), ErrorCode::InvalidWsolSa);
+        require_keys_eq!(
+            wsol_sa.key(),
+            native_mint_sa(wsol_mint, token_program),
+            ErrorCode::InvalidWsolSa
+        );
     }
 
     Ok(())
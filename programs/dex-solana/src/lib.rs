@@ -17,6 +17,8 @@ pub use instructions::*;
 pub use limitorder::instructions as limitorder_instructions;
 pub use limitorder::instructions::*;
 pub use processor::*;
+pub use utils::fee_quote::{FeeQuote, QuoteFeesArgs, SwapMode};
+pub use utils::mint_policy::{MintPolicy, MintRiskAction};
 
 #[cfg(feature = "staging")]
 declare_id!("preZmu827KVPCoQ4LYwSoec13x6seQrKA3QpjgDtx1R");
@@ -148,6 +150,12 @@ pub mod dex_solana {
     }
 
     // ******************** Swap V3 ******************** //
+    // The entrypoints taking hook_accounts_len read the last hook_accounts_len remaining accounts
+    // as the transfer hook accounts of the mints, including the memo program if a fee or
+    // destination account requires incoming memos
+    /// commission_info bit29 sells native SOL: the source token account is the ephemeral wSOL
+    /// account of the payer (NATIVE_SOL_WSOL_SEED), created and funded by the swap and closed
+    /// after it, refunding the rent and the unused lamports
     pub fn swap_v3<'a>(
         ctx: Context<'_, '_, 'a, 'a, CommissionProxySwapAccountsV3<'a>>,
         args: SwapArgs,
@@ -164,6 +172,9 @@ pub mod dex_solana {
         )
     }
 
+    /// commission_info bit29 sells native SOL: the source token account is the ephemeral wSOL
+    /// account of the payer (NATIVE_SOL_WSOL_SEED), created and funded by the swap and closed
+    /// after it, refunding the rent and the unused lamports
     pub fn swap_tob_v3<'a>(
         ctx: Context<'_, '_, 'a, 'a, CommissionProxySwapAccountsV3<'a>>,
         args: SwapArgs,
@@ -185,6 +196,9 @@ pub mod dex_solana {
     /// Swap ToB with optional specified receiver
     /// - For normal token swaps: sol_receiver should be None
     /// - For swap to SOL with custom receiver: sol_receiver should be Some and acc_close_flag must be true
+    /// - For swap to tokens with custom receiver: sol_receiver is the wallet and
+    ///   destination_token_account its associated token account of the destination mint, created
+    ///   if needed
     pub fn swap_tob_v3_with_receiver<'a>(
         ctx: Context<'_, '_, 'a, 'a, CommissionProxySwapAccountsV3WithReceiver<'a>>,
         args: SwapArgs,
@@ -223,6 +237,80 @@ pub mod dex_solana {
         )
     }
 
+    /// Swap ToB leaving user_share_rate of the trimmed surplus to the user,
+    /// the trailing remaining accounts are the trim account, then the charge account if charged
+    pub fn swap_tob_v3_with_user_share<'a>(
+        ctx: Context<'_, '_, 'a, 'a, CommissionProxySwapAccountsV3<'a>>,
+        args: SwapArgs,
+        commission_info: u32,
+        trim_rate: u8,
+        charge_rate: u16,
+        user_share_rate: u16,
+        platform_fee_rate: u16,
+        order_id: u64,
+        hook_accounts_len: u8,
+    ) -> Result<()> {
+        instructions::swap_tob_user_share_handler(
+            ctx,
+            args,
+            commission_info,
+            order_id,
+            trim_rate,
+            charge_rate,
+            user_share_rate,
+            Some(platform_fee_rate),
+            hook_accounts_len,
+        )
+    }
+
+    /// Swap with versioned args, explicit fee config and named fee accounts
+    pub fn swap_v4<'a>(
+        ctx: Context<'_, '_, 'a, 'a, SwapV4Accounts<'a>>,
+        args: SwapV4Args,
+    ) -> Result<()> {
+        instructions::swap_v4_handler(ctx, args)
+    }
+
+    /// Swap ToC with the commission split across the trailing remaining accounts
+    pub fn swap_v3_with_splits<'a>(
+        ctx: Context<'_, '_, 'a, 'a, CommissionProxySwapAccountsV3<'a>>,
+        args: SwapArgs,
+        commission_info: u32,
+        commission_rates: Vec<u32>,
+        platform_fee_rate: u16,
+        order_id: u64,
+        hook_accounts_len: u8,
+    ) -> Result<()> {
+        instructions::swap_toc_splits_handler(
+            ctx,
+            args,
+            commission_info,
+            commission_rates,
+            order_id,
+            Some(platform_fee_rate),
+            hook_accounts_len,
+        )
+    }
+
+    /// Swap ToC accruing the fees into the fee vault instead of transferring to the recipients
+    pub fn swap_v3_with_fee_vault<'a>(
+        ctx: Context<'_, '_, 'a, 'a, CommissionProxySwapAccountsV3WithFeeVault<'a>>,
+        args: SwapArgs,
+        commission_info: u32,
+        platform_fee_rate: u16,
+        order_id: u64,
+        hook_accounts_len: u8,
+    ) -> Result<()> {
+        instructions::swap_toc_fee_vault_handler(
+            ctx,
+            args,
+            commission_info,
+            order_id,
+            Some(platform_fee_rate),
+            hook_accounts_len,
+        )
+    }
+
     pub fn wrap_unwrap_v3<'a>(
         ctx: Context<'_, '_, 'a, 'a, PlatformFeeWrapUnwrapAccounts<'a>>,
         args: PlatformFeeWrapUnwrapArgs,
@@ -245,8 +333,195 @@ pub mod dex_solana {
         instructions::create_token_account_with_seed_handler(ctx, bump, seed)
     }
 
+    /// Quote the commission, platform fee, trim and charge of a v3 swap, returned as return data
+    pub fn quote_fees(ctx: Context<QuoteFees>, args: QuoteFeesArgs) -> Result<FeeQuote> {
+        instructions::quote_fees_handler(ctx, args)
+    }
+
+    // ******************** Fee Vault ******************** //
+    pub fn init_fee_vault(ctx: Context<InitFeeVault>) -> Result<()> {
+        instructions::init_fee_vault_handler(ctx)
+    }
+
+    pub fn init_fee_accrual(ctx: Context<InitFeeAccrual>) -> Result<()> {
+        instructions::init_fee_accrual_handler(ctx)
+    }
+
+    /// The remaining accounts resolve the transfer hook of the mint
+    pub fn withdraw_fees<'a>(ctx: Context<'_, '_, 'a, 'a, WithdrawFees<'a>>) -> Result<()> {
+        instructions::withdraw_fees_handler(ctx)
+    }
+
+    // ******************** Integrator ******************** //
+    pub fn init_integrator(
+        ctx: Context<InitIntegrator>,
+        integrator_id: u64,
+        max_commission_rate: u32,
+        platform_fee_share: u16,
+    ) -> Result<()> {
+        global_config_instructions::init_integrator_handler(
+            ctx,
+            integrator_id,
+            max_commission_rate,
+            platform_fee_share,
+        )
+    }
+
+    pub fn set_integrator_max_commission_rate(
+        ctx: Context<UpdateIntegrator>,
+        max_commission_rate: u32,
+    ) -> Result<()> {
+        global_config_instructions::set_integrator_max_commission_rate_handler(
+            ctx,
+            max_commission_rate,
+        )
+    }
+
+    pub fn set_integrator_platform_fee_share(
+        ctx: Context<UpdateIntegrator>,
+        platform_fee_share: u16,
+    ) -> Result<()> {
+        global_config_instructions::set_integrator_platform_fee_share_handler(
+            ctx,
+            platform_fee_share,
+        )
+    }
+
+    pub fn set_integrator_platform_fee_recipient(
+        ctx: Context<UpdateIntegrator>,
+        platform_fee_recipient: Pubkey,
+    ) -> Result<()> {
+        global_config_instructions::set_integrator_platform_fee_recipient_handler(
+            ctx,
+            platform_fee_recipient,
+        )
+    }
+
+    pub fn set_integrator_max_trim_rate(
+        ctx: Context<UpdateIntegrator>,
+        max_trim_rate: u8,
+    ) -> Result<()> {
+        global_config_instructions::set_integrator_max_trim_rate_handler(ctx, max_trim_rate)
+    }
+
+    pub fn set_integrator_enabled(ctx: Context<UpdateIntegrator>, enabled: bool) -> Result<()> {
+        global_config_instructions::set_integrator_enabled_handler(ctx, enabled)
+    }
+
+    pub fn add_integrator_fee_recipient(
+        ctx: Context<UpdateIntegrator>,
+        recipient: Pubkey,
+    ) -> Result<()> {
+        global_config_instructions::add_integrator_fee_recipient_handler(ctx, recipient)
+    }
+
+    pub fn remove_integrator_fee_recipient(
+        ctx: Context<UpdateIntegrator>,
+        recipient: Pubkey,
+    ) -> Result<()> {
+        global_config_instructions::remove_integrator_fee_recipient_handler(ctx, recipient)
+    }
+
+    pub fn set_integrator_reference_mint(
+        ctx: Context<UpdateIntegrator>,
+        reference_mint: Pubkey,
+    ) -> Result<()> {
+        global_config_instructions::set_integrator_reference_mint_handler(ctx, reference_mint)
+    }
+
+    pub fn set_integrator_fee_tier(
+        ctx: Context<UpdateIntegrator>,
+        index: u8,
+        min_volume: u64,
+        commission_rate: u32,
+    ) -> Result<()> {
+        global_config_instructions::set_integrator_fee_tier_handler(
+            ctx,
+            index,
+            min_volume,
+            commission_rate,
+        )
+    }
+
+    pub fn init_user_volume(ctx: Context<InitUserVolume>) -> Result<()> {
+        instructions::init_user_volume_handler(ctx)
+    }
+
+    // ******************** Router Config ******************** //
+    pub fn init_router_config(ctx: Context<InitRouterConfig>) -> Result<()> {
+        global_config_instructions::init_router_config_handler(ctx)
+    }
+
+    /// Set the default mint policy of the swaps
+    pub fn set_mint_policy(
+        ctx: Context<UpdateRouterConfig>,
+        mint_policy: MintPolicy,
+    ) -> Result<()> {
+        global_config_instructions::set_mint_policy_handler(ctx, mint_policy)
+    }
+
+    /// Require an integrator agreement for the v3 and v4 swaps charging fees
+    pub fn set_integrator_required(
+        ctx: Context<UpdateRouterConfig>,
+        integrator_required: bool,
+    ) -> Result<()> {
+        global_config_instructions::set_integrator_required_handler(ctx, integrator_required)
+    }
+
+    /// Allow claim_batch to claim to the receiver
+    pub fn add_claim_receiver(ctx: Context<UpdateRouterConfig>, receiver: Pubkey) -> Result<()> {
+        global_config_instructions::add_claim_receiver_handler(ctx, receiver)
+    }
+
+    pub fn remove_claim_receiver(ctx: Context<UpdateRouterConfig>, receiver: Pubkey) -> Result<()> {
+        global_config_instructions::remove_claim_receiver_handler(ctx, receiver)
+    }
+
+    /// Allow the signer to claim from the SA, limited to the mint if set and to max_amount per
+    /// CLAIM_PERIOD_SECONDS if not 0, which requires the mint, updates an existing claimer
+    pub fn set_claimer(
+        ctx: Context<UpdateRouterConfig>,
+        authority: Pubkey,
+        mint: Option<Pubkey>,
+        max_amount: u64,
+    ) -> Result<()> {
+        global_config_instructions::set_claimer_handler(ctx, authority, mint, max_amount)
+    }
+
+    pub fn remove_claimer(ctx: Context<UpdateRouterConfig>, authority: Pubkey) -> Result<()> {
+        global_config_instructions::remove_claimer_handler(ctx, authority)
+    }
+
     // ******************** Claim ******************** //
     pub fn claim<'a>(ctx: Context<'_, '_, 'a, 'a, ClaimAccounts<'a>>) -> Result<()> {
         instructions::claim_handler(ctx)
     }
+
+    /// Create the claim ledger of the receiver for the mint, the default pubkey for the SA SOL
+    pub fn init_claim_ledger(ctx: Context<InitClaimLedger>, mint: Pubkey) -> Result<()> {
+        instructions::init_claim_ledger_handler(ctx, mint)
+    }
+
+    /// Claim the balances of several SA token accounts, and optionally the SOL of the SA, to an
+    /// allowed receiver, recording the claims in the claim ledgers of the receiver. The remaining
+    /// accounts are CLAIM_BATCH_ACCOUNTS_LEN accounts per mint, followed by hook_accounts_len
+    /// accounts resolving the transfer hooks of the mints.
+    pub fn claim_batch<'a>(
+        ctx: Context<'_, '_, 'a, 'a, ClaimBatchAccounts<'a>>,
+        claim_sol: bool,
+        hook_accounts_len: u8,
+    ) -> Result<()> {
+        instructions::claim_batch_handler(ctx, claim_sol, hook_accounts_len)
+    }
+
+    /// Swap the fees accrued in an SA token account into USDC or wSOL, then claim to the receiver.
+    /// The remaining accounts are the route accounts followed by hook_accounts_len accounts
+    /// resolving the transfer hook of the settlement mint.
+    pub fn swap_and_claim<'a>(
+        ctx: Context<'_, '_, 'a, 'a, SwapAndClaimAccounts<'a>>,
+        args: SwapArgs,
+        hook_accounts_len: u8,
+    ) -> Result<()> {
+        instructions::swap_and_claim_handler(ctx, args, hook_accounts_len)
+    }
 }
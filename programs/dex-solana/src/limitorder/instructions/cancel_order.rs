@@ -27,18 +27,9 @@ pub struct CancelOrder<'info> {
     )]
     pub global_config: AccountLoader<'info, GlobalConfig>,
 
-    /// The order PDA account
-    #[account(
-        mut,
-        close = maker,
-        seeds = [
-            ORDER_V1_SEED.as_bytes(),
-            &order_id.to_le_bytes(),
-            maker.key().as_ref(),
-        ],
-        bump = order_pda.bump,
-    )]
-    pub order_pda: Account<'info, OrderV1>,
+    /// CHECK: The V1 or V2 order PDA account, closed to the maker
+    #[account(mut)]
+    pub order_pda: UncheckedAccount<'info>,
 
     /// The escrow token account for the order
     #[account(
@@ -75,14 +66,18 @@ pub struct CancelOrder<'info> {
     pub instructions_sysvar: UncheckedAccount<'info>,
 }
 
-pub fn cancel_order_handler(ctx: Context<CancelOrder>, order_id: u64, tips: u64) -> Result<()> {
+pub fn cancel_order_handler<'a>(
+    ctx: Context<'_, '_, 'a, 'a, CancelOrder<'a>>,
+    order_id: u64,
+    tips: u64,
+) -> Result<()> {
     msg!("CancelOrder orderId: {}", order_id);
     let global_config = ctx.accounts.global_config.load()?;
 
     let update_ts = clock::Clock::get()?.unix_timestamp as u64;
-    let order = &ctx.accounts.order_pda;
     let payer = ctx.accounts.payer.key();
     let maker = ctx.accounts.maker.key();
+    let order = OrderV2::read_pda(&ctx.accounts.order_pda, order_id, &maker)?;
     let is_maker = payer == maker;
     // Only the maker & resolver can cancel the order
     if !is_maker {
@@ -123,7 +118,7 @@ pub fn cancel_order_handler(ctx: Context<CancelOrder>, order_id: u64, tips: u64)
         &[&[ORDER_V1_SEED.as_bytes(), &order_id.to_le_bytes(), maker.as_ref(), &[order.bump]]];
 
     // Transfer the escrow token from the escrow account to the maker
-    transfer_token(
+    transfer_token_with_hook(
         ctx.accounts.order_pda.to_account_info(),
         ctx.accounts.escrow_token_account.to_account_info(),
         ctx.accounts.input_token_account.to_account_info(),
@@ -132,6 +127,7 @@ pub fn cancel_order_handler(ctx: Context<CancelOrder>, order_id: u64, tips: u64)
         amount,
         ctx.accounts.input_token_mint.decimals,
         Some(order_pda_seeds),
+        ctx.remaining_accounts,
     )?;
 
     // Harvest the transfer fee if it exists
@@ -155,16 +151,32 @@ pub fn cancel_order_handler(ctx: Context<CancelOrder>, order_id: u64, tips: u64)
 
     // Collect fees has to be done towards the end, since native transfers have to happen first
     if !is_maker {
-        collect_fees(
+        let order_info = ctx.accounts.order_pda.to_account_info();
+        let order_min_rent = Rent::get()?.minimum_balance(order_info.data_len());
+        let fees = collect_fees(
             tips,
             global_config.fee_multiplier,
-            ctx.accounts.order_pda.to_account_info(),
+            order.max_fill_fee,
+            order_info,
             ctx.accounts.payer.to_account_info(),
             &ctx.accounts.instructions_sysvar.to_account_info(),
-            ORDER_MIN_RENT,
+            order_min_rent,
         )?;
+        emit_cpi!(FeeBreakdownEvent {
+            order_id,
+            payer,
+            priority_fee: fees.priority_fee,
+            tips: fees.tips,
+            fee_multiplier: fees.fee_multiplier,
+            total_fee: fees.total_fee,
+            max_fill_fee: order.max_fill_fee,
+            charged_fee: fees.charged_fee,
+        });
     }
 
+    // Close the order PDA
+    close_order(&ctx.accounts.order_pda, &ctx.accounts.maker)?;
+
     emit_cpi!(RefundEvent { order_id, maker, input_token_mint, amount });
     emit_cpi!(CancelOrderEvent { order_id, payer, maker, update_ts });
     Ok(())
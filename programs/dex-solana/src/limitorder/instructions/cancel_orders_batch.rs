@@ -0,0 +1,179 @@
+use crate::state::{config::*, event::*, order::*};
+use crate::utils::*;
+use crate::{constants::*, error::LimitOrderError};
+use anchor_lang::{prelude::*, solana_program::clock, solana_program::sysvar};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Number of remaining accounts per order:
+/// [order_pda, escrow_token_account, input_token_account, maker]
+pub const CANCEL_ORDER_ACCOUNTS_LEN: usize = 4;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelOrdersBatch<'info> {
+    /// The payer of the transaction
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The global config account
+    #[account(
+        seeds = [
+            GLOBAL_CONFIG_SEED.as_bytes(),
+        ],
+        bump = global_config.load()?.bump,
+        constraint = !global_config.load()?.paused @ LimitOrderError::TradingPaused,
+        constraint = global_config.load()?.is_resolver(payer.key()) @ LimitOrderError::OnlyResolver,
+    )]
+    pub global_config: AccountLoader<'info, GlobalConfig>,
+
+    /// The input token mint shared by the orders
+    #[account(mut)]
+    pub input_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// SPL program for input token transfers
+    pub input_token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: Solana Instructions Sysvar
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+}
+
+/// Cancels the orders of one input token mint by resolver. The remaining accounts hold
+/// `CANCEL_ORDER_ACCOUNTS_LEN` accounts per order followed by `hook_accounts_len` transfer hook
+/// accounts of the mint. The fees are collected once and split across the orders.
+///
+/// The rent of the escrow and the order is returned to the maker, as in cancel_order.
+pub fn cancel_orders_batch_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CancelOrdersBatch<'info>>,
+    tips: u64,
+    hook_accounts_len: u8,
+) -> Result<()> {
+    let (order_accounts, hook_accounts) =
+        split_hook_accounts(ctx.remaining_accounts, hook_accounts_len)?;
+    let orders = order_accounts.chunks_exact(CANCEL_ORDER_ACCOUNTS_LEN);
+    require!(orders.len() > 0 && orders.remainder().is_empty(), LimitOrderError::InvalidAccount);
+    let order_count = orders.len() as u64;
+    msg!("CancelOrdersBatch orders: {}", order_count);
+
+    let update_ts = clock::Clock::get()?.unix_timestamp as u64;
+    let payer = ctx.accounts.payer.key();
+    let input_token_mint = ctx.accounts.input_token_mint.key();
+    let input_mint_info = ctx.accounts.input_token_mint.to_account_info();
+    let input_token_program = ctx.accounts.input_token_program.to_account_info();
+
+    // Calculate the fees once, split evenly across the orders
+    let fees = calculate_resolver_fees(
+        tips,
+        ctx.accounts.global_config.load()?.fee_multiplier,
+        &ctx.accounts.instructions_sysvar.to_account_info(),
+    )?;
+    let fee_per_order = fees.total_fee / order_count;
+    let fee_remainder = fees.total_fee % order_count;
+
+    for (i, accounts) in orders.enumerate() {
+        let order_info = &accounts[0];
+        let escrow_info = &accounts[1];
+        let input_token_info = &accounts[2];
+        let maker_info = &accounts[3];
+
+        // Check the order accounts
+        let order = OrderV2::read(order_info)?;
+        let order_id = order.order_id;
+        let maker = order.maker;
+        let order_pda_seeds: &[&[&[u8]]] =
+            &[&[ORDER_V1_SEED.as_bytes(), &order_id.to_le_bytes(), maker.as_ref(), &[order.bump]]];
+        require_keys_eq!(
+            escrow_info.key(),
+            order.escrow_token_account,
+            LimitOrderError::InvalidAccount
+        );
+        require_keys_eq!(maker_info.key(), maker, LimitOrderError::InvalidAccount);
+        require_keys_eq!(order.input_token_mint, input_token_mint, LimitOrderError::InvalidAccount);
+        require_keys_eq!(
+            order.input_token_program,
+            input_token_program.key(),
+            LimitOrderError::InvalidAccount
+        );
+        require!(
+            order_info.is_writable
+                && escrow_info.is_writable
+                && input_token_info.is_writable
+                && maker_info.is_writable,
+            LimitOrderError::InvalidAccount
+        );
+
+        // Check if the order has expired
+        #[cfg(feature = "check-deadline")]
+        require_gt!(update_ts, order.deadline, LimitOrderError::OrderNotExpired);
+
+        // Check the input token owner, only the maker can receive the refund and the rent
+        let escrow_token_account = InterfaceAccount::<TokenAccount>::try_from(escrow_info)?;
+        let input_token_account = InterfaceAccount::<TokenAccount>::try_from(input_token_info)?;
+        require!(
+            input_token_account.mint == input_token_mint && input_token_account.owner == maker,
+            LimitOrderError::InvalidInputTokenAccount
+        );
+
+        // Transfer the escrow token from the escrow account to the maker
+        let amount = escrow_token_account.amount;
+        transfer_token_with_hook(
+            order_info.clone(),
+            escrow_info.clone(),
+            input_token_info.clone(),
+            input_mint_info.clone(),
+            input_token_program.clone(),
+            amount,
+            ctx.accounts.input_token_mint.decimals,
+            Some(order_pda_seeds),
+            hook_accounts,
+        )?;
+
+        // Harvest the transfer fee if it exists
+        if get_transfer_fee(&input_mint_info, amount)? > 0 {
+            harvest_withheld_tokens_to_mint(
+                input_token_program.clone(),
+                input_mint_info.clone(),
+                escrow_info.clone(),
+                Some(order_pda_seeds),
+            )?;
+        }
+
+        // Close the escrow token account
+        close_token_account(
+            escrow_info.clone(),
+            maker_info.clone(),
+            order_info.clone(),
+            input_token_program.clone(),
+            Some(order_pda_seeds),
+        )?;
+
+        // Collect fees, the remainder is charged to the last order
+        let fee =
+            if i as u64 == order_count - 1 { fee_per_order + fee_remainder } else { fee_per_order };
+        let charged_fee = if order.max_fill_fee > 0 { fee.min(order.max_fill_fee) } else { fee };
+        let order_min_rent = Rent::get()?.minimum_balance(order_info.data_len());
+        charge_fees(
+            charged_fee,
+            order_info.clone(),
+            ctx.accounts.payer.to_account_info(),
+            order_min_rent,
+        )?;
+        emit_cpi!(FeeBreakdownEvent {
+            order_id,
+            payer,
+            priority_fee: fees.priority_fee,
+            tips: fees.tips,
+            fee_multiplier: fees.fee_multiplier,
+            total_fee: fee,
+            max_fill_fee: order.max_fill_fee,
+            charged_fee,
+        });
+
+        // Close the order account
+        close_order(order_info, maker_info)?;
+
+        emit_cpi!(RefundEvent { order_id, maker, input_token_mint, amount });
+        emit_cpi!(CancelOrderEvent { order_id, payer, maker, update_ts });
+    }
+    Ok(())
+}
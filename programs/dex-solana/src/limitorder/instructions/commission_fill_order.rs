@@ -45,18 +45,10 @@ pub struct CommissionFillOrder<'info> {
     #[account(mut)]
     pub output_token_sa: Option<UncheckedAccount<'info>>,
 
-    /// The order PDA account
-    #[account(
-        mut,
-        close = maker,
-        seeds = [
-            ORDER_V1_SEED.as_bytes(),
-            &order_id.to_le_bytes(),
-            maker.key().as_ref(),
-        ],
-        bump = order_pda.bump,
-    )]
-    pub order_pda: Box<Account<'info, OrderV1>>,
+    /// CHECK: The order PDA account, a V1 order is migrated to V2 in place, closed to the maker
+    /// once fully filled
+    #[account(mut)]
+    pub order_pda: UncheckedAccount<'info>,
 
     /// The escrow token account for the order
     #[account(
@@ -90,9 +82,6 @@ pub struct CommissionFillOrder<'info> {
     pub input_token_mint: Box<InterfaceAccount<'info, Mint>>,
 
     /// The mint of output token
-    #[account(
-        constraint = output_token_mint.key() == order_pda.output_token_mint,
-    )]
     pub output_token_mint: Box<InterfaceAccount<'info, Mint>>,
 
     pub input_token_program: Interface<'info, TokenInterface>,
@@ -117,11 +106,25 @@ pub fn commission_fill_order_handler<'a>(
     commission_info: u32,
 ) -> Result<()> {
     msg!("FillOrder orderId: {}", order_id);
-    let order = &ctx.accounts.order_pda;
+    let mut order = OrderV2::load_mut(
+        &ctx.accounts.order_pda,
+        &ctx.accounts.payer,
+        order_id,
+        ctx.accounts.maker.key,
+    )?;
+    require_keys_eq!(
+        ctx.accounts.output_token_mint.key(),
+        order.output_token_mint,
+        ErrorCode::ConstraintRaw
+    );
 
     // Check order deadline
     let current_ts = Clock::get()?.unix_timestamp as u64;
     require_gte!(order.deadline, current_ts, LimitOrderError::OrderExpired);
+    require_gte!(current_ts, order.start_ts, LimitOrderError::OrderNotStarted);
+
+    // Post-only orders can only be filled against another order
+    require!(!order.is_post_only(), LimitOrderError::PostOnlyOrder);
 
     // update on 2025-05-23: fix tax token issue start
     // Check amount_in
@@ -137,12 +140,33 @@ pub fn commission_fill_order_handler<'a>(
     );
     // update on 2025-05-23: fix tax token issue end
 
+    // Partial fill orders can be filled by amount_in, others are always filled in full
+    let fill_amount = if order.is_partial_fill() && args.amount_in > 0 {
+        args.amount_in.min(escrow_token_amount)
+    } else {
+        escrow_token_amount
+    };
+    let is_full_fill = fill_amount == escrow_token_amount;
+    let (expect_taking_amount, min_return_amount) =
+        order.fill_terms(fill_amount, escrow_token_amount).ok_or(LimitOrderError::MathOverflow)?;
+
+    // Commission terms locked by the maker can not be changed by the resolver
+    if order.commission_locked {
+        require!(order.commission_rate > 0, LimitOrderError::CommissionLocked);
+        require!(commission_info == order.commission_info(), LimitOrderError::CommissionMismatch);
+        require!(
+            ctx.accounts.commission_token_account.key() == order.commission_token_account,
+            LimitOrderError::InvalidCommissionTokenAccount
+        );
+    }
+
     // Parse fee info
     let fee_direction = commission_info >> 31 == 1;
     let fee_rate = commission_info & ((1 << 31) - 1);
 
     let payer = ctx.accounts.payer.key();
     let maker = ctx.accounts.maker.key();
+    let receiver = order.receiver();
     let input_token_mint = ctx.accounts.input_token_mint.key();
     let output_token_mint = ctx.accounts.output_token_mint.key();
     let order_pda_seeds: &[&[&[u8]]] = &[&[
@@ -157,7 +181,7 @@ pub fn commission_fill_order_handler<'a>(
 
     // Set source token account
     let mut source_token_account = &mut ctx.accounts.escrow_token_account;
-    let mut real_amount_in = source_token_account.amount;
+    let mut real_amount_in = fill_amount;
     if is_wsol_input {
         // The pump.fun adapter can only close a temporary wsol account of a system account
         if let Some(temp_input_token_account) = &mut ctx.accounts.temp_input_token_account {
@@ -166,36 +190,31 @@ pub fn commission_fill_order_handler<'a>(
                 LimitOrderError::InvalidInputTokenOwner
             );
 
-            let fee_amount = real_amount_in
-                .checked_mul(fee_rate as u64)
-                .ok_or(LimitOrderError::MathOverflow)?
-                .checked_div(COMMISSION_DENOMINATOR_V2)
-                .ok_or(LimitOrderError::MathOverflow)?;
-
-            msg!(
-                "fee_direction: {:?}, fee_rate: {:?}, fee_amount: {:?}",
-                true,
-                fee_rate,
-                fee_amount
-            );
-
             // Transfer token to fee token account
-            transfer_token(
-                ctx.accounts.order_pda.to_account_info(),
-                ctx.accounts.escrow_token_account.to_account_info(),
-                ctx.accounts.commission_token_account.to_account_info(),
-                ctx.accounts.input_token_mint.to_account_info(),
-                ctx.accounts.input_token_program.to_account_info(),
-                fee_amount,
-                ctx.accounts.input_token_mint.decimals,
-                Some(order_pda_seeds),
+            let escrow_token_account = ctx.accounts.escrow_token_account.to_account_info();
+            let source = FeeSource {
+                authority: &ctx.accounts.order_pda.to_account_info(),
+                token_account: Some(&escrow_token_account),
+                token_mint: &ctx.accounts.input_token_mint,
+                token_program: Some(&ctx.accounts.input_token_program),
+                signer_seeds: Some(order_pda_seeds),
+                is_charge_sol: false,
+                legacy_transfer: true,
+                transfer_ctx: TransferContext::default(),
+            };
+            let fee_amount = pay_limit_order_fee(
+                &source,
+                &ctx.accounts.commission_token_account.to_account_info(),
+                real_amount_in,
+                fee_rate,
+                true,
             )?;
 
             real_amount_in =
                 real_amount_in.checked_sub(fee_amount).ok_or(LimitOrderError::MathOverflow)?;
 
             // Transfer token to temp_input_token_account
-            transfer_token(
+            transfer_token_with_hook(
                 ctx.accounts.order_pda.to_account_info(),
                 ctx.accounts.escrow_token_account.to_account_info(),
                 temp_input_token_account.to_account_info(),
@@ -204,6 +223,7 @@ pub fn commission_fill_order_handler<'a>(
                 real_amount_in,
                 ctx.accounts.input_token_mint.decimals,
                 Some(order_pda_seeds),
+                &[],
             )?;
             source_token_account = temp_input_token_account;
         }
@@ -211,7 +231,9 @@ pub fn commission_fill_order_handler<'a>(
 
     // Check output token owner
     let output_token_account = &mut ctx.accounts.output_token_account;
-    if is_wsol_output {
+    // Only the maker account is passed, so sol can only be unwrapped to the maker
+    let is_sol_output = is_wsol_output && receiver == maker;
+    if is_sol_output {
         // Owner is payer, support toToken is sol, The following instruction will close output_token_account for user and recover the rent through tips.
         // Owner is maker, support toToken is wsol
         require!(
@@ -219,15 +241,15 @@ pub fn commission_fill_order_handler<'a>(
             LimitOrderError::InvalidOutputTokenOwner
         );
     } else {
-        // Owner is maker, support other toToken
-        require!(output_token_account.owner == maker, LimitOrderError::InvalidOutputTokenOwner);
+        // Owner is receiver, support other toToken and wsol for a receiver other than the maker
+        require!(output_token_account.owner == receiver, LimitOrderError::InvalidOutputTokenOwner);
     }
 
     // Reset swap args
     let mut _args = args.clone();
     _args.amount_in = real_amount_in;
-    _args.expect_amount_out = order.expect_taking_amount;
-    _args.min_return = order.min_return_amount;
+    _args.expect_amount_out = expect_taking_amount;
+    _args.min_return = min_return_amount;
 
     // Swap
     let actual_taking_amount = common_swap(
@@ -254,7 +276,7 @@ pub fn commission_fill_order_handler<'a>(
         Some(&ctx.accounts.commission_token_account),
     )?;
 
-    if is_wsol_output && output_token_account.owner == payer {
+    if is_sol_output && output_token_account.owner == payer {
         // Output token is sol, close the output_token_account and transfer sol to maker
         handle_sol_output(
             output_token_account,
@@ -270,45 +292,74 @@ pub fn commission_fill_order_handler<'a>(
         )?;
     }
 
-    // Harvest the transfer fee if it exists
-    if get_transfer_fee(&ctx.accounts.input_token_mint.to_account_info(), real_amount_in)? > 0 {
-        harvest_withheld_tokens_to_mint(
-            ctx.accounts.input_token_program.to_account_info(),
-            ctx.accounts.input_token_mint.to_account_info(),
+    if is_full_fill {
+        // Harvest the transfer fee if it exists
+        if get_transfer_fee(&ctx.accounts.input_token_mint.to_account_info(), real_amount_in)? > 0 {
+            harvest_withheld_tokens_to_mint(
+                ctx.accounts.input_token_program.to_account_info(),
+                ctx.accounts.input_token_mint.to_account_info(),
+                ctx.accounts.escrow_token_account.to_account_info(),
+                Some(order_pda_seeds),
+            )?;
+        }
+
+        // Close the escrow token account
+        close_token_account(
             ctx.accounts.escrow_token_account.to_account_info(),
+            ctx.accounts.maker.to_account_info(),
+            ctx.accounts.order_pda.to_account_info(),
+            ctx.accounts.input_token_program.to_account_info(),
             Some(order_pda_seeds),
         )?;
     }
 
-    // Close the escrow token account
-    close_token_account(
-        ctx.accounts.escrow_token_account.to_account_info(),
-        ctx.accounts.maker.to_account_info(),
-        ctx.accounts.order_pda.to_account_info(),
-        ctx.accounts.input_token_program.to_account_info(),
-        Some(order_pda_seeds),
-    )?;
-
     // Collect fees
-    collect_fees(
+    let order_info = ctx.accounts.order_pda.to_account_info();
+    let order_min_rent = Rent::get()?.minimum_balance(order_info.data_len());
+    let max_fill_fee = order.max_fill_fee;
+    let fees = collect_fees(
         tips,
         ctx.accounts.global_config.load()?.fee_multiplier,
-        ctx.accounts.order_pda.to_account_info(),
+        max_fill_fee,
+        order_info,
         ctx.accounts.payer.to_account_info(),
         &ctx.accounts.instructions_sysvar.to_account_info(),
-        ORDER_MIN_RENT,
+        order_min_rent,
     )?;
 
+    // Update the order, close it once fully filled
+    let remaining_amount = escrow_token_amount - fill_amount;
+    if is_full_fill {
+        close_order(&ctx.accounts.order_pda, &ctx.accounts.maker)?;
+    } else {
+        order
+            .record_fill(fill_amount, actual_taking_amount, expect_taking_amount, min_return_amount)
+            .ok_or(LimitOrderError::MathOverflow)?;
+        order.write(&ctx.accounts.order_pda)?;
+    }
+
     // Emit event
+    emit_cpi!(FeeBreakdownEvent {
+        order_id,
+        payer,
+        priority_fee: fees.priority_fee,
+        tips: fees.tips,
+        fee_multiplier: fees.fee_multiplier,
+        total_fee: fees.total_fee,
+        max_fill_fee,
+        charged_fee: fees.charged_fee,
+    });
     emit_cpi!(FillOrderEvent {
-        order_id: order.order_id,
+        order_id,
         payer,
         maker,
         input_token_mint,
         output_token_mint,
-        making_amount: order.making_amount,
+        making_amount: fill_amount,
         taking_amount: actual_taking_amount,
         update_ts: current_ts,
+        receiver,
+        remaining_amount,
     });
     Ok(())
 }
@@ -327,24 +378,24 @@ fn handle_sol_output<'info>(
 ) -> Result<()> {
     let mut real_amount_out = amount;
     if !fee_direction && !is_output_token_sa {
-        let fee_amount = amount
-            .checked_mul(fee_rate as u64)
-            .ok_or(LimitOrderError::MathOverflow)?
-            .checked_div(COMMISSION_DENOMINATOR_V2)
-            .ok_or(LimitOrderError::MathOverflow)?;
-
-        msg!("fee_direction: {:?}, fee_rate: {:?}, fee_amount: {:?}", false, fee_rate, fee_amount);
-
         // Transfer token to fee token account
-        transfer_token(
-            payer.to_account_info(),
-            output_token_account.to_account_info(),
-            fee_token_account.to_account_info(),
-            output_token_mint.to_account_info(),
-            output_token_program.to_account_info(),
-            fee_amount,
-            output_token_mint.decimals,
-            None,
+        let token_account = output_token_account.to_account_info();
+        let source = FeeSource {
+            authority: payer,
+            token_account: Some(&token_account),
+            token_mint: output_token_mint,
+            token_program: Some(output_token_program),
+            signer_seeds: None,
+            is_charge_sol: false,
+            legacy_transfer: true,
+            transfer_ctx: TransferContext::default(),
+        };
+        let fee_amount = pay_limit_order_fee(
+            &source,
+            &fee_token_account.to_account_info(),
+            amount,
+            fee_rate,
+            false,
         )?;
 
         real_amount_out =
@@ -46,18 +46,10 @@ pub struct FillOrder<'info> {
     #[account(mut)]
     pub output_token_sa: Option<UncheckedAccount<'info>>,
 
-    /// The order PDA account
-    #[account(
-        mut,
-        close = maker,
-        seeds = [
-            ORDER_V1_SEED.as_bytes(),
-            &order_id.to_le_bytes(),
-            maker.key().as_ref(),
-        ],
-        bump = order_pda.bump,
-    )]
-    pub order_pda: Box<Account<'info, OrderV1>>,
+    /// CHECK: The order PDA account, a V1 order is migrated to V2 in place, closed to the maker
+    /// once fully filled
+    #[account(mut)]
+    pub order_pda: UncheckedAccount<'info>,
 
     /// The escrow token account for the order
     #[account(
@@ -91,9 +83,6 @@ pub struct FillOrder<'info> {
     pub input_token_mint: Box<InterfaceAccount<'info, Mint>>,
 
     /// The mint of output token
-    #[account(
-        constraint = output_token_mint.key() == order_pda.output_token_mint,
-    )]
     pub output_token_mint: Box<InterfaceAccount<'info, Mint>>,
 
     pub input_token_program: Interface<'info, TokenInterface>,
@@ -113,11 +102,31 @@ pub fn fill_order_by_resolver_handler<'a>(
     args: SwapArgs,
 ) -> Result<()> {
     msg!("FillOrder orderId: {}", order_id);
-    let order = &ctx.accounts.order_pda;
+    let mut order = OrderV2::load_mut(
+        &ctx.accounts.order_pda,
+        &ctx.accounts.payer,
+        order_id,
+        ctx.accounts.maker.key,
+    )?;
+    require_keys_eq!(
+        ctx.accounts.output_token_mint.key(),
+        order.output_token_mint,
+        ErrorCode::ConstraintRaw
+    );
 
     // Check order deadline
     let current_ts = Clock::get()?.unix_timestamp as u64;
     require_gte!(order.deadline, current_ts, LimitOrderError::OrderExpired);
+    require_gte!(current_ts, order.start_ts, LimitOrderError::OrderNotStarted);
+
+    // Post-only orders can only be filled against another order
+    require!(!order.is_post_only(), LimitOrderError::PostOnlyOrder);
+
+    // Orders with a locked commission must be filled by commission_fill_order
+    require!(
+        !order.commission_locked || order.commission_rate == 0,
+        LimitOrderError::CommissionLocked
+    );
 
     // update on 2025-05-23: fix tax token issue start
     // Check amount_in
@@ -129,8 +138,19 @@ pub fn fill_order_by_resolver_handler<'a>(
     msg!("FillOrder amount_in: {}, escrow_token_amount: {}", args.amount_in, escrow_token_amount);
     // update on 2025-05-23: fix tax token issue end
 
+    // Partial fill orders can be filled by amount_in, others are always filled in full
+    let fill_amount = if order.is_partial_fill() && args.amount_in > 0 {
+        args.amount_in.min(escrow_token_amount)
+    } else {
+        escrow_token_amount
+    };
+    let is_full_fill = fill_amount == escrow_token_amount;
+    let (expect_taking_amount, min_return_amount) =
+        order.fill_terms(fill_amount, escrow_token_amount).ok_or(LimitOrderError::MathOverflow)?;
+
     let payer = ctx.accounts.payer.key();
     let maker = ctx.accounts.maker.key();
+    let receiver = order.receiver();
     let input_token_mint = ctx.accounts.input_token_mint.key();
     let output_token_mint = ctx.accounts.output_token_mint.key();
     let order_pda_seeds: &[&[&[u8]]] = &[&[
@@ -154,15 +174,16 @@ pub fn fill_order_by_resolver_handler<'a>(
             );
 
             // Transfer token to temp_input_token_account
-            transfer_token(
+            transfer_token_with_hook(
                 ctx.accounts.order_pda.to_account_info(),
                 ctx.accounts.escrow_token_account.to_account_info(),
                 temp_input_token_account.to_account_info(),
                 ctx.accounts.input_token_mint.to_account_info(),
                 ctx.accounts.input_token_program.to_account_info(),
-                args.amount_in,
+                fill_amount,
                 ctx.accounts.input_token_mint.decimals,
                 Some(order_pda_seeds),
+                &[],
             )?;
             source_token_account = temp_input_token_account;
         }
@@ -170,7 +191,9 @@ pub fn fill_order_by_resolver_handler<'a>(
 
     // Check output token owner
     let output_token_account = &mut ctx.accounts.output_token_account;
-    if is_wsol_output {
+    // Only the maker account is passed, so sol can only be unwrapped to the maker
+    let is_sol_output = is_wsol_output && receiver == maker;
+    if is_sol_output {
         // Owner is payer, support toToken is sol, The following instruction will close output_token_account for user and recover the rent through tips.
         // Owner is maker, support toToken is wsol
         require!(
@@ -178,15 +201,15 @@ pub fn fill_order_by_resolver_handler<'a>(
             LimitOrderError::InvalidOutputTokenOwner
         );
     } else {
-        // Owner is maker, support other toToken
-        require!(output_token_account.owner == maker, LimitOrderError::InvalidOutputTokenOwner);
+        // Owner is receiver, support other toToken and wsol for a receiver other than the maker
+        require!(output_token_account.owner == receiver, LimitOrderError::InvalidOutputTokenOwner);
     }
 
     // Reset swap args
     let mut _args = args.clone();
-    _args.amount_in = escrow_token_amount;
-    _args.expect_amount_out = order.expect_taking_amount;
-    _args.min_return = order.min_return_amount;
+    _args.amount_in = fill_amount;
+    _args.expect_amount_out = expect_taking_amount;
+    _args.min_return = min_return_amount;
 
     // Swap
     let actual_taking_amount = common_swap(
@@ -213,7 +236,7 @@ pub fn fill_order_by_resolver_handler<'a>(
         None,
     )?;
 
-    if is_wsol_output && output_token_account.owner == payer {
+    if is_sol_output && output_token_account.owner == payer {
         // Output token is sol, close the output_token_account and transfer sol to maker
         handle_sol_output(
             output_token_account,
@@ -224,45 +247,74 @@ pub fn fill_order_by_resolver_handler<'a>(
         )?;
     }
 
-    // Harvest the transfer fee if it exists
-    if get_transfer_fee(&ctx.accounts.input_token_mint.to_account_info(), args.amount_in)? > 0 {
-        harvest_withheld_tokens_to_mint(
-            ctx.accounts.input_token_program.to_account_info(),
-            ctx.accounts.input_token_mint.to_account_info(),
+    if is_full_fill {
+        // Harvest the transfer fee if it exists
+        if get_transfer_fee(&ctx.accounts.input_token_mint.to_account_info(), fill_amount)? > 0 {
+            harvest_withheld_tokens_to_mint(
+                ctx.accounts.input_token_program.to_account_info(),
+                ctx.accounts.input_token_mint.to_account_info(),
+                ctx.accounts.escrow_token_account.to_account_info(),
+                Some(order_pda_seeds),
+            )?;
+        }
+
+        // Close the escrow token account
+        close_token_account(
             ctx.accounts.escrow_token_account.to_account_info(),
+            ctx.accounts.maker.to_account_info(),
+            ctx.accounts.order_pda.to_account_info(),
+            ctx.accounts.input_token_program.to_account_info(),
             Some(order_pda_seeds),
         )?;
     }
 
-    // Close the escrow token account
-    close_token_account(
-        ctx.accounts.escrow_token_account.to_account_info(),
-        ctx.accounts.maker.to_account_info(),
-        ctx.accounts.order_pda.to_account_info(),
-        ctx.accounts.input_token_program.to_account_info(),
-        Some(order_pda_seeds),
-    )?;
-
     // Collect fees
-    collect_fees(
+    let order_info = ctx.accounts.order_pda.to_account_info();
+    let order_min_rent = Rent::get()?.minimum_balance(order_info.data_len());
+    let max_fill_fee = order.max_fill_fee;
+    let fees = collect_fees(
         tips,
         ctx.accounts.global_config.load()?.fee_multiplier,
-        ctx.accounts.order_pda.to_account_info(),
+        max_fill_fee,
+        order_info,
         ctx.accounts.payer.to_account_info(),
         &ctx.accounts.instructions_sysvar.to_account_info(),
-        ORDER_MIN_RENT,
+        order_min_rent,
     )?;
 
+    // Update the order, close it once fully filled
+    let remaining_amount = escrow_token_amount - fill_amount;
+    if is_full_fill {
+        close_order(&ctx.accounts.order_pda, &ctx.accounts.maker)?;
+    } else {
+        order
+            .record_fill(fill_amount, actual_taking_amount, expect_taking_amount, min_return_amount)
+            .ok_or(LimitOrderError::MathOverflow)?;
+        order.write(&ctx.accounts.order_pda)?;
+    }
+
     // Emit event
+    emit_cpi!(FeeBreakdownEvent {
+        order_id,
+        payer,
+        priority_fee: fees.priority_fee,
+        tips: fees.tips,
+        fee_multiplier: fees.fee_multiplier,
+        total_fee: fees.total_fee,
+        max_fill_fee,
+        charged_fee: fees.charged_fee,
+    });
     emit_cpi!(FillOrderEvent {
-        order_id: order.order_id,
+        order_id,
         payer,
         maker,
         input_token_mint,
         output_token_mint,
-        making_amount: order.making_amount,
+        making_amount: fill_amount,
         taking_amount: actual_taking_amount,
         update_ts: current_ts,
+        receiver,
+        remaining_amount,
     });
     Ok(())
 }
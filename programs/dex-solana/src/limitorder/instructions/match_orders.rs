@@ -0,0 +1,407 @@
+use crate::constants::*;
+use crate::error::LimitOrderError;
+use crate::state::{config::*, event::*, order::*};
+use crate::utils::*;
+use anchor_lang::{prelude::*, solana_program::clock::Clock, solana_program::sysvar};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(order_id_a: u64, order_id_b: u64)]
+pub struct MatchOrders<'info> {
+    /// The payer of the transaction
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: The maker of order A
+    #[account(mut)]
+    pub maker_a: AccountInfo<'info>,
+
+    /// CHECK: The maker of order B
+    #[account(mut)]
+    pub maker_b: AccountInfo<'info>,
+
+    /// The global config account
+    #[account(
+        seeds = [
+            GLOBAL_CONFIG_SEED.as_bytes(),
+        ],
+        bump = global_config.load()?.bump,
+        constraint = !global_config.load()?.paused @ LimitOrderError::TradingPaused,
+        constraint = global_config.load()?.is_resolver(payer.key()) @ LimitOrderError::OnlyResolver,
+    )]
+    pub global_config: AccountLoader<'info, GlobalConfig>,
+
+    /// CHECK: The V1 or V2 order A PDA account, sells token X for token Y, closed to maker A
+    #[account(mut)]
+    pub order_a: UncheckedAccount<'info>,
+
+    /// CHECK: The V1 or V2 order B PDA account, sells token Y for token X, closed to maker B
+    #[account(mut)]
+    pub order_b: UncheckedAccount<'info>,
+
+    /// The escrow token account of order A
+    #[account(
+        mut,
+        token::mint = token_x_mint,
+        token::authority = order_a,
+        token::token_program = token_x_program,
+        seeds = [
+            ESCROW_TOKEN_SEED.as_bytes(),
+            order_a.key().as_ref(),
+            token_x_mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub escrow_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The escrow token account of order B
+    #[account(
+        mut,
+        token::mint = token_y_mint,
+        token::authority = order_b,
+        token::token_program = token_y_program,
+        seeds = [
+            ESCROW_TOKEN_SEED.as_bytes(),
+            order_b.key().as_ref(),
+            token_y_mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub escrow_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The token Y account receiving the output of order A
+    #[account(
+        mut,
+        token::mint = token_y_mint,
+        token::token_program = token_y_program,
+    )]
+    pub output_token_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The token X account receiving the output of order B
+    #[account(
+        mut,
+        token::mint = token_x_mint,
+        token::token_program = token_x_program,
+    )]
+    pub output_token_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The token X account of maker A receiving the refund
+    #[account(
+        mut,
+        token::mint = token_x_mint,
+        token::authority = maker_a,
+        token::token_program = token_x_program,
+    )]
+    pub refund_token_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The token Y account of maker B receiving the refund
+    #[account(
+        mut,
+        token::mint = token_y_mint,
+        token::authority = maker_b,
+        token::token_program = token_y_program,
+    )]
+    pub refund_token_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of token X
+    #[account(mut)]
+    pub token_x_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of token Y
+    #[account(mut)]
+    pub token_y_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub token_x_program: Interface<'info, TokenInterface>,
+    pub token_y_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: Solana Instructions Sysvar
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+}
+
+/// Settles two crossing orders escrow-to-escrow.
+///
+/// Both orders are filled in full. The surplus of each escrow above the counterparty's
+/// `min_return_amount` is split in half: one half goes to the counterparty, the other half
+/// is refunded to the maker.
+pub fn match_orders_handler<'a>(
+    ctx: Context<'_, '_, 'a, 'a, MatchOrders<'a>>,
+    order_id_a: u64,
+    order_id_b: u64,
+    tips: u64,
+) -> Result<()> {
+    msg!("MatchOrders orderIdA: {}, orderIdB: {}", order_id_a, order_id_b);
+    let order_a = OrderV2::read_pda(&ctx.accounts.order_a, order_id_a, ctx.accounts.maker_a.key)?;
+    let order_b = OrderV2::read_pda(&ctx.accounts.order_b, order_id_b, ctx.accounts.maker_b.key)?;
+
+    // Check the orders cross, order A sells token X for token Y and order B the other way
+    let token_x_mint = ctx.accounts.token_x_mint.key();
+    let token_y_mint = ctx.accounts.token_y_mint.key();
+    require!(
+        order_a.input_token_mint == token_x_mint
+            && order_a.output_token_mint == token_y_mint
+            && order_b.input_token_mint == token_y_mint
+            && order_b.output_token_mint == token_x_mint,
+        LimitOrderError::OrdersNotMatched
+    );
+    require!(
+        ctx.accounts.output_token_account_a.owner == order_a.receiver()
+            && ctx.accounts.output_token_account_b.owner == order_b.receiver(),
+        LimitOrderError::InvalidOutputTokenOwner
+    );
+
+    // Check order deadline
+    let current_ts = Clock::get()?.unix_timestamp as u64;
+    require_gte!(order_a.deadline, current_ts, LimitOrderError::OrderExpired);
+    require_gte!(order_b.deadline, current_ts, LimitOrderError::OrderExpired);
+    require_gte!(current_ts, order_a.start_ts, LimitOrderError::OrderNotStarted);
+    require_gte!(current_ts, order_b.start_ts, LimitOrderError::OrderNotStarted);
+
+    // The commission locked by the maker can only be charged through a swap fill
+    require!(
+        !order_a.commission_locked || order_a.commission_rate == 0,
+        LimitOrderError::CommissionLocked
+    );
+    require!(
+        !order_b.commission_locked || order_b.commission_rate == 0,
+        LimitOrderError::CommissionLocked
+    );
+
+    // Calculate the settlement amounts
+    let escrow_amount_a = ctx.accounts.escrow_a.amount;
+    let escrow_amount_b = ctx.accounts.escrow_b.amount;
+    let (amount_a_to_b, refund_a) = split_surplus(escrow_amount_a, order_b.min_return_amount)?;
+    let (amount_b_to_a, refund_b) = split_surplus(escrow_amount_b, order_a.min_return_amount)?;
+    msg!(
+        "MatchOrders amount_a_to_b: {}, refund_a: {}, amount_b_to_a: {}, refund_b: {}",
+        amount_a_to_b,
+        refund_a,
+        amount_b_to_a,
+        refund_b
+    );
+
+    let payer = ctx.accounts.payer.key();
+    let maker_a = ctx.accounts.maker_a.key();
+    let maker_b = ctx.accounts.maker_b.key();
+    let order_a_seeds: &[&[&[u8]]] = &[&[
+        ORDER_V1_SEED.as_bytes(),
+        &order_id_a.to_le_bytes(),
+        maker_a.as_ref(),
+        &[order_a.bump],
+    ]];
+    let order_b_seeds: &[&[&[u8]]] = &[&[
+        ORDER_V1_SEED.as_bytes(),
+        &order_id_b.to_le_bytes(),
+        maker_b.as_ref(),
+        &[order_b.bump],
+    ]];
+
+    let before_output_a = ctx.accounts.output_token_account_a.amount;
+    let before_output_b = ctx.accounts.output_token_account_b.amount;
+
+    // Settle order A: token X from escrow A to the receiver of order B
+    transfer_token_with_hook(
+        ctx.accounts.order_a.to_account_info(),
+        ctx.accounts.escrow_a.to_account_info(),
+        ctx.accounts.output_token_account_b.to_account_info(),
+        ctx.accounts.token_x_mint.to_account_info(),
+        ctx.accounts.token_x_program.to_account_info(),
+        amount_a_to_b,
+        ctx.accounts.token_x_mint.decimals,
+        Some(order_a_seeds),
+        ctx.remaining_accounts,
+    )?;
+
+    // Settle order B: token Y from escrow B to the receiver of order A
+    transfer_token_with_hook(
+        ctx.accounts.order_b.to_account_info(),
+        ctx.accounts.escrow_b.to_account_info(),
+        ctx.accounts.output_token_account_a.to_account_info(),
+        ctx.accounts.token_y_mint.to_account_info(),
+        ctx.accounts.token_y_program.to_account_info(),
+        amount_b_to_a,
+        ctx.accounts.token_y_mint.decimals,
+        Some(order_b_seeds),
+        ctx.remaining_accounts,
+    )?;
+
+    // Check the received amounts against the min return amounts
+    ctx.accounts.output_token_account_a.reload()?;
+    ctx.accounts.output_token_account_b.reload()?;
+    let taking_amount_a = ctx
+        .accounts
+        .output_token_account_a
+        .amount
+        .checked_sub(before_output_a)
+        .ok_or(LimitOrderError::MathOverflow)?;
+    let taking_amount_b = ctx
+        .accounts
+        .output_token_account_b
+        .amount
+        .checked_sub(before_output_b)
+        .ok_or(LimitOrderError::MathOverflow)?;
+    require_gte!(
+        taking_amount_a,
+        order_a.min_return_amount,
+        LimitOrderError::InvalidMinReturnAmount
+    );
+    require_gte!(
+        taking_amount_b,
+        order_b.min_return_amount,
+        LimitOrderError::InvalidMinReturnAmount
+    );
+
+    // Refund the rest of the surplus to the makers
+    if refund_a > 0 {
+        transfer_token_with_hook(
+            ctx.accounts.order_a.to_account_info(),
+            ctx.accounts.escrow_a.to_account_info(),
+            ctx.accounts.refund_token_account_a.to_account_info(),
+            ctx.accounts.token_x_mint.to_account_info(),
+            ctx.accounts.token_x_program.to_account_info(),
+            refund_a,
+            ctx.accounts.token_x_mint.decimals,
+            Some(order_a_seeds),
+            ctx.remaining_accounts,
+        )?;
+        emit_cpi!(RefundEvent {
+            order_id: order_id_a,
+            maker: maker_a,
+            input_token_mint: token_x_mint,
+            amount: refund_a,
+        });
+    }
+    if refund_b > 0 {
+        transfer_token_with_hook(
+            ctx.accounts.order_b.to_account_info(),
+            ctx.accounts.escrow_b.to_account_info(),
+            ctx.accounts.refund_token_account_b.to_account_info(),
+            ctx.accounts.token_y_mint.to_account_info(),
+            ctx.accounts.token_y_program.to_account_info(),
+            refund_b,
+            ctx.accounts.token_y_mint.decimals,
+            Some(order_b_seeds),
+            ctx.remaining_accounts,
+        )?;
+        emit_cpi!(RefundEvent {
+            order_id: order_id_b,
+            maker: maker_b,
+            input_token_mint: token_y_mint,
+            amount: refund_b,
+        });
+    }
+
+    // Harvest the transfer fee if it exists
+    if get_transfer_fee(&ctx.accounts.token_x_mint.to_account_info(), escrow_amount_a)? > 0 {
+        harvest_withheld_tokens_to_mint(
+            ctx.accounts.token_x_program.to_account_info(),
+            ctx.accounts.token_x_mint.to_account_info(),
+            ctx.accounts.escrow_a.to_account_info(),
+            Some(order_a_seeds),
+        )?;
+    }
+    if get_transfer_fee(&ctx.accounts.token_y_mint.to_account_info(), escrow_amount_b)? > 0 {
+        harvest_withheld_tokens_to_mint(
+            ctx.accounts.token_y_program.to_account_info(),
+            ctx.accounts.token_y_mint.to_account_info(),
+            ctx.accounts.escrow_b.to_account_info(),
+            Some(order_b_seeds),
+        )?;
+    }
+
+    // Close the escrow token accounts
+    close_token_account(
+        ctx.accounts.escrow_a.to_account_info(),
+        ctx.accounts.maker_a.to_account_info(),
+        ctx.accounts.order_a.to_account_info(),
+        ctx.accounts.token_x_program.to_account_info(),
+        Some(order_a_seeds),
+    )?;
+    close_token_account(
+        ctx.accounts.escrow_b.to_account_info(),
+        ctx.accounts.maker_b.to_account_info(),
+        ctx.accounts.order_b.to_account_info(),
+        ctx.accounts.token_y_program.to_account_info(),
+        Some(order_b_seeds),
+    )?;
+
+    // Collect fees once, split between the two orders
+    let fees = calculate_resolver_fees(
+        tips,
+        ctx.accounts.global_config.load()?.fee_multiplier,
+        &ctx.accounts.instructions_sysvar.to_account_info(),
+    )?;
+    let fee_a = fees.total_fee / 2;
+    let fee_b = fees.total_fee - fee_a;
+    for (order_info, order, fee) in
+        [(&ctx.accounts.order_a, &order_a, fee_a), (&ctx.accounts.order_b, &order_b, fee_b)]
+    {
+        let charged_fee = if order.max_fill_fee > 0 { fee.min(order.max_fill_fee) } else { fee };
+        let order_info = order_info.to_account_info();
+        let order_min_rent = Rent::get()?.minimum_balance(order_info.data_len());
+        charge_fees(charged_fee, order_info, ctx.accounts.payer.to_account_info(), order_min_rent)?;
+        emit_cpi!(FeeBreakdownEvent {
+            order_id: order.order_id,
+            payer,
+            priority_fee: fees.priority_fee,
+            tips: fees.tips,
+            fee_multiplier: fees.fee_multiplier,
+            total_fee: fee,
+            max_fill_fee: order.max_fill_fee,
+            charged_fee,
+        });
+    }
+
+    // Close the order accounts
+    close_order(&ctx.accounts.order_a, &ctx.accounts.maker_a)?;
+    close_order(&ctx.accounts.order_b, &ctx.accounts.maker_b)?;
+
+    // Emit events, the making amount is what left each escrow to the counterparty
+    emit_cpi!(FillOrderEvent {
+        order_id: order_id_a,
+        payer,
+        maker: maker_a,
+        input_token_mint: token_x_mint,
+        output_token_mint: token_y_mint,
+        making_amount: amount_a_to_b,
+        taking_amount: taking_amount_a,
+        update_ts: current_ts,
+        receiver: order_a.receiver(),
+        remaining_amount: 0,
+    });
+    emit_cpi!(FillOrderEvent {
+        order_id: order_id_b,
+        payer,
+        maker: maker_b,
+        input_token_mint: token_y_mint,
+        output_token_mint: token_x_mint,
+        making_amount: amount_b_to_a,
+        taking_amount: taking_amount_b,
+        update_ts: current_ts,
+        receiver: order_b.receiver(),
+        remaining_amount: 0,
+    });
+    emit_cpi!(MatchOrdersEvent {
+        order_id_a,
+        order_id_b,
+        payer,
+        maker_a,
+        maker_b,
+        amount_a_to_b,
+        amount_b_to_a,
+        refund_a,
+        refund_b,
+        update_ts: current_ts,
+    });
+    Ok(())
+}
+
+// split the escrow amount into (amount to the counterparty, refund to the maker)
+fn split_surplus(escrow_amount: u64, counterparty_min_return: u64) -> Result<(u64, u64)> {
+    let surplus = escrow_amount
+        .checked_sub(counterparty_min_return)
+        .ok_or(LimitOrderError::OrdersNotMatched)?;
+    let refund = surplus / 2;
+    Ok((escrow_amount - refund, refund))
+}
@@ -0,0 +1,59 @@
+use crate::constants::*;
+use crate::error::LimitOrderError;
+use crate::state::{config::*, event::*, order::*};
+use anchor_lang::{Discriminator, prelude::*};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(order_id: u64)]
+pub struct MigrateOrder<'info> {
+    /// The payer of the transaction, the maker or a resolver
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: The order maker
+    pub maker: AccountInfo<'info>,
+
+    /// The global config account
+    #[account(
+        seeds = [
+            GLOBAL_CONFIG_SEED.as_bytes(),
+        ],
+        bump = global_config.load()?.bump,
+        constraint = payer.key() == maker.key()
+            || global_config.load()?.is_resolver(payer.key()) @ LimitOrderError::OnlyResolver,
+    )]
+    pub global_config: AccountLoader<'info, GlobalConfig>,
+
+    /// CHECK: The V1 order PDA account, checked against its stored bump and upgraded to V2 in place
+    #[account(mut, owner = crate::ID)]
+    pub order_pda: UncheckedAccount<'info>,
+
+    /// System program
+    pub system_program: Program<'info, System>,
+}
+
+pub fn migrate_order_handler(ctx: Context<MigrateOrder>, order_id: u64) -> Result<()> {
+    msg!("MigrateOrder orderId: {}", order_id);
+    let order_info = ctx.accounts.order_pda.to_account_info();
+
+    // Only V1 orders can be migrated, the other order instructions migrate them on the fly too
+    require!(
+        order_info.try_borrow_data()?.starts_with(OrderV1::DISCRIMINATOR),
+        LimitOrderError::InvalidOrderVersion
+    );
+    OrderV2::load_mut(
+        &order_info,
+        &ctx.accounts.payer.to_account_info(),
+        order_id,
+        &ctx.accounts.maker.key(),
+    )?;
+
+    emit_cpi!(MigrateOrderEvent {
+        order_id,
+        payer: ctx.accounts.payer.key(),
+        maker: ctx.accounts.maker.key(),
+        version: OrderV2::VERSION,
+    });
+    Ok(())
+}
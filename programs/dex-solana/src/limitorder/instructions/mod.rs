@@ -1,11 +1,17 @@
 pub mod cancel_order;
+pub mod cancel_orders_batch;
 pub mod commission_fill_order;
 pub mod fill_order;
+pub mod match_orders;
+pub mod migrate_order;
 pub mod place_order;
 pub mod update_order;
 
 pub use cancel_order::*;
+pub use cancel_orders_batch::*;
 pub use commission_fill_order::*;
 pub use fill_order::*;
+pub use match_orders::*;
+pub use migrate_order::*;
 pub use place_order::*;
 pub use update_order::*;
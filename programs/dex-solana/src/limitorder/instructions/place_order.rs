@@ -1,7 +1,7 @@
 use crate::constants::*;
 use crate::error::LimitOrderError;
 use crate::state::{config::*, event::*, order::*};
-use crate::utils::{transfer_sol, transfer_token};
+use crate::utils::{transfer_sol, transfer_token_with_hook, unpack_token_account};
 use anchor_lang::{prelude::*, solana_program};
 use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
@@ -33,9 +33,9 @@ pub struct PlaceOrder<'info> {
         ],
         bump,
         payer = maker,
-        space = OrderV1::LEN
+        space = OrderV2::LEN
     )]
-    pub order_pda: Account<'info, OrderV1>,
+    pub order_pda: Account<'info, OrderV2>,
 
     /// The escrow token account for the order
     #[account(
@@ -79,8 +79,122 @@ pub struct PlaceOrder<'info> {
     pub system_program: Program<'info, System>,
 }
 
-pub fn place_order_handler(
-    ctx: Context<PlaceOrder>,
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(order_id: u64)]
+pub struct PlaceOrderV2<'info> {
+    /// The maker of the order
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    /// The global config account
+    #[account(
+        seeds = [
+            GLOBAL_CONFIG_SEED.as_bytes(),
+        ],
+        bump = global_config.load()?.bump,
+        constraint = !global_config.load()?.paused @ LimitOrderError::TradingPaused,
+    )]
+    pub global_config: AccountLoader<'info, GlobalConfig>,
+
+    /// The order PDA account
+    #[account(
+        init,
+        seeds = [
+            ORDER_V1_SEED.as_bytes(),
+            &order_id.to_le_bytes(),
+            maker.key().as_ref(),
+        ],
+        bump,
+        payer = maker,
+        space = OrderV2::LEN
+    )]
+    pub order_pda: Account<'info, OrderV2>,
+
+    /// The escrow token account for the order
+    #[account(
+        init,
+        token::mint = input_token_mint,
+        token::authority = order_pda,
+        token::token_program = input_token_program,
+        seeds = [
+            ESCROW_TOKEN_SEED.as_bytes(),
+            order_pda.key().as_ref(),
+            input_token_mint.key().as_ref(),
+        ],
+        bump,
+        payer = maker,
+    )]
+    pub escrow_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The user token account for input token
+    #[account(
+        mut,
+        token::mint = input_token_mint,
+        token::authority = maker,
+        token::token_program = input_token_program,
+    )]
+    pub input_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of input token
+    #[account(
+        token::token_program = input_token_program,
+    )]
+    pub input_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of output token
+    #[account(
+        token::token_program = output_token_program,
+    )]
+    pub output_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub input_token_program: Interface<'info, TokenInterface>,
+    pub output_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+
+    /// The commission token account locked into the order
+    pub commission_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+}
+
+/// The terms of an order, place_order leaves the optional ones to their defaults
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+pub struct PlaceOrderV2Args {
+    pub making_amount: u64,
+    pub expect_taking_amount: u64,
+    pub min_return_amount: u64,
+    pub deadline: u64,
+    pub trade_fee: u64,
+    /// The receiver of the output token, the maker if None
+    pub receiver: Option<Pubkey>,
+    /// The commission terms locked into the order, the resolver chooses them if None
+    pub commission_info: Option<u32>,
+    /// The max fee in lamports a resolver can charge per fill or cancel, 0 means no cap
+    pub max_fill_fee: u64,
+    /// ORDER_FLAG_*
+    pub flags: u8,
+    /// The timestamp from which the order can be filled, 0 means immediately
+    pub start_ts: u64,
+}
+
+/// The accounts shared by place_order and place_order_v2
+struct PlaceOrderAccounts<'b, 'a> {
+    maker: &'b Signer<'a>,
+    global_config: &'b AccountLoader<'a, GlobalConfig>,
+    order_pda: &'b mut Account<'a, OrderV2>,
+    order_bump: u8,
+    escrow_token_account: &'b mut InterfaceAccount<'a, TokenAccount>,
+    input_token_account: &'b InterfaceAccount<'a, TokenAccount>,
+    input_token_mint: &'b InterfaceAccount<'a, Mint>,
+    output_token_mint: &'b InterfaceAccount<'a, Mint>,
+    input_token_program: &'b Interface<'a, TokenInterface>,
+    output_token_program: &'b Interface<'a, TokenInterface>,
+    commission_token_account: Option<&'b InterfaceAccount<'a, TokenAccount>>,
+    /// The remaining accounts resolve the transfer hook of the input mint
+    hook_accounts: &'b [AccountInfo<'a>],
+}
+
+pub fn place_order_handler<'a>(
+    mut ctx: Context<'_, '_, 'a, 'a, PlaceOrder<'a>>,
     order_id: u64,
     making_amount: u64,
     expect_taking_amount: u64,
@@ -88,16 +202,95 @@ pub fn place_order_handler(
     deadline: u64,
     trade_fee: u64,
 ) -> Result<()> {
+    let args = PlaceOrderV2Args {
+        making_amount,
+        expect_taking_amount,
+        min_return_amount,
+        deadline,
+        trade_fee,
+        ..Default::default()
+    };
+    let accounts = &mut ctx.accounts;
+    let event = place_order(
+        PlaceOrderAccounts {
+            maker: &accounts.maker,
+            global_config: &accounts.global_config,
+            order_pda: &mut accounts.order_pda,
+            order_bump: ctx.bumps.order_pda,
+            escrow_token_account: &mut accounts.escrow_token_account,
+            input_token_account: &accounts.input_token_account,
+            input_token_mint: &accounts.input_token_mint,
+            output_token_mint: &accounts.output_token_mint,
+            input_token_program: &accounts.input_token_program,
+            output_token_program: &accounts.output_token_program,
+            commission_token_account: None,
+            hook_accounts: ctx.remaining_accounts,
+        },
+        order_id,
+        &args,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+pub fn place_order_v2_handler<'a>(
+    mut ctx: Context<'_, '_, 'a, 'a, PlaceOrderV2<'a>>,
+    order_id: u64,
+    args: PlaceOrderV2Args,
+) -> Result<()> {
+    let accounts = &mut ctx.accounts;
+    let event = place_order(
+        PlaceOrderAccounts {
+            maker: &accounts.maker,
+            global_config: &accounts.global_config,
+            order_pda: &mut accounts.order_pda,
+            order_bump: ctx.bumps.order_pda,
+            escrow_token_account: &mut accounts.escrow_token_account,
+            input_token_account: &accounts.input_token_account,
+            input_token_mint: &accounts.input_token_mint,
+            output_token_mint: &accounts.output_token_mint,
+            input_token_program: &accounts.input_token_program,
+            output_token_program: &accounts.output_token_program,
+            commission_token_account: accounts.commission_token_account.as_deref(),
+            hook_accounts: ctx.remaining_accounts,
+        },
+        order_id,
+        &args,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+fn place_order(
+    accounts: PlaceOrderAccounts,
+    order_id: u64,
+    args: &PlaceOrderV2Args,
+) -> Result<PlaceOrderEvent> {
     msg!("PlaceOrder orderId: {}", order_id);
-    let global_config = ctx.accounts.global_config.load()?;
+    let global_config = accounts.global_config.load()?;
+    let PlaceOrderV2Args {
+        making_amount,
+        expect_taking_amount,
+        min_return_amount,
+        deadline,
+        trade_fee,
+        max_fill_fee,
+        flags,
+        ..
+    } = *args;
 
     // Check if the deadline is valid: should >= 5 minutes
     let create_ts = solana_program::clock::Clock::get()?.unix_timestamp as u64;
     require_gte!(deadline, create_ts + MIN_DEADLINE, LimitOrderError::InvalidDeadline);
 
+    // Check flags and start time, start time 0 means the order can be filled immediately
+    require!(flags & !ORDER_FLAGS_MASK == 0, LimitOrderError::InvalidOrderFlags);
+    let start_ts = args.start_ts.max(create_ts);
+    require_gt!(deadline, start_ts, LimitOrderError::InvalidDeadline);
+
     // Check if the input token is the same as the output token
     require!(
-        ctx.accounts.input_token_mint.key() != ctx.accounts.output_token_mint.key(),
+        accounts.input_token_mint.key() != accounts.output_token_mint.key(),
         LimitOrderError::InputAndOutputTokenSame
     );
 
@@ -110,56 +303,103 @@ pub fn place_order_handler(
     );
     require!(trade_fee >= global_config.trade_fee, LimitOrderError::InvalidTradeFee);
 
+    // Check commission terms, once locked they can not be changed by the resolver
+    let commission_locked = args.commission_info.is_some();
+    let commission_info = args.commission_info.unwrap_or(0);
+    let commission_direction = commission_info >> 31 == 1;
+    let commission_rate = commission_info & ((1 << 31) - 1);
+    require!(commission_rate <= COMMISSION_RATE_LIMIT_V2, LimitOrderError::InvalidCommissionRate);
+    let commission_token_account = match accounts.commission_token_account {
+        Some(commission_token_account) => {
+            require!(commission_locked, LimitOrderError::InvalidCommissionTokenAccount);
+            let commission_mint = if commission_direction {
+                accounts.input_token_mint.key()
+            } else {
+                accounts.output_token_mint.key()
+            };
+            require!(
+                commission_token_account.mint == commission_mint,
+                LimitOrderError::InvalidCommissionTokenAccount
+            );
+            commission_token_account.key()
+        }
+        None => {
+            require!(commission_rate == 0, LimitOrderError::InvalidCommissionTokenAccount);
+            Pubkey::default()
+        }
+    };
+
     // Prepaid trade fee
     transfer_sol(
-        ctx.accounts.maker.to_account_info(),
-        ctx.accounts.order_pda.to_account_info(),
+        accounts.maker.to_account_info(),
+        accounts.order_pda.to_account_info(),
         trade_fee,
         None,
     )?;
 
-    let before_balance = ctx.accounts.escrow_token_account.amount;
+    // The escrow must be debitable by the order PDA alone, e.g. not frozen by a default state
+    let escrow = unpack_token_account(&accounts.escrow_token_account.to_account_info())?;
+    require!(
+        escrow.is_held_by(&accounts.order_pda.key(), &accounts.input_token_mint.key())
+            && escrow.delegate.is_none(),
+        LimitOrderError::InvalidEscrowTokenAccount
+    );
+
+    let before_balance = accounts.escrow_token_account.amount;
     // Transfer input token from user to escrow account
-    transfer_token(
-        ctx.accounts.maker.to_account_info(),
-        ctx.accounts.input_token_account.to_account_info(),
-        ctx.accounts.escrow_token_account.to_account_info(),
-        ctx.accounts.input_token_mint.to_account_info(),
-        ctx.accounts.input_token_program.to_account_info(),
+    transfer_token_with_hook(
+        accounts.maker.to_account_info(),
+        accounts.input_token_account.to_account_info(),
+        accounts.escrow_token_account.to_account_info(),
+        accounts.input_token_mint.to_account_info(),
+        accounts.input_token_program.to_account_info(),
         making_amount,
-        ctx.accounts.input_token_mint.decimals,
+        accounts.input_token_mint.decimals,
         None,
+        accounts.hook_accounts,
     )?;
 
     // Calculate the actual making amount
-    ctx.accounts.escrow_token_account.reload()?;
-    let after_balance = ctx.accounts.escrow_token_account.amount;
+    accounts.escrow_token_account.reload()?;
+    let after_balance = accounts.escrow_token_account.amount;
     let actual_making_amount =
         after_balance.checked_sub(before_balance).ok_or(LimitOrderError::MathOverflow)?;
     require!(actual_making_amount > 0, LimitOrderError::ActualMakingAmountIsZero);
 
-    let maker = ctx.accounts.maker.key();
-    let input_token_mint = ctx.accounts.input_token_mint.key();
-    let output_token_mint = ctx.accounts.output_token_mint.key();
+    let maker = accounts.maker.key();
+    let input_token_mint = accounts.input_token_mint.key();
+    let output_token_mint = accounts.output_token_mint.key();
+    let receiver = args.receiver.filter(|receiver| *receiver != Pubkey::default()).unwrap_or(maker);
 
     // Initialize the order PDA
-    let order_pda = &mut ctx.accounts.order_pda;
+    let order_pda = accounts.order_pda;
+    order_pda.version = OrderV2::VERSION;
+    order_pda.flags = flags;
     order_pda.order_id = order_id;
     order_pda.maker = maker;
     order_pda.making_amount = actual_making_amount;
     order_pda.expect_taking_amount = expect_taking_amount;
     order_pda.min_return_amount = min_return_amount;
     order_pda.create_ts = create_ts;
+    order_pda.start_ts = start_ts;
     order_pda.deadline = deadline;
-    order_pda.escrow_token_account = ctx.accounts.escrow_token_account.key();
+    order_pda.escrow_token_account = accounts.escrow_token_account.key();
     order_pda.input_token_mint = input_token_mint;
     order_pda.output_token_mint = output_token_mint;
-    order_pda.input_token_program = ctx.accounts.input_token_program.key();
-    order_pda.output_token_program = ctx.accounts.output_token_program.key();
-    order_pda.bump = ctx.bumps.order_pda;
+    order_pda.input_token_program = accounts.input_token_program.key();
+    order_pda.output_token_program = accounts.output_token_program.key();
+    order_pda.receiver = receiver;
+    order_pda.commission_locked = commission_locked;
+    order_pda.commission_direction = commission_direction;
+    order_pda.commission_rate = commission_rate;
+    order_pda.commission_token_account = commission_token_account;
+    order_pda.max_fill_fee = max_fill_fee;
+    order_pda.bump = accounts.order_bump;
+    order_pda.filled_making_amount = 0;
+    order_pda.filled_taking_amount = 0;
     order_pda.padding = [0u8; 128];
 
-    emit_cpi!(PlaceOrderEvent {
+    Ok(PlaceOrderEvent {
         order_id,
         maker,
         input_token_mint,
@@ -170,6 +410,11 @@ pub fn place_order_handler(
         create_ts,
         deadline,
         trade_fee,
-    });
-    Ok(())
+        receiver,
+        commission_info,
+        commission_token_account,
+        max_fill_fee,
+        flags,
+        start_ts,
+    })
 }
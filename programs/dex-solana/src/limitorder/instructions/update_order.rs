@@ -22,17 +22,9 @@ pub struct UpdateOrder<'info> {
     )]
     pub global_config: AccountLoader<'info, GlobalConfig>,
 
-    /// The order PDA account
-    #[account(
-        mut,
-        seeds = [
-            ORDER_V1_SEED.as_bytes(),
-            &order_id.to_le_bytes(),
-            maker.key().as_ref(),
-        ],
-        bump = order_pda.bump,
-    )]
-    pub order_pda: Account<'info, OrderV1>,
+    /// CHECK: The order PDA account, a V1 order is migrated to V2 in place
+    #[account(mut)]
+    pub order_pda: UncheckedAccount<'info>,
 
     /// System program
     pub system_program: Program<'info, System>,
@@ -49,7 +41,9 @@ pub fn update_order_handler<'a>(
     msg!("UpdateOrder orderId: {}", order_id);
 
     // Check if the order is expired
-    let order = &mut ctx.accounts.order_pda;
+    let maker = ctx.accounts.maker.to_account_info();
+    let order_info = ctx.accounts.order_pda.to_account_info();
+    let mut order = OrderV2::load_mut(&order_info, &maker, order_id, maker.key)?;
     let update_ts = solana_program::clock::Clock::get()?.unix_timestamp as u64;
     require_gte!(order.deadline, update_ts, LimitOrderError::OrderExpired);
 
@@ -81,13 +75,9 @@ pub fn update_order_handler<'a>(
 
     // Increase trade fee
     if increase_fee > 0 {
-        transfer_sol(
-            ctx.accounts.maker.to_account_info(),
-            order.to_account_info(),
-            increase_fee,
-            None,
-        )?;
+        transfer_sol(maker, order_info.clone(), increase_fee, None)?;
     }
+    order.write(&order_info)?;
 
     emit_cpi!(UpdateOrderEvent {
         order_id: order.order_id,
@@ -4,6 +4,8 @@ pub mod proxy_swap_processor;
 pub mod swap_processor;
 pub mod swap_tob_processor;
 pub mod swap_toc_processor;
+pub mod swap_toc_split_processor;
+pub mod swap_toc_vault_processor;
 
 pub use common_processor::*;
 pub use platform_fee_processor::*;
@@ -11,3 +13,5 @@ pub use proxy_swap_processor::*;
 pub use swap_processor::*;
 pub use swap_tob_processor::*;
 pub use swap_toc_processor::*;
+pub use swap_toc_split_processor::*;
+pub use swap_toc_vault_processor::*;
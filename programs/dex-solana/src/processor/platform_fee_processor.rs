@@ -1,3 +1,4 @@
+use crate::utils::TransferContext;
 use anchor_lang::prelude::*;
 use anchor_spl::associated_token::AssociatedToken;
 use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
@@ -38,6 +39,7 @@ pub trait PlatformFeeV3Processor<'info> {
         // PLATFORM FEE
         _platform_fee_rate: Option<u16>,
         _platform_fee_account: &Option<AccountInfo<'info>>,
+        _transfer_ctx: &TransferContext<'_, 'info>,
     ) -> Result<u64> {
         Ok(_amount_in)
     }
@@ -62,9 +64,11 @@ pub trait PlatformFeeV3Processor<'info> {
         // TRIM
         _trim_rate: Option<u8>,
         _charge_rate: Option<u16>,
+        _user_share_rate: Option<u16>,
         _trim_account: Option<&AccountInfo<'info>>,
         _charge_account: Option<&AccountInfo<'info>>,
         _acc_close_flag: bool,
+        _transfer_ctx: &TransferContext<'_, 'info>,
     ) -> Result<u64> {
         Ok(_amount_out)
     }
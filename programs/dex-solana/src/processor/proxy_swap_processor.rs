@@ -3,9 +3,6 @@ use crate::error::ErrorCode;
 use crate::processor::common_processor::CommonSwapProcessor;
 use crate::utils::*;
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::program_pack::Pack;
-use anchor_spl::token::spl_token::state::Account as SplTokenAccount;
-use anchor_spl::token_2022::spl_token_2022::state::Account as SplToken2022Account;
 use anchor_spl::{
     associated_token::AssociatedToken,
     token_interface::{Mint, TokenAccount, TokenInterface},
@@ -13,18 +10,6 @@ use anchor_spl::{
 
 pub struct ProxySwapProcessor;
 
-fn unpack_token_owner_and_mint(account_info: &AccountInfo) -> Result<(Pubkey, Pubkey)> {
-    if *account_info.owner == anchor_spl::token::Token::id() {
-        let acc = SplTokenAccount::unpack(&account_info.try_borrow_data()?)?;
-        Ok((acc.owner, acc.mint))
-    } else if *account_info.owner == anchor_spl::token_2022::Token2022::id() {
-        let acc = SplToken2022Account::unpack_from_slice(&account_info.try_borrow_data()?)?;
-        Ok((acc.owner, acc.mint))
-    } else {
-        Err(ErrorCode::InvalidTokenAccount.into())
-    }
-}
-
 impl ProxySwapProcessor {
     pub fn get_swap_accounts<'info>(
         &self,
@@ -67,7 +52,7 @@ impl ProxySwapProcessor {
         Ok((source_account, destination_account))
     }
 
-    /// Proxy handle before swap
+    /// Proxy handle before swap, returns the amount the SA received net of the transfer fee
     pub fn proxy_handle_before<'info>(
         &self,
         payer: &AccountInfo<'info>,
@@ -77,17 +62,18 @@ impl ProxySwapProcessor {
         source_token_program: &Option<Interface<'info, TokenInterface>>,
         amount: u64,
         owner_seeds: Option<&[&[&[u8]]]>,
-    ) -> Result<()> {
+        transfer_ctx: &TransferContext<'_, 'info>,
+    ) -> Result<u64> {
         if source_token_sa.is_none() || source_token_program.is_none() {
-            return Ok(());
+            return Ok(amount);
         }
         let source_token_program = source_token_program.as_ref().unwrap();
         let source_token_sa_info = source_token_sa.as_ref().unwrap().to_account_info();
-        let (acc_owner, acc_mint) = unpack_token_owner_and_mint(&source_token_sa_info)?;
-        require_keys_eq!(acc_owner, authority_pda::ID, ErrorCode::InvalidSourceTokenSa);
-        require_keys_eq!(acc_mint, source_mint.key(), ErrorCode::InvalidSourceTokenSaMint);
+        let token_sa = unpack_token_account(&source_token_sa_info)?;
+        require_keys_eq!(token_sa.owner, authority_pda::ID, ErrorCode::InvalidSourceTokenSa);
+        require_keys_eq!(token_sa.mint, source_mint.key(), ErrorCode::InvalidSourceTokenSaMint);
 
-        transfer_token(
+        transfer_token_routed(
             payer.to_account_info(),
             source_token_account.to_account_info(),
             source_token_sa_info,
@@ -96,11 +82,16 @@ impl ProxySwapProcessor {
             amount,
             source_mint.decimals,
             owner_seeds,
+            transfer_ctx,
         )?;
-        Ok(())
+
+        // The SA receives the amount net of the transfer fee of the source mint
+        amount
+            .checked_sub(get_transfer_fee(&source_mint.to_account_info(), amount)?)
+            .ok_or(ErrorCode::CalculationError.into())
     }
 
-    /// Proxy handle after swap
+    /// Proxy handle after swap, returns the amount received net of the transfer fee
     pub fn proxy_handle_after<'info>(
         &self,
         sa_authority: &Option<UncheckedAccount<'info>>,
@@ -110,26 +101,27 @@ impl ProxySwapProcessor {
         destination_token_program: &Option<Interface<'info, TokenInterface>>,
         amount_out: u64,
         owner_seeds: Option<&[&[&[u8]]]>,
-    ) -> Result<()> {
+        transfer_ctx: &TransferContext<'_, 'info>,
+    ) -> Result<u64> {
         if sa_authority.is_none()
             || destination_token_sa.is_none()
             || destination_token_program.is_none()
         {
-            return Ok(());
+            return Ok(amount_out);
         }
 
         let sa_authority = sa_authority.as_ref().unwrap();
         let destination_token_sa_info = destination_token_sa.as_ref().unwrap().to_account_info();
         let destination_token_program = destination_token_program.as_ref().unwrap();
-        let (acc_owner, acc_mint) = unpack_token_owner_and_mint(&destination_token_sa_info)?;
+        let token_sa = unpack_token_account(&destination_token_sa_info)?;
         require_keys_eq!(
-            acc_mint,
+            token_sa.mint,
             destination_mint.key(),
             ErrorCode::InvalidDestinationTokenSaMint
         );
-        require_keys_eq!(acc_owner, authority_pda::ID, ErrorCode::InvalidSourceTokenSa);
+        require_keys_eq!(token_sa.owner, authority_pda::ID, ErrorCode::InvalidSourceTokenSa);
 
-        transfer_token(
+        transfer_token_routed(
             sa_authority.to_account_info(),
             destination_token_sa_info,
             destination_token_account.to_account_info(),
@@ -138,8 +130,13 @@ impl ProxySwapProcessor {
             amount_out,
             destination_mint.decimals,
             owner_seeds,
+            transfer_ctx,
         )?;
-        Ok(())
+
+        // The destination token account receives the amount net of the transfer fee
+        amount_out
+            .checked_sub(get_transfer_fee(&destination_mint.to_account_info(), amount_out)?)
+            .ok_or(ErrorCode::CalculationError.into())
     }
 }
 
@@ -197,6 +194,7 @@ impl<'info> CommonSwapProcessor<'info> for ProxySwapProcessor {
             source_token_program,
             amount_in,
             owner_seeds,
+            &TransferContext::default(),
         )?;
         Ok(amount_in)
     }
@@ -223,6 +221,7 @@ impl<'info> CommonSwapProcessor<'info> for ProxySwapProcessor {
             destination_token_program,
             amount_out,
             owner_seeds,
+            &TransferContext::default(),
         )?;
         Ok(())
     }
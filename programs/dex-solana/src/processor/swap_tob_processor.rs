@@ -2,6 +2,7 @@ use crate::constants::*;
 use crate::error::ErrorCode;
 use crate::processor::platform_fee_processor::PlatformFeeV3Processor;
 use crate::processor::proxy_swap_processor::ProxySwapProcessor;
+use crate::state::event::{TransferFeeEvent, TrimEvent};
 use crate::utils::*;
 use anchor_lang::prelude::*;
 use anchor_spl::{
@@ -20,31 +21,16 @@ impl SwapToBProcessor {
         commission_direction: bool,
         platform_fee_rate: Option<u16>,
     ) -> Result<(u64, u64, u64, bool)> {
-        let mut commission_amount = 0;
-        let mut platform_fee_amount = 0;
-        let mut actual_amount_in = amount_in;
-
-        if commission_direction && commission_rate > 0 {
-            // Calculate commission and platform fee amounts
-            (commission_amount, platform_fee_amount) = calculate_fee_amounts(
-                amount_in,
-                commission_rate,
-                commission_direction,
-                platform_fee_rate,
-            )?;
-
-            actual_amount_in = actual_amount_in
-                .checked_add(commission_amount)
-                .ok_or(ErrorCode::CalculationError)?
-                .checked_add(platform_fee_amount)
-                .ok_or(ErrorCode::CalculationError)?;
-        }
+        let policy = FeePolicy::v3(commission_rate, commission_direction, platform_fee_rate);
+        let fees = quote_from_fees(&policy, amount_in)?;
+        let actual_amount_in =
+            amount_in.checked_add(fees.total()?).ok_or(ErrorCode::CalculationError)?;
 
         Ok((
-            commission_amount,
-            platform_fee_amount,
+            fees.commission_amount,
+            fees.platform_fee_amount,
             actual_amount_in,
-            commission_amount > 0 || platform_fee_amount > 0,
+            fees.commission_amount > 0 || fees.platform_fee_amount > 0,
         ))
     }
 
@@ -58,53 +44,23 @@ impl SwapToBProcessor {
         platform_fee_rate: Option<u16>,
         trim_rate: Option<u8>,
         charge_rate: Option<u16>,
-    ) -> Result<(u64, u64, u64, u64, u64, bool)> {
-        let mut commission_amount = 0;
-        let mut platform_fee_amount = 0;
-        let mut actual_amount_out = amount_out;
-
-        if !commission_direction && commission_rate > 0 {
-            // Calculate commission and platform fee amounts
-            (commission_amount, platform_fee_amount) = calculate_fee_amounts(
-                amount_out,
-                commission_rate,
-                commission_direction,
-                platform_fee_rate,
-            )?;
-        }
-
-        // Calculate trim amount
-        let (trim_amount, charge_amount) = calculate_trim_amount(
-            amount_out,
-            expected_amount_out,
-            commission_amount,
-            platform_fee_amount,
-            commission_direction,
-            trim_rate,
-            charge_rate,
-        )?;
-
-        // Calculate actual amount out
-        actual_amount_out = actual_amount_out
-            .checked_sub(commission_amount)
-            .ok_or(ErrorCode::CalculationError)?
-            .checked_sub(platform_fee_amount)
-            .ok_or(ErrorCode::CalculationError)?
-            .checked_sub(trim_amount)
-            .ok_or(ErrorCode::CalculationError)?
-            .checked_sub(charge_amount)
-            .ok_or(ErrorCode::CalculationError)?;
+        user_share_rate: Option<u16>,
+    ) -> Result<(u64, u64, TrimAmounts, u64, bool)> {
+        let policy = FeePolicy::v3(commission_rate, commission_direction, platform_fee_rate)
+            .with_trim(trim_rate, charge_rate)
+            .with_user_share(user_share_rate);
+        let (fees, trim, actual_amount_out) =
+            quote_to_fees(&policy, amount_out, expected_amount_out)?;
 
         Ok((
-            commission_amount,
-            platform_fee_amount,
-            trim_amount,
-            charge_amount,
+            fees.commission_amount,
+            fees.platform_fee_amount,
+            trim,
             actual_amount_out,
-            commission_amount > 0
-                || platform_fee_amount > 0
-                || trim_amount > 0
-                || charge_amount > 0,
+            fees.commission_amount > 0
+                || fees.platform_fee_amount > 0
+                || trim.trim_amount > 0
+                || trim.charge_amount > 0,
         ))
     }
 
@@ -119,7 +75,7 @@ impl SwapToBProcessor {
         destination_token_program: &Option<Interface<'info, TokenInterface>>,
         amount_out: u64,
     ) -> Result<()> {
-        require!(destination_mint.key() == wsol_program::ID, ErrorCode::InvalidMint);
+        require!(is_native_mint(&destination_mint.key()), ErrorCode::InvalidMint);
         require!(destination_token_program.is_some(), ErrorCode::DestinationTokenProgramIsNone);
         require!(
             destination_token_account.owner == payer.key(),
@@ -165,6 +121,7 @@ impl SwapToBProcessor {
         platform_fee_account: &Option<AccountInfo<'info>>,
         is_charge_fee: bool,
         is_charge_sol: bool,
+        transfer_ctx: &TransferContext<'_, 'info>,
     ) -> Result<()> {
         if !is_charge_fee {
             return Ok(());
@@ -178,74 +135,26 @@ impl SwapToBProcessor {
         require!(sa_authority.is_some(), ErrorCode::SaAuthorityIsNone);
         let sa_authority = sa_authority.as_ref().unwrap();
 
-        if is_charge_sol {
-            // Transfer SOL commission
-            if commission_amount > 0 {
-                let commission_account = commission_account.as_ref().unwrap();
-                let adjust_amount = transfer_sol_fee(
-                    sa_authority,
-                    commission_account,
-                    commission_amount,
-                    Some(SA_AUTHORITY_SEED),
-                )?;
-                log_commission_info(true, commission_amount, adjust_amount);
-                commission_account.key().log();
-            }
-
-            // Transfer SOL platform fee
-            if platform_fee_amount > 0 {
-                let platform_fee_account = platform_fee_account.as_ref().unwrap();
-                let adjust_amount = transfer_sol_fee(
-                    sa_authority,
-                    platform_fee_account,
-                    platform_fee_amount,
-                    Some(SA_AUTHORITY_SEED),
-                )?;
-                log_platform_fee_info(
-                    platform_fee_amount,
-                    adjust_amount,
-                    &platform_fee_account.key(),
-                );
-            }
-        } else {
+        if !is_charge_sol {
             require!(source_token_sa.is_some(), ErrorCode::SourceTokenSaIsNone);
             require!(source_token_program.is_some(), ErrorCode::SourceTokenProgramIsNone);
-            let source_token_sa = source_token_sa.as_ref().unwrap();
-            let source_token_program = source_token_program.as_ref().unwrap();
-
-            // Transfer token commission
-            if commission_amount > 0 {
-                let commission_account = commission_account.as_ref().unwrap();
-                transfer_token_fee(
-                    sa_authority,
-                    source_token_sa,
-                    source_mint,
-                    source_token_program,
-                    commission_account,
-                    commission_amount,
-                    Some(SA_AUTHORITY_SEED),
-                )?;
-                log_commission_info(true, commission_amount, 0);
-                commission_account.key().log();
-            }
-
-            // Transfer token platform fee
-            if platform_fee_amount > 0 {
-                let platform_fee_account = platform_fee_account.as_ref().unwrap();
-                transfer_token_fee(
-                    sa_authority,
-                    source_token_sa,
-                    source_mint,
-                    source_token_program,
-                    platform_fee_account,
-                    platform_fee_amount,
-                    Some(SA_AUTHORITY_SEED),
-                )?;
-                log_platform_fee_info(platform_fee_amount, 0, &platform_fee_account.key());
-            }
         }
-
-        Ok(())
+        let source = FeeSource {
+            authority: sa_authority,
+            token_account: source_token_sa.as_deref(),
+            token_mint: source_mint,
+            token_program: source_token_program.as_deref(),
+            signer_seeds: Some(SA_AUTHORITY_SEED),
+            is_charge_sol,
+            legacy_transfer: false,
+            transfer_ctx: *transfer_ctx,
+        };
+        FeeRecipients {
+            commission_account: commission_account.as_ref(),
+            platform_fee_account: platform_fee_account.as_ref(),
+            ..Default::default()
+        }
+        .pay_fees(&source, &FeeAmounts { commission_amount, platform_fee_amount }, true)
     }
 
     /// Transfer to fees and log results
@@ -263,6 +172,7 @@ impl SwapToBProcessor {
         commission_account: &Option<AccountInfo<'info>>,
         platform_fee_account: &Option<AccountInfo<'info>>,
         acc_close_flag: bool,
+        transfer_ctx: &TransferContext<'_, 'info>,
     ) -> Result<bool> {
         if commission_amount == 0 && platform_fee_amount == 0 {
             return Ok(false);
@@ -286,7 +196,9 @@ impl SwapToBProcessor {
             }
         }
 
-        if is_charge_sol(commission_account, platform_fee_account, destination_mint) {
+        let is_charge_sol =
+            is_charge_sol(commission_account, platform_fee_account, destination_mint);
+        if is_charge_sol {
             // Unwrap wsol to sa_authority
             self.unwrap_wsol_to_sa(
                 payer,
@@ -297,74 +209,31 @@ impl SwapToBProcessor {
                 destination_token_program,
                 amount_out,
             )?;
-
-            // Transfer SOL fees
-            if commission_amount > 0 {
-                let commission_account = commission_account.as_ref().unwrap();
-                let adjust_amount = transfer_sol_fee(
-                    sa_authority,
-                    commission_account,
-                    commission_amount,
-                    Some(SA_AUTHORITY_SEED),
-                )?;
-                log_commission_info(false, commission_amount, adjust_amount);
-                commission_account.key().log();
-            }
-
-            if platform_fee_amount > 0 {
-                let platform_fee_account = platform_fee_account.as_ref().unwrap();
-                let adjust_amount = transfer_sol_fee(
-                    sa_authority,
-                    platform_fee_account,
-                    platform_fee_amount,
-                    Some(SA_AUTHORITY_SEED),
-                )?;
-                log_platform_fee_info(
-                    platform_fee_amount,
-                    adjust_amount,
-                    &platform_fee_account.key(),
-                );
-            }
-
-            return Ok(true);
         } else {
             require!(destination_token_sa.is_some(), ErrorCode::DestinationTokenSaIsNone);
             require!(destination_token_program.is_some(), ErrorCode::DestinationTokenProgramIsNone);
-            let destination_token_sa = destination_token_sa.as_ref().unwrap();
-            let destination_token_program = destination_token_program.as_ref().unwrap();
-
-            // Transfer token fees
-            if commission_amount > 0 {
-                let commission_account = commission_account.as_ref().unwrap();
-                transfer_token_fee(
-                    sa_authority,
-                    destination_token_sa,
-                    destination_mint,
-                    destination_token_program,
-                    commission_account,
-                    commission_amount,
-                    Some(SA_AUTHORITY_SEED),
-                )?;
-                log_commission_info(false, commission_amount, 0);
-                commission_account.key().log();
-            }
-
-            if platform_fee_amount > 0 {
-                let platform_fee_account = platform_fee_account.as_ref().unwrap();
-                transfer_token_fee(
-                    sa_authority,
-                    destination_token_sa,
-                    destination_mint,
-                    destination_token_program,
-                    platform_fee_account,
-                    platform_fee_amount,
-                    Some(SA_AUTHORITY_SEED),
-                )?;
-                log_platform_fee_info(platform_fee_amount, 0, &platform_fee_account.key());
-            }
-
-            return Ok(false);
         }
+        let source = FeeSource {
+            authority: sa_authority,
+            token_account: destination_token_sa.as_deref(),
+            token_mint: destination_mint,
+            token_program: destination_token_program.as_deref(),
+            signer_seeds: Some(SA_AUTHORITY_SEED),
+            is_charge_sol,
+            legacy_transfer: false,
+            transfer_ctx: *transfer_ctx,
+        };
+        FeeRecipients {
+            commission_account: commission_account.as_ref(),
+            platform_fee_account: platform_fee_account.as_ref(),
+            ..Default::default()
+        }
+        .pay_fees(
+            &source,
+            &FeeAmounts { commission_amount, platform_fee_amount },
+            false,
+        )?;
+        Ok(is_charge_sol)
     }
 
     /// Transfer trim and log results
@@ -383,6 +252,7 @@ impl SwapToBProcessor {
         charge_account: Option<&AccountInfo<'info>>,
         is_unwrap_wsol_to_sa: bool,
         acc_close_flag: bool,
+        transfer_ctx: &TransferContext<'_, 'info>,
     ) -> Result<bool> {
         if trim_amount == 0 && charge_amount == 0 {
             return Ok(is_unwrap_wsol_to_sa);
@@ -405,7 +275,9 @@ impl SwapToBProcessor {
             None
         };
 
-        if is_charge_sol(&Some(trim_account.to_account_info()), &charge_account, destination_mint) {
+        let is_charge_sol =
+            is_charge_sol(&Some(trim_account.to_account_info()), &charge_account, destination_mint);
+        if is_charge_sol {
             if !is_unwrap_wsol_to_sa {
                 // Unwrap wsol to sa_authority
                 self.unwrap_wsol_to_sa(
@@ -418,61 +290,27 @@ impl SwapToBProcessor {
                     amount_out,
                 )?;
             }
-
-            if trim_amount > 0 {
-                // Transfer SOL trim fee
-                let adjust_amount = transfer_sol_fee(
-                    sa_authority,
-                    trim_account,
-                    trim_amount,
-                    Some(SA_AUTHORITY_SEED),
-                )?;
-                log_trim_fee_info(trim_amount, adjust_amount, &trim_account.key());
-            }
-
-            if let Some(charge_acc) = charge_account {
-                let adjust_amount = transfer_sol_fee(
-                    sa_authority,
-                    &charge_acc,
-                    charge_amount,
-                    Some(SA_AUTHORITY_SEED),
-                )?;
-                log_charge_fee_info(charge_amount, adjust_amount, &charge_acc.key());
-            }
-            return Ok(true);
         } else {
             require!(destination_token_sa.is_some(), ErrorCode::DestinationTokenSaIsNone);
             require!(destination_token_program.is_some(), ErrorCode::DestinationTokenProgramIsNone);
-            let destination_token_sa = destination_token_sa.as_ref().unwrap();
-            let destination_token_program = destination_token_program.as_ref().unwrap();
-
-            if trim_amount > 0 {
-                transfer_token_fee(
-                    sa_authority,
-                    destination_token_sa,
-                    destination_mint,
-                    destination_token_program,
-                    trim_account,
-                    trim_amount,
-                    Some(SA_AUTHORITY_SEED),
-                )?;
-                log_trim_fee_info(trim_amount, 0, &trim_account.key());
-            }
-
-            if let Some(charge_acc) = charge_account {
-                transfer_token_fee(
-                    sa_authority,
-                    destination_token_sa,
-                    destination_mint,
-                    destination_token_program,
-                    &charge_acc,
-                    charge_amount,
-                    Some(SA_AUTHORITY_SEED),
-                )?;
-                log_charge_fee_info(charge_amount, 0, &charge_acc.key());
-            }
-            return Ok(false);
         }
+        let source = FeeSource {
+            authority: sa_authority,
+            token_account: destination_token_sa.as_deref(),
+            token_mint: destination_mint,
+            token_program: destination_token_program.as_deref(),
+            signer_seeds: Some(SA_AUTHORITY_SEED),
+            is_charge_sol,
+            legacy_transfer: false,
+            transfer_ctx: *transfer_ctx,
+        };
+        FeeRecipients {
+            trim_account: Some(trim_account),
+            charge_account: charge_account.as_ref(),
+            ..Default::default()
+        }
+        .pay_trim(&source, &TrimAmounts { trim_amount, charge_amount, user_share_amount: 0 })?;
+        Ok(is_charge_sol)
     }
 
     /// Transfer remaining token or SOL to user
@@ -487,6 +325,7 @@ impl SwapToBProcessor {
         actual_amount_out: u64,
         is_unwrap_wsol_to_sa: bool,
         acc_close_flag: bool,
+        transfer_ctx: &TransferContext<'_, 'info>,
     ) -> Result<()> {
         if is_unwrap_wsol_to_sa {
             // Transfer remaining SOL & token account rent to payer
@@ -505,7 +344,7 @@ impl SwapToBProcessor {
             let destination_token_program = destination_token_program.as_ref().unwrap();
 
             // Transfer remaining tokens to destination account
-            transfer_token(
+            transfer_token_routed(
                 sa_authority.to_account_info(),
                 destination_token_sa.to_account_info(),
                 destination_token_account.to_account_info(),
@@ -514,10 +353,11 @@ impl SwapToBProcessor {
                 actual_amount_out,
                 destination_mint.decimals,
                 Some(SA_AUTHORITY_SEED),
+                transfer_ctx,
             )?;
 
             if acc_close_flag
-                && destination_token_account.mint == wsol_program::ID
+                && is_native_mint(&destination_token_account.mint)
                 && is_token_account_initialized(&destination_token_account.to_account_info())
             {
                 close_token_account(
@@ -580,6 +420,7 @@ impl<'info> PlatformFeeV3Processor<'info> for SwapToBProcessor {
         commission_account: &Option<AccountInfo<'info>>,
         platform_fee_rate: Option<u16>,
         platform_fee_account: &Option<AccountInfo<'info>>,
+        transfer_ctx: &TransferContext<'_, 'info>,
     ) -> Result<u64> {
         // Check if commission is SOL
         let is_charge_sol = is_charge_sol(commission_account, platform_fee_account, source_mint);
@@ -593,45 +434,37 @@ impl<'info> PlatformFeeV3Processor<'info> for SwapToBProcessor {
                 platform_fee_rate,
             )?;
 
-        // Proxy handle before swap
-        if is_charge_sol {
-            ProxySwapProcessor.proxy_handle_before(
-                payer,
-                source_token_account,
-                source_token_sa,
-                source_mint,
-                source_token_program,
-                amount_in,
-                None,
-            )?;
-            if is_charge_fee {
-                require!(sa_authority.is_some(), ErrorCode::SaAuthorityIsNone);
-                let sa_authority = sa_authority.as_ref().unwrap();
-                require!(sa_authority.key() == authority_pda::ID, ErrorCode::InvalidSaAuthority);
-                let total_fee = commission_amount
-                    .checked_add(platform_fee_amount)
-                    .ok_or(ErrorCode::CalculationError)?;
-
-                // Transfer SOL fees to sa_authority
-                transfer_sol(
-                    payer.to_account_info(),
-                    sa_authority.to_account_info(),
-                    total_fee,
-                    None,
-                )?;
-            }
-        } else {
-            ProxySwapProcessor.proxy_handle_before(
-                payer,
-                source_token_account,
-                source_token_sa,
-                source_mint,
-                source_token_program,
-                actual_amount_in,
-                None,
-            )?;
+        // Proxy handle before swap, the SA bears the transfer fee of the source mint
+        let debit_amount = if is_charge_sol { amount_in } else { actual_amount_in };
+        let received_amount = ProxySwapProcessor.proxy_handle_before(
+            payer,
+            source_token_account,
+            source_token_sa,
+            source_mint,
+            source_token_program,
+            debit_amount,
+            None,
+            transfer_ctx,
+        )?;
+        if is_charge_sol && is_charge_fee {
+            require!(sa_authority.is_some(), ErrorCode::SaAuthorityIsNone);
+            let sa_authority = sa_authority.as_ref().unwrap();
+            require!(sa_authority.key() == authority_pda::ID, ErrorCode::InvalidSaAuthority);
+            let total_fee = commission_amount
+                .checked_add(platform_fee_amount)
+                .ok_or(ErrorCode::CalculationError)?;
+
+            // Transfer SOL fees to sa_authority
+            transfer_sol(payer.to_account_info(), sa_authority.to_account_info(), total_fee, None)?;
         }
 
+        // The fees are paid in full, the swap gets the rest net of the transfer fee
+        let real_amount_in = amount_in
+            .checked_sub(
+                debit_amount.checked_sub(received_amount).ok_or(ErrorCode::CalculationError)?,
+            )
+            .ok_or(ErrorCode::CalculationError)?;
+
         // Transfer from fees and log results
         self.transfer_from_fees_and_log(
             sa_authority,
@@ -644,9 +477,10 @@ impl<'info> PlatformFeeV3Processor<'info> for SwapToBProcessor {
             platform_fee_account,
             is_charge_fee,
             is_charge_sol,
+            transfer_ctx,
         )?;
 
-        Ok(amount_in)
+        Ok(real_amount_in)
     }
 
     fn after_swap(
@@ -666,27 +500,39 @@ impl<'info> PlatformFeeV3Processor<'info> for SwapToBProcessor {
         platform_fee_account: &Option<AccountInfo<'info>>,
         trim_rate: Option<u8>,
         charge_rate: Option<u16>,
+        user_share_rate: Option<u16>,
         trim_account: Option<&AccountInfo<'info>>,
         charge_account: Option<&AccountInfo<'info>>,
         acc_close_flag: bool,
+        transfer_ctx: &TransferContext<'_, 'info>,
     ) -> Result<u64> {
+        // The user bears the transfer fee of the destination mint when receiving from the SA,
+        // trim only what is left above the expected amount out after that fee
+        let expected_amount_out =
+            get_transfer_gross_amount(&destination_mint.to_account_info(), expected_amount_out)?;
+
         // Calculate fees and actual amount out if commission is applied to to
-        let (
-            commission_amount,
-            platform_fee_amount,
-            trim_amount,
-            charge_amount,
-            actual_amount_out,
-            is_charge_fee,
-        ) = self.calculate_to_fees(
-            amount_out,
-            expected_amount_out,
-            commission_rate,
-            commission_direction,
-            platform_fee_rate,
-            trim_rate,
-            charge_rate,
-        )?;
+        let (commission_amount, platform_fee_amount, trim, actual_amount_out, is_charge_fee) = self
+            .calculate_to_fees(
+                amount_out,
+                expected_amount_out,
+                commission_rate,
+                commission_direction,
+                platform_fee_rate,
+                trim_rate,
+                charge_rate,
+                user_share_rate,
+            )?;
+        if trim.user_share_amount > 0 {
+            log_user_share_info(trim.user_share_amount);
+        }
+        if trim_rate.unwrap_or(0) > 0 {
+            emit!(TrimEvent {
+                trim_amount: trim.trim_amount,
+                charge_amount: trim.charge_amount,
+                user_share_amount: trim.user_share_amount,
+            });
+        }
 
         // Proxy handle after swap
         if !is_charge_fee {
@@ -698,10 +544,11 @@ impl<'info> PlatformFeeV3Processor<'info> for SwapToBProcessor {
                 destination_token_program,
                 amount_out,
                 Some(SA_AUTHORITY_SEED),
+                transfer_ctx,
             )?;
 
             if acc_close_flag
-                && destination_token_account.mint == wsol_program::ID
+                && is_native_mint(&destination_token_account.mint)
                 && is_token_account_initialized(&destination_token_account.to_account_info())
             {
                 require!(
@@ -734,6 +581,7 @@ impl<'info> PlatformFeeV3Processor<'info> for SwapToBProcessor {
                 commission_account,
                 platform_fee_account,
                 acc_close_flag,
+                transfer_ctx,
             )?;
 
             is_unwrap_wsol_to_sa = self.transfer_trim_and_log(
@@ -744,12 +592,13 @@ impl<'info> PlatformFeeV3Processor<'info> for SwapToBProcessor {
                 destination_token_sa,
                 destination_token_program,
                 amount_out,
-                trim_amount,
-                charge_amount,
+                trim.trim_amount,
+                trim.charge_amount,
                 trim_account,
                 charge_account,
                 is_unwrap_wsol_to_sa,
                 acc_close_flag,
+                transfer_ctx,
             )?;
 
             self.transfer_to_user(
@@ -762,9 +611,24 @@ impl<'info> PlatformFeeV3Processor<'info> for SwapToBProcessor {
                 actual_amount_out,
                 is_unwrap_wsol_to_sa,
                 acc_close_flag,
+                transfer_ctx,
             )?;
         }
 
-        Ok(actual_amount_out)
+        // Return what the user received, net of the transfer fee
+        let transfer_fee =
+            get_transfer_fee(&destination_mint.to_account_info(), actual_amount_out)?;
+        if transfer_fee == 0 {
+            return Ok(actual_amount_out);
+        }
+        let net_amount_out =
+            actual_amount_out.checked_sub(transfer_fee).ok_or(ErrorCode::CalculationError)?;
+        emit!(TransferFeeEvent {
+            mint: destination_mint.key(),
+            account: destination_token_account.key(),
+            gross_amount: actual_amount_out,
+            net_amount: net_amount_out,
+        });
+        Ok(net_amount_out)
     }
 }
@@ -9,251 +9,60 @@ use anchor_spl::{
     token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
-pub struct SwapToCProcessor;
-
-impl SwapToCProcessor {
-    /// Calculate fees if commission is applied to from amount
-    fn calculate_from_fees<'info>(
+/// Pays out the commission and platform fee of a ToC swap
+pub trait ToCFeePayout<'info> {
+    /// Whether the fees of a native mint are paid in SOL
+    fn is_charge_sol(
         &self,
-        amount_in: u64,
-        commission_rate: u32,
-        commission_direction: bool,
-        platform_fee_rate: Option<u16>,
-    ) -> Result<(u64, u64, bool)> {
-        let mut commission_amount = 0;
-        let mut platform_fee_amount = 0;
-
-        if commission_direction && commission_rate > 0 {
-            // Calculate commission and platform fee amounts
-            (commission_amount, platform_fee_amount) = calculate_fee_amounts(
-                amount_in,
-                commission_rate,
-                commission_direction,
-                platform_fee_rate,
-            )?;
-        }
-
-        Ok((
-            commission_amount,
-            platform_fee_amount,
-            commission_amount > 0 || platform_fee_amount > 0,
-        ))
-    }
+        commission_account: &Option<AccountInfo<'info>>,
+        platform_fee_account: &Option<AccountInfo<'info>>,
+        token_mint: &InterfaceAccount<'info, Mint>,
+    ) -> bool;
 
-    /// Calculate fees if commission is applied to to amount
-    fn calculate_to_fees<'info>(
+    fn pay_fees(
         &self,
-        amount_out: u64,
-        commission_rate: u32,
+        source: &FeeSource<'_, 'info>,
+        fees: &FeeAmounts,
+        commission_account: &Option<AccountInfo<'info>>,
+        platform_fee_account: &Option<AccountInfo<'info>>,
         commission_direction: bool,
-        platform_fee_rate: Option<u16>,
-    ) -> Result<(u64, u64, u64, bool)> {
-        let mut commission_amount = 0;
-        let mut platform_fee_amount = 0;
-        let mut actual_amount_out = amount_out;
-
-        if !commission_direction && commission_rate > 0 {
-            // Calculate commission and platform fee amounts
-            (commission_amount, platform_fee_amount) = calculate_fee_amounts(
-                amount_out,
-                commission_rate,
-                commission_direction,
-                platform_fee_rate,
-            )?;
-
-            // Calculate actual amount out
-            actual_amount_out = actual_amount_out
-                .checked_sub(commission_amount)
-                .ok_or(ErrorCode::CalculationError)?
-                .checked_sub(platform_fee_amount)
-                .ok_or(ErrorCode::CalculationError)?;
-        }
+    ) -> Result<()>;
+}
 
-        Ok((
-            commission_amount,
-            platform_fee_amount,
-            actual_amount_out,
-            commission_amount > 0 || platform_fee_amount > 0,
-        ))
-    }
+/// Pays the fees to the commission account and the platform fee account
+pub struct FeeAccountsPayout;
 
-    /// Transfer from fees and log results
-    fn transfer_from_fees_and_log<'info>(
+impl<'info> ToCFeePayout<'info> for FeeAccountsPayout {
+    fn is_charge_sol(
         &self,
-        payer: &AccountInfo<'info>,
-        source_token_account: &InterfaceAccount<'info, TokenAccount>,
-        source_mint: &InterfaceAccount<'info, Mint>,
-        source_token_program: &Option<Interface<'info, TokenInterface>>,
-        commission_amount: u64,
-        platform_fee_amount: u64,
         commission_account: &Option<AccountInfo<'info>>,
         platform_fee_account: &Option<AccountInfo<'info>>,
-        is_charge_fee: bool,
-    ) -> Result<()> {
-        if !is_charge_fee {
-            return Ok(());
-        }
-        if commission_amount > 0 {
-            require!(commission_account.is_some(), ErrorCode::CommissionAccountIsNone);
-        }
-        if platform_fee_amount > 0 {
-            require!(platform_fee_account.is_some(), ErrorCode::PlatformFeeAccountIsNone);
-        }
-
-        if is_charge_sol(commission_account, platform_fee_account, source_mint) {
-            // Transfer SOL commission
-            if commission_amount > 0 {
-                let commission_account = commission_account.as_ref().unwrap();
-                let adjust_amount =
-                    transfer_sol_fee(payer, commission_account, commission_amount, None)?;
-                log_commission_info(true, commission_amount, adjust_amount);
-                commission_account.key().log();
-            }
-
-            // Transfer SOL platform fee
-            if platform_fee_amount > 0 {
-                let platform_fee_account = platform_fee_account.as_ref().unwrap();
-                let adjust_amount =
-                    transfer_sol_fee(payer, platform_fee_account, platform_fee_amount, None)?;
-                log_platform_fee_info(
-                    platform_fee_amount,
-                    adjust_amount,
-                    &platform_fee_account.key(),
-                );
-            }
-        } else {
-            require!(source_token_program.is_some(), ErrorCode::SourceTokenProgramIsNone);
-            let source_token_program = source_token_program.as_ref().unwrap();
-
-            // Transfer token commission
-            if commission_amount > 0 {
-                let commission_account = commission_account.as_ref().unwrap();
-                transfer_token_fee(
-                    payer,
-                    &source_token_account.to_account_info(),
-                    source_mint,
-                    source_token_program,
-                    commission_account,
-                    commission_amount,
-                    None,
-                )?;
-                log_commission_info(true, commission_amount, 0);
-                commission_account.key().log();
-            }
-
-            // Transfer token platform fee
-            if platform_fee_amount > 0 {
-                let platform_fee_account = platform_fee_account.as_ref().unwrap();
-                transfer_token_fee(
-                    payer,
-                    &source_token_account.to_account_info(),
-                    source_mint,
-                    source_token_program,
-                    platform_fee_account,
-                    platform_fee_amount,
-                    None,
-                )?;
-                log_platform_fee_info(platform_fee_amount, 0, &platform_fee_account.key());
-            }
-        }
-
-        Ok(())
+        token_mint: &InterfaceAccount<'info, Mint>,
+    ) -> bool {
+        is_charge_sol(commission_account, platform_fee_account, token_mint)
     }
 
-    /// Transfer to fees and log results
-    fn transfer_to_fees_and_log<'info>(
+    fn pay_fees(
         &self,
-        payer: &AccountInfo<'info>,
-        destination_token_account: &InterfaceAccount<'info, TokenAccount>,
-        destination_mint: &InterfaceAccount<'info, Mint>,
-        destination_token_program: &Option<Interface<'info, TokenInterface>>,
-        commission_amount: u64,
-        platform_fee_amount: u64,
+        source: &FeeSource<'_, 'info>,
+        fees: &FeeAmounts,
         commission_account: &Option<AccountInfo<'info>>,
         platform_fee_account: &Option<AccountInfo<'info>>,
-        is_charge_fee: bool,
+        commission_direction: bool,
     ) -> Result<()> {
-        if !is_charge_fee {
-            return Ok(());
+        FeeRecipients {
+            commission_account: commission_account.as_ref(),
+            platform_fee_account: platform_fee_account.as_ref(),
+            ..Default::default()
         }
-        if commission_amount > 0 {
-            require!(commission_account.is_some(), ErrorCode::CommissionAccountIsNone);
-        }
-        if platform_fee_amount > 0 {
-            require!(platform_fee_account.is_some(), ErrorCode::PlatformFeeAccountIsNone);
-        }
-        require!(destination_token_program.is_some(), ErrorCode::DestinationTokenProgramIsNone);
-        let destination_token_program = destination_token_program.as_ref().unwrap();
-
-        if is_charge_sol(commission_account, platform_fee_account, destination_mint) {
-            // Close temp wsol token account
-            require!(
-                destination_token_account.owner == payer.key(),
-                ErrorCode::InvalidDestinationTokenAccount
-            );
-            close_token_account(
-                destination_token_account.to_account_info(),
-                payer.to_account_info(),
-                payer.to_account_info(),
-                destination_token_program.to_account_info(),
-                None,
-            )?;
-
-            // Transfer sol fees
-            if commission_amount > 0 {
-                let commission_account = commission_account.as_ref().unwrap();
-                let adjust_amount =
-                    transfer_sol_fee(payer, commission_account, commission_amount, None)?;
-                log_commission_info(false, commission_amount, adjust_amount);
-                commission_account.key().log();
-            }
-
-            if platform_fee_amount > 0 {
-                let platform_fee_account = platform_fee_account.as_ref().unwrap();
-                let adjust_amount =
-                    transfer_sol_fee(payer, platform_fee_account, platform_fee_amount, None)?;
-                log_platform_fee_info(
-                    platform_fee_amount,
-                    adjust_amount,
-                    &platform_fee_account.key(),
-                );
-            }
-        } else {
-            // Transfer token fees
-            if commission_amount > 0 {
-                let commission_account = commission_account.as_ref().unwrap();
-                transfer_token_fee(
-                    payer,
-                    &destination_token_account.to_account_info(),
-                    destination_mint,
-                    destination_token_program,
-                    commission_account,
-                    commission_amount,
-                    None,
-                )?;
-                log_commission_info(false, commission_amount, 0);
-                commission_account.key().log();
-            }
-
-            if platform_fee_amount > 0 {
-                let platform_fee_account = platform_fee_account.as_ref().unwrap();
-                transfer_token_fee(
-                    payer,
-                    &destination_token_account.to_account_info(),
-                    destination_mint,
-                    destination_token_program,
-                    platform_fee_account,
-                    platform_fee_amount,
-                    None,
-                )?;
-                log_platform_fee_info(platform_fee_amount, 0, &platform_fee_account.key());
-            }
-        }
-        Ok(())
+        .pay_fees(source, fees, commission_direction)
     }
 }
 
-impl<'info> PlatformFeeV3Processor<'info> for SwapToCProcessor {
+/// ToC processor, the fees are taken from the payer and paid out by the payout
+pub struct SwapToCProcessor<P = FeeAccountsPayout>(pub P);
+
+impl<'info, P: ToCFeePayout<'info>> PlatformFeeV3Processor<'info> for SwapToCProcessor<P> {
     fn get_swap_accounts(
         &self,
         payer: &AccountInfo<'info>,
@@ -300,9 +109,10 @@ impl<'info> PlatformFeeV3Processor<'info> for SwapToCProcessor {
         commission_account: &Option<AccountInfo<'info>>,
         platform_fee_rate: Option<u16>,
         platform_fee_account: &Option<AccountInfo<'info>>,
+        transfer_ctx: &TransferContext<'_, 'info>,
     ) -> Result<u64> {
-        // Proxy handle before swap
-        ProxySwapProcessor.proxy_handle_before(
+        // Proxy handle before swap, the SA bears the transfer fee of the source mint
+        let real_amount_in = ProxySwapProcessor.proxy_handle_before(
             payer,
             source_token_account,
             source_token_sa,
@@ -310,29 +120,41 @@ impl<'info> PlatformFeeV3Processor<'info> for SwapToCProcessor {
             source_token_program,
             amount_in,
             None,
+            transfer_ctx,
         )?;
 
         // Calculate fees if commission is applied to from
-        let (commission_amount, platform_fee_amount, is_charge_fee) = self.calculate_from_fees(
-            amount_in,
-            commission_rate,
-            commission_direction,
-            platform_fee_rate,
-        )?;
+        let policy = FeePolicy::v3(commission_rate, commission_direction, platform_fee_rate);
+        let fees = quote_from_fees(&policy, amount_in)?;
+        if fees.total()? == 0 {
+            return Ok(real_amount_in);
+        }
 
-        // Transfer from fees and log results
-        self.transfer_from_fees_and_log(
-            payer,
-            source_token_account,
-            source_mint,
-            source_token_program,
-            commission_amount,
-            platform_fee_amount,
+        // Pay the fees and log results
+        let is_charge_sol =
+            self.0.is_charge_sol(commission_account, platform_fee_account, source_mint);
+        if !is_charge_sol {
+            require!(source_token_program.is_some(), ErrorCode::SourceTokenProgramIsNone);
+        }
+        let token_account = source_token_account.to_account_info();
+        let source = FeeSource {
+            authority: payer,
+            token_account: Some(&token_account),
+            token_mint: source_mint,
+            token_program: source_token_program.as_deref(),
+            signer_seeds: None,
+            is_charge_sol,
+            legacy_transfer: false,
+            transfer_ctx: *transfer_ctx,
+        };
+        self.0.pay_fees(
+            &source,
+            &fees,
             commission_account,
             platform_fee_account,
-            is_charge_fee,
+            commission_direction,
         )?;
-        Ok(amount_in)
+        Ok(real_amount_in)
     }
 
     fn after_swap(
@@ -352,12 +174,15 @@ impl<'info> PlatformFeeV3Processor<'info> for SwapToCProcessor {
         platform_fee_account: &Option<AccountInfo<'info>>,
         _trim_rate: Option<u8>,
         _charge_rate: Option<u16>,
+        _user_share_rate: Option<u16>,
         _trim_account: Option<&AccountInfo<'info>>,
         _charge_account: Option<&AccountInfo<'info>>,
         _acc_close_flag: bool,
+        transfer_ctx: &TransferContext<'_, 'info>,
     ) -> Result<u64> {
-        // Proxy handle after swap
-        ProxySwapProcessor.proxy_handle_after(
+        // Proxy handle after swap, the fees are taken from what the user received net of the
+        // transfer fee of the destination mint
+        let received_amount = ProxySwapProcessor.proxy_handle_after(
             sa_authority,
             destination_token_account,
             destination_mint,
@@ -365,28 +190,52 @@ impl<'info> PlatformFeeV3Processor<'info> for SwapToCProcessor {
             destination_token_program,
             amount_out,
             Some(SA_AUTHORITY_SEED),
+            transfer_ctx,
         )?;
 
         // Calculate fees and actual amount out if commission is applied to to
-        let (commission_amount, platform_fee_amount, actual_amount_out, is_charge_fee) = self
-            .calculate_to_fees(
-                amount_out,
-                commission_rate,
-                commission_direction,
-                platform_fee_rate,
+        let policy = FeePolicy::v3(commission_rate, commission_direction, platform_fee_rate);
+        let (fees, _, actual_amount_out) = quote_to_fees(&policy, received_amount, 0)?;
+        if fees.total()? == 0 {
+            return Ok(actual_amount_out);
+        }
+        require!(destination_token_program.is_some(), ErrorCode::DestinationTokenProgramIsNone);
+
+        let is_charge_sol =
+            self.0.is_charge_sol(commission_account, platform_fee_account, destination_mint);
+        if is_charge_sol {
+            // Close temp wsol token account
+            require!(
+                destination_token_account.owner == payer.key(),
+                ErrorCode::InvalidDestinationTokenAccount
+            );
+            close_token_account(
+                destination_token_account.to_account_info(),
+                payer.to_account_info(),
+                payer.to_account_info(),
+                destination_token_program.as_ref().unwrap().to_account_info(),
+                None,
             )?;
+        }
 
-        // Transfer to fees and log results
-        self.transfer_to_fees_and_log(
-            payer,
-            destination_token_account,
-            destination_mint,
-            destination_token_program,
-            commission_amount,
-            platform_fee_amount,
+        // Pay the fees and log results
+        let token_account = destination_token_account.to_account_info();
+        let source = FeeSource {
+            authority: payer,
+            token_account: Some(&token_account),
+            token_mint: destination_mint,
+            token_program: destination_token_program.as_deref(),
+            signer_seeds: None,
+            is_charge_sol,
+            legacy_transfer: false,
+            transfer_ctx: *transfer_ctx,
+        };
+        self.0.pay_fees(
+            &source,
+            &fees,
             commission_account,
             platform_fee_account,
-            is_charge_fee,
+            commission_direction,
         )?;
 
         Ok(actual_amount_out)
@@ -0,0 +1,51 @@
+use crate::processor::swap_toc_processor::ToCFeePayout;
+use crate::utils::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+/// Pays the commission split across multiple recipients, e.g. wallet, referrer and
+/// sub-referrer, and the platform fee to the platform fee account.
+pub struct CommissionSplitsPayout<'info> {
+    /// The commission recipients, in the same order as `commission_rates`
+    pub recipients: &'info [AccountInfo<'info>],
+    pub commission_rates: Vec<u32>,
+}
+
+impl<'info> ToCFeePayout<'info> for CommissionSplitsPayout<'info> {
+    fn is_charge_sol(
+        &self,
+        _commission_account: &Option<AccountInfo<'info>>,
+        platform_fee_account: &Option<AccountInfo<'info>>,
+        token_mint: &InterfaceAccount<'info, Mint>,
+    ) -> bool {
+        is_charge_sol(&None, platform_fee_account, token_mint)
+            || self
+                .recipients
+                .iter()
+                .any(|recipient| is_charge_sol(&Some(recipient.clone()), &None, token_mint))
+    }
+
+    fn pay_fees(
+        &self,
+        source: &FeeSource<'_, 'info>,
+        fees: &FeeAmounts,
+        _commission_account: &Option<AccountInfo<'info>>,
+        platform_fee_account: &Option<AccountInfo<'info>>,
+        commission_direction: bool,
+    ) -> Result<()> {
+        // One aggregated commission per recipient
+        let commission_amounts =
+            split_commission_amount(fees.commission_amount, &self.commission_rates)?;
+        for (recipient, commission_amount) in self.recipients.iter().zip(commission_amounts) {
+            if commission_amount == 0 {
+                continue;
+            }
+            let adjust_amount = source.pay(recipient, commission_amount)?;
+            log_commission_info(commission_direction, commission_amount, adjust_amount);
+            recipient.key().log();
+        }
+
+        FeeRecipients { platform_fee_account: platform_fee_account.as_ref(), ..Default::default() }
+            .pay_fees(source, &FeeAmounts { commission_amount: 0, ..*fees }, commission_direction)
+    }
+}
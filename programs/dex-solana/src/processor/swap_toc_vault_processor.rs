@@ -0,0 +1,86 @@
+use crate::error::ErrorCode;
+use crate::processor::swap_toc_processor::ToCFeePayout;
+use crate::state::fee_vault::FeeAccrual;
+use crate::utils::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+/// Deposits the fees into the mint's fee vault in one transfer, and credits the commission and
+/// platform fee accrual accounts.
+pub struct FeeVaultPayout<'info> {
+    pub fee_vault: AccountInfo<'info>,
+}
+
+impl<'info> ToCFeePayout<'info> for FeeVaultPayout<'info> {
+    /// The fee vault holds wSOL as tokens
+    fn is_charge_sol(
+        &self,
+        _commission_account: &Option<AccountInfo<'info>>,
+        _platform_fee_account: &Option<AccountInfo<'info>>,
+        _token_mint: &InterfaceAccount<'info, Mint>,
+    ) -> bool {
+        false
+    }
+
+    fn pay_fees(
+        &self,
+        source: &FeeSource<'_, 'info>,
+        fees: &FeeAmounts,
+        commission_account: &Option<AccountInfo<'info>>,
+        platform_fee_account: &Option<AccountInfo<'info>>,
+        commission_direction: bool,
+    ) -> Result<()> {
+        let token_mint = source.token_mint;
+        let FeeAmounts { commission_amount, platform_fee_amount } = *fees;
+        let total_fee_amount = fees.total()?;
+        if commission_amount > 0 {
+            require!(commission_account.is_some(), ErrorCode::CommissionAccountIsNone);
+        }
+        if platform_fee_amount > 0 {
+            require!(platform_fee_account.is_some(), ErrorCode::PlatformFeeAccountIsNone);
+        }
+        let token_program = source.token_program.ok_or(ErrorCode::InvalidTokenProgram)?;
+        let token_account = source.token_account.ok_or(ErrorCode::InvalidTokenAccount)?;
+
+        transfer_token_routed(
+            source.authority.to_account_info(),
+            token_account.to_account_info(),
+            self.fee_vault.clone(),
+            token_mint.to_account_info(),
+            token_program.to_account_info(),
+            total_fee_amount,
+            token_mint.decimals,
+            source.signer_seeds,
+            &source.transfer_ctx,
+        )?;
+
+        // Credit what the vault received, the transfer fee is borne pro rata
+        let received_amount = total_fee_amount
+            .checked_sub(get_transfer_fee(&token_mint.to_account_info(), total_fee_amount)?)
+            .ok_or(ErrorCode::CalculationError)?;
+        let platform_fee_credit = u64::try_from(
+            u128::from(received_amount)
+                .checked_mul(platform_fee_amount as u128)
+                .ok_or(ErrorCode::CalculationError)?
+                .checked_div(total_fee_amount as u128)
+                .ok_or(ErrorCode::CalculationError)?,
+        )
+        .unwrap();
+        let commission_credit =
+            received_amount.checked_sub(platform_fee_credit).ok_or(ErrorCode::CalculationError)?;
+
+        let mint = token_mint.key();
+        if commission_amount > 0 {
+            let commission_account = commission_account.as_ref().unwrap();
+            FeeAccrual::accrue_to(commission_account, &mint, commission_credit)?;
+            log_commission_info(commission_direction, commission_credit, 0);
+            commission_account.key().log();
+        }
+        if platform_fee_amount > 0 {
+            let platform_fee_account = platform_fee_account.as_ref().unwrap();
+            FeeAccrual::accrue_to(platform_fee_account, &mint, platform_fee_credit)?;
+            log_platform_fee_info(platform_fee_credit, 0, &platform_fee_account.key());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,78 @@
+use crate::error::ErrorCode;
+use anchor_lang::{Discriminator, prelude::*};
+
+/// The claims of a receiver for a mint, the default pubkey mint for the SOL of the SA.
+#[account(zero_copy(unsafe))]
+#[derive(Debug)]
+pub struct ClaimLedger {
+    /// Bump to identify PDA.
+    pub bump: u8,
+
+    /// The receiver of the claims.
+    pub receiver: Pubkey,
+
+    /// The claimed mint.
+    pub mint: Pubkey,
+
+    /// The amount claimed in total.
+    pub claimed_amount: u64,
+
+    /// The number of claims.
+    pub claim_count: u64,
+
+    /// The unix timestamp of the last claim.
+    pub last_claim_ts: i64,
+
+    /// padding for upgrade
+    pub padding: [u8; 64],
+}
+
+impl Default for ClaimLedger {
+    fn default() -> Self {
+        ClaimLedger {
+            bump: 0,
+            receiver: Pubkey::default(),
+            mint: Pubkey::default(),
+            claimed_amount: 0,
+            claim_count: 0,
+            last_claim_ts: 0,
+            padding: [0u8; 64],
+        }
+    }
+}
+
+impl ClaimLedger {
+    pub const LEN: usize = 8 + std::mem::size_of::<ClaimLedger>();
+
+    pub fn record(&mut self, amount: u64, now: i64) -> Result<()> {
+        self.claimed_amount =
+            self.claimed_amount.checked_add(amount).ok_or(ErrorCode::CalculationError)?;
+        self.claim_count = self.claim_count.checked_add(1).ok_or(ErrorCode::CalculationError)?;
+        self.last_claim_ts = now;
+        Ok(())
+    }
+
+    /// Records the claim to a ledger account passed as a raw account during a claim.
+    pub fn record_to(
+        claim_ledger: &AccountInfo,
+        receiver: &Pubkey,
+        mint: &Pubkey,
+        amount: u64,
+    ) -> Result<()> {
+        require!(
+            claim_ledger.owner == &crate::ID && claim_ledger.is_writable,
+            ErrorCode::InvalidClaimLedger
+        );
+        let mut data = claim_ledger.try_borrow_mut_data()?;
+        require!(
+            data.len() >= Self::LEN && data.starts_with(Self::DISCRIMINATOR),
+            ErrorCode::InvalidClaimLedger
+        );
+        let ledger: &mut ClaimLedger = bytemuck::from_bytes_mut(&mut data[8..Self::LEN]);
+        require!(
+            ledger.receiver == *receiver && ledger.mint == *mint,
+            ErrorCode::InvalidClaimLedger
+        );
+        ledger.record(amount, Clock::get()?.unix_timestamp)
+    }
+}
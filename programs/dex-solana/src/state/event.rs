@@ -1,3 +1,4 @@
+use crate::constants::{MAX_FEE_RECIPIENTS, MAX_FEE_TIERS};
 use anchor_lang::prelude::*;
 
 #[event]
@@ -36,6 +37,111 @@ pub struct SetFeeMultiplierEvent {
     pub fee_multiplier: u8,
 }
 
+// ******************** Integrator ******************** //
+
+#[event]
+pub struct UpdateIntegratorEvent {
+    pub integrator_id: u64,
+    pub enabled: bool,
+    pub max_commission_rate: u32,
+    pub platform_fee_share: u16,
+    pub fee_recipients: [Pubkey; MAX_FEE_RECIPIENTS],
+    pub platform_fee_recipient: Pubkey,
+    pub max_trim_rate: u8,
+}
+
+#[event]
+pub struct UpdateFeeTiersEvent {
+    pub integrator_id: u64,
+    pub reference_mint: Pubkey,
+    pub min_volumes: [u64; MAX_FEE_TIERS],
+    pub commission_rates: [u32; MAX_FEE_TIERS],
+}
+
+// ******************** Mint Policy ******************** //
+
+#[event]
+pub struct UpdateRouterConfigEvent {
+    pub admin: Pubkey,
+    pub mint_risk_action: u8,
+    pub max_transfer_fee_bps: u16,
+    pub integrator_required: bool,
+}
+
+#[event]
+pub struct MintRiskEvent {
+    pub mint: Pubkey,
+    /// MINT_RISK_* flags
+    pub risks: u8,
+    pub rejected: bool,
+}
+
+// ******************** Claim ******************** //
+
+#[event]
+pub struct AddClaimReceiverEvent {
+    pub receiver: Pubkey,
+}
+
+#[event]
+pub struct RemoveClaimReceiverEvent {
+    pub receiver: Pubkey,
+}
+
+#[event]
+pub struct SetClaimerEvent {
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub max_amount: u64,
+}
+
+#[event]
+pub struct RemoveClaimerEvent {
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct ClaimEvent {
+    pub claimer: Pubkey,
+    pub receiver: Pubkey,
+    /// The default pubkey for the SOL of the SA
+    pub mint: Pubkey,
+    pub source: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub source_change: u64,
+    pub destination_change: u64,
+}
+
+// ******************** Fee Vault ******************** //
+
+#[event]
+pub struct WithdrawFeesEvent {
+    pub recipient: Pubkey,
+    pub mint: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+}
+
+// ******************** Transfer Fee ******************** //
+
+#[event]
+pub struct TransferFeeEvent {
+    pub mint: Pubkey,
+    pub account: Pubkey,
+    pub gross_amount: u64,
+    pub net_amount: u64,
+}
+
+// ******************** Trim ******************** //
+
+#[event]
+pub struct TrimEvent {
+    pub trim_amount: u64,
+    pub charge_amount: u64,
+    pub user_share_amount: u64,
+}
+
 // ******************** Limit Order V1 ******************** //
 
 #[event]
@@ -50,6 +156,20 @@ pub struct PlaceOrderEvent {
     pub create_ts: u64,
     pub deadline: u64,
     pub trade_fee: u64,
+    pub receiver: Pubkey,
+    pub commission_info: u32,
+    pub commission_token_account: Pubkey,
+    pub max_fill_fee: u64,
+    pub flags: u8,
+    pub start_ts: u64,
+}
+
+#[event]
+pub struct MigrateOrderEvent {
+    pub order_id: u64,
+    pub payer: Pubkey,
+    pub maker: Pubkey,
+    pub version: u8,
 }
 
 #[event]
@@ -89,4 +209,32 @@ pub struct FillOrderEvent {
     pub making_amount: u64,
     pub taking_amount: u64,
     pub update_ts: u64,
+    pub receiver: Pubkey,
+    pub remaining_amount: u64,
+}
+
+#[event]
+pub struct FeeBreakdownEvent {
+    pub order_id: u64,
+    pub payer: Pubkey,
+    pub priority_fee: u64,
+    pub tips: u64,
+    pub fee_multiplier: u8,
+    pub total_fee: u64,
+    pub max_fill_fee: u64,
+    pub charged_fee: u64,
+}
+
+#[event]
+pub struct MatchOrdersEvent {
+    pub order_id_a: u64,
+    pub order_id_b: u64,
+    pub payer: Pubkey,
+    pub maker_a: Pubkey,
+    pub maker_b: Pubkey,
+    pub amount_a_to_b: u64,
+    pub amount_b_to_a: u64,
+    pub refund_a: u64,
+    pub refund_b: u64,
+    pub update_ts: u64,
 }
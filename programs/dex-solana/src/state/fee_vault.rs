@@ -0,0 +1,76 @@
+use crate::error::ErrorCode;
+use anchor_lang::{Discriminator, prelude::*};
+
+/// Fees accrued by a recipient for a mint, the tokens are held by the mint's fee vault.
+#[account(zero_copy(unsafe))]
+#[derive(Debug)]
+pub struct FeeAccrual {
+    /// Bump to identify PDA.
+    pub bump: u8,
+
+    /// The fee recipient allowed to withdraw.
+    pub recipient: Pubkey,
+
+    /// The fee token mint.
+    pub mint: Pubkey,
+
+    /// The fees accrued and not withdrawn yet.
+    pub accrued_amount: u64,
+
+    /// The fees withdrawn in total.
+    pub withdrawn_amount: u64,
+
+    /// padding for upgrade
+    pub padding: [u8; 64],
+}
+
+impl Default for FeeAccrual {
+    fn default() -> Self {
+        FeeAccrual {
+            bump: 0,
+            recipient: Pubkey::default(),
+            mint: Pubkey::default(),
+            accrued_amount: 0,
+            withdrawn_amount: 0,
+            padding: [0u8; 64],
+        }
+    }
+}
+
+impl FeeAccrual {
+    pub const LEN: usize = 8 + std::mem::size_of::<FeeAccrual>();
+
+    pub fn accrue(&mut self, amount: u64) -> Result<()> {
+        self.accrued_amount =
+            self.accrued_amount.checked_add(amount).ok_or(ErrorCode::CalculationError)?;
+        Ok(())
+    }
+
+    /// Credits the fee to an accrual account passed as a raw account during a swap.
+    pub fn accrue_to(fee_accrual: &AccountInfo, mint: &Pubkey, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        require!(
+            fee_accrual.owner == &crate::ID && fee_accrual.is_writable,
+            ErrorCode::InvalidFeeAccrual
+        );
+        let mut data = fee_accrual.try_borrow_mut_data()?;
+        require!(
+            data.len() >= Self::LEN && data.starts_with(Self::DISCRIMINATOR),
+            ErrorCode::InvalidFeeAccrual
+        );
+        let accrual: &mut FeeAccrual = bytemuck::from_bytes_mut(&mut data[8..Self::LEN]);
+        require_keys_eq!(accrual.mint, *mint, ErrorCode::InvalidFeeAccrual);
+        accrual.accrue(amount)
+    }
+
+    pub fn withdraw(&mut self) -> Result<u64> {
+        let amount = self.accrued_amount;
+        require!(amount > 0, ErrorCode::NoFeesToWithdraw);
+        self.accrued_amount = 0;
+        self.withdrawn_amount =
+            self.withdrawn_amount.checked_add(amount).ok_or(ErrorCode::CalculationError)?;
+        Ok(amount)
+    }
+}
@@ -0,0 +1,276 @@
+use crate::constants::*;
+use crate::error::ErrorCode;
+use crate::state::fee_vault::FeeAccrual;
+use crate::utils::fee_policy::FeePolicy;
+use anchor_lang::{Discriminator, prelude::*};
+use anchor_spl::{token::Token, token_2022::Token2022};
+
+#[account(zero_copy(unsafe))]
+#[derive(Debug)]
+pub struct Integrator {
+    /// Bump to identify PDA.
+    pub bump: u8,
+
+    /// The integrator id.
+    pub integrator_id: u64,
+
+    /// Indicate whether the integrator can be used for swaps.
+    pub enabled: bool,
+
+    /// The max commission rate, denominated in COMMISSION_DENOMINATOR_V2.
+    pub max_commission_rate: u32,
+
+    /// The platform fee share of the commission, denominated in PLATFORM_FEE_DENOMINATOR_V3.
+    pub platform_fee_share: u16,
+
+    /// The accounts allowed to receive commission, trim and charge, either a wallet or its token
+    /// accounts.
+    pub fee_recipients: [Pubkey; MAX_FEE_RECIPIENTS],
+
+    /// The mint the user volume is accumulated in.
+    pub reference_mint: Pubkey,
+
+    /// The volume tiers, ascending by min_volume, a zero min_volume marks an unused tier.
+    pub fee_tiers: [FeeTier; MAX_FEE_TIERS],
+
+    /// The account receiving the platform fee share, either a wallet or its token accounts.
+    pub platform_fee_recipient: Pubkey,
+
+    /// The max trim rate, denominated in TRIM_DENOMINATOR_V2.
+    pub max_trim_rate: u8,
+
+    /// padding for upgrade
+    pub padding: [u8; 15],
+}
+
+#[zero_copy(unsafe)]
+#[derive(Debug, Default)]
+pub struct FeeTier {
+    /// The user volume in the reference mint from which the tier applies.
+    pub min_volume: u64,
+
+    /// The commission rate of the tier, denominated in COMMISSION_DENOMINATOR_V2.
+    pub commission_rate: u32,
+}
+
+impl Default for Integrator {
+    fn default() -> Self {
+        Integrator {
+            bump: 0,
+            integrator_id: 0,
+            enabled: false,
+            max_commission_rate: 0,
+            platform_fee_share: 0,
+            fee_recipients: [Pubkey::default(); MAX_FEE_RECIPIENTS],
+            reference_mint: Pubkey::default(),
+            fee_tiers: [FeeTier::default(); MAX_FEE_TIERS],
+            platform_fee_recipient: Pubkey::default(),
+            max_trim_rate: 0,
+            padding: [0u8; 15],
+        }
+    }
+}
+
+impl Integrator {
+    pub const LEN: usize = 8 + std::mem::size_of::<Integrator>();
+
+    pub fn set_max_commission_rate(&mut self, max_commission_rate: u32) -> Result<()> {
+        require!(max_commission_rate <= COMMISSION_RATE_LIMIT_V2, ErrorCode::InvalidCommissionRate);
+        self.max_commission_rate = max_commission_rate;
+        Ok(())
+    }
+
+    pub fn set_platform_fee_share(&mut self, platform_fee_share: u16) -> Result<()> {
+        require!(
+            platform_fee_share as u64 <= PLATFORM_FEE_RATE_LIMIT_V3,
+            ErrorCode::InvalidPlatformFeeRate
+        );
+        self.platform_fee_share = platform_fee_share;
+        Ok(())
+    }
+
+    pub fn set_platform_fee_recipient(&mut self, platform_fee_recipient: Pubkey) -> Result<()> {
+        self.platform_fee_recipient = platform_fee_recipient;
+        Ok(())
+    }
+
+    pub fn set_max_trim_rate(&mut self, max_trim_rate: u8) -> Result<()> {
+        require!(max_trim_rate <= TRIM_RATE_LIMIT_V2, ErrorCode::InvalidTrimRate);
+        self.max_trim_rate = max_trim_rate;
+        Ok(())
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) -> Result<()> {
+        self.enabled = enabled;
+        Ok(())
+    }
+
+    pub fn add_fee_recipient(&mut self, recipient: Pubkey) -> Result<()> {
+        require_keys_neq!(recipient, Pubkey::default(), ErrorCode::InvalidIntegrator);
+        for item in self.fee_recipients {
+            require_keys_neq!(recipient, item, ErrorCode::FeeRecipientIsExist);
+        }
+        for item in &mut self.fee_recipients {
+            if *item == Pubkey::default() {
+                *item = recipient;
+                return Ok(());
+            }
+        }
+        Err(ErrorCode::ExceedFeeRecipientLimit.into())
+    }
+
+    pub fn remove_fee_recipient(&mut self, recipient: Pubkey) -> Result<()> {
+        require_keys_neq!(recipient, Pubkey::default(), ErrorCode::InvalidIntegrator);
+        for item in &mut self.fee_recipients {
+            if *item == recipient {
+                *item = Pubkey::default();
+                return Ok(());
+            }
+        }
+        Err(ErrorCode::FeeRecipientIsNotExist.into())
+    }
+
+    pub fn set_reference_mint(&mut self, reference_mint: Pubkey) -> Result<()> {
+        self.reference_mint = reference_mint;
+        Ok(())
+    }
+
+    pub fn set_fee_tier(&mut self, index: u8, min_volume: u64, commission_rate: u32) -> Result<()> {
+        require!((index as usize) < MAX_FEE_TIERS, ErrorCode::InvalidFeeTier);
+        require!(commission_rate <= COMMISSION_RATE_LIMIT_V2, ErrorCode::InvalidCommissionRate);
+        self.fee_tiers[index as usize] = FeeTier { min_volume, commission_rate };
+
+        // Used tiers must ascend by min_volume and descend by commission rate
+        let mut prev: Option<&FeeTier> = None;
+        for tier in self.fee_tiers.iter().filter(|tier| tier.min_volume > 0) {
+            if let Some(prev) = prev {
+                require!(
+                    tier.min_volume > prev.min_volume
+                        && tier.commission_rate <= prev.commission_rate,
+                    ErrorCode::InvalidFeeTier
+                );
+            }
+            prev = Some(tier);
+        }
+        Ok(())
+    }
+
+    /// The commission rate of the highest tier reached by the volume, capped by commission_rate.
+    pub fn tier_commission_rate(&self, volume: u64, commission_rate: u32) -> u32 {
+        self.fee_tiers
+            .iter()
+            .filter(|tier| tier.min_volume > 0 && tier.min_volume <= volume)
+            .map(|tier| tier.commission_rate)
+            .min()
+            .map_or(commission_rate, |tier_rate| tier_rate.min(commission_rate))
+    }
+
+    pub fn is_fee_recipient(&self, recipient: Pubkey) -> bool {
+        recipient != Pubkey::default() && self.fee_recipients.contains(&recipient)
+    }
+
+    pub fn is_platform_fee_recipient(&self, recipient: Pubkey) -> bool {
+        recipient != Pubkey::default() && recipient == self.platform_fee_recipient
+    }
+
+    /// Checks the fee recipient account itself, the owner if it is a token account, or the
+    /// recipient if it is a fee accrual account.
+    pub fn is_fee_recipient_account(&self, account: &AccountInfo) -> Result<bool> {
+        is_recipient_account(account, |recipient| self.is_fee_recipient(recipient))
+    }
+
+    /// Checks the platform fee recipient account itself, the owner if it is a token account, or
+    /// the recipient if it is a fee accrual account.
+    pub fn is_platform_fee_recipient_account(&self, account: &AccountInfo) -> Result<bool> {
+        is_recipient_account(account, |recipient| self.is_platform_fee_recipient(recipient))
+    }
+
+    /// Validates the swap fees against the integrator agreement, every account receiving a fee
+    /// must be registered.
+    pub fn validate_swap(
+        &self,
+        policy: &FeePolicy,
+        commission_account: Option<&AccountInfo>,
+        platform_fee_account: Option<&AccountInfo>,
+        trim_account: Option<&AccountInfo>,
+        charge_account: Option<&AccountInfo>,
+    ) -> Result<()> {
+        require!(self.enabled, ErrorCode::IntegratorDisabled);
+        require!(
+            policy.commission_rate <= self.max_commission_rate,
+            ErrorCode::CommissionRateExceedsIntegratorLimit
+        );
+        if policy.commission_rate > 0 {
+            let commission_account =
+                commission_account.ok_or(ErrorCode::CommissionAccountIsNone)?;
+            require!(
+                self.is_fee_recipient_account(commission_account)?,
+                ErrorCode::FeeRecipientNotAllowed
+            );
+
+            // The platform takes exactly its agreed share of the commission
+            require!(
+                policy.platform_fee_share()? == self.platform_fee_share as u64,
+                ErrorCode::PlatformFeeShareMismatch
+            );
+            if self.platform_fee_share > 0 {
+                let platform_fee_account =
+                    platform_fee_account.ok_or(ErrorCode::PlatformFeeAccountIsNone)?;
+                require!(
+                    self.is_platform_fee_recipient_account(platform_fee_account)?,
+                    ErrorCode::FeeRecipientNotAllowed
+                );
+            }
+        }
+        if policy.trim_rate > 0 {
+            require!(
+                policy.trim_rate <= self.max_trim_rate,
+                ErrorCode::TrimRateExceedsIntegratorLimit
+            );
+            let trim_account = trim_account.ok_or(ErrorCode::TrimAccountIsNone)?;
+            require!(
+                self.is_fee_recipient_account(trim_account)?,
+                ErrorCode::FeeRecipientNotAllowed
+            );
+
+            // The charge is a share of the trim, paid to the integrator or the platform
+            if policy.charge_rate > 0 {
+                let charge_account = charge_account.ok_or(ErrorCode::ChargeAccountIsNone)?;
+                require!(
+                    self.is_fee_recipient_account(charge_account)?
+                        || self.is_platform_fee_recipient_account(charge_account)?,
+                    ErrorCode::FeeRecipientNotAllowed
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Checks the account itself, the owner if it is a token account, or the recipient if it is a fee
+/// accrual account.
+fn is_recipient_account(
+    account: &AccountInfo,
+    is_recipient: impl Fn(Pubkey) -> bool,
+) -> Result<bool> {
+    if is_recipient(account.key()) {
+        return Ok(true);
+    }
+    if account.owner == &crate::ID {
+        // A fee accrual account credits its recipient
+        let data = account.try_borrow_data()?;
+        if data.len() < FeeAccrual::LEN || !data.starts_with(FeeAccrual::DISCRIMINATOR) {
+            return Ok(false);
+        }
+        let accrual: &FeeAccrual = bytemuck::from_bytes(&data[8..FeeAccrual::LEN]);
+        return Ok(is_recipient(accrual.recipient));
+    }
+    if account.owner != &Token::id() && account.owner != &Token2022::id() {
+        return Ok(false);
+    }
+    let data = account.try_borrow_data()?;
+    if data.len() < 64 {
+        return Ok(false);
+    }
+    Ok(is_recipient(Pubkey::try_from(&data[32..64]).unwrap()))
+}
@@ -1,3 +1,8 @@
+pub mod claim_ledger;
 pub mod config;
 pub mod event;
+pub mod fee_vault;
+pub mod integrator;
 pub mod order;
+pub mod router_config;
+pub mod user_volume;
@@ -1,4 +1,7 @@
-use anchor_lang::prelude::*;
+use crate::constants::ORDER_V1_SEED;
+use crate::error::LimitOrderError;
+use crate::utils::transfer_sol;
+use anchor_lang::{Discriminator, prelude::*};
 
 #[account]
 #[derive(Debug)]
@@ -42,8 +45,26 @@ pub struct OrderV1 {
     /// The deadline of the order.
     pub deadline: u64,
 
+    /// The receiver of the output token, default means the maker.
+    pub receiver: Pubkey,
+
+    /// Whether the commission terms below were fixed by the maker at placement.
+    pub commission_locked: bool,
+
+    /// The commission direction, true: from input token, false: from output token.
+    pub commission_direction: bool,
+
+    /// The commission rate, denominated in COMMISSION_DENOMINATOR_V2.
+    pub commission_rate: u32,
+
+    /// The commission token account.
+    pub commission_token_account: Pubkey,
+
+    /// The max fee in lamports a resolver can charge per fill or cancel, 0 means no cap.
+    pub max_fill_fee: u64,
+
     /// padding
-    pub padding: [u8; 128],
+    pub padding: [u8; 50],
 }
 
 impl Default for OrderV1 {
@@ -62,7 +83,13 @@ impl Default for OrderV1 {
             output_token_program: Pubkey::default(),
             create_ts: 0,
             deadline: 0,
-            padding: [0u8; 128],
+            receiver: Pubkey::default(),
+            commission_locked: false,
+            commission_direction: false,
+            commission_rate: 0,
+            commission_token_account: Pubkey::default(),
+            max_fill_fee: 0,
+            padding: [0u8; 50],
         }
     }
 }
@@ -70,3 +97,281 @@ impl Default for OrderV1 {
 impl OrderV1 {
     pub const LEN: usize = 8 + std::mem::size_of::<OrderV1>();
 }
+
+pub const ORDER_FLAG_PARTIAL_FILL: u8 = 1 << 0;
+pub const ORDER_FLAG_POST_ONLY: u8 = 1 << 1;
+pub const ORDER_FLAGS_MASK: u8 = ORDER_FLAG_PARTIAL_FILL | ORDER_FLAG_POST_ONLY;
+
+/// Order account format V2, lives at the same PDA as V1 so that migrated orders keep their address.
+#[account]
+#[derive(Debug)]
+pub struct OrderV2 {
+    /// Bump to identify PDA.
+    pub bump: u8,
+
+    /// The version of the order account format.
+    pub version: u8,
+
+    /// The order flags, see `ORDER_FLAG_*`.
+    pub flags: u8,
+
+    /// The order id.
+    pub order_id: u64,
+
+    /// The maker of the order.
+    pub maker: Pubkey,
+
+    /// The makeing amount of the order.
+    pub making_amount: u64,
+
+    /// The expect taking amount of the remaining order.
+    pub expect_taking_amount: u64,
+
+    /// The min return amount of the remaining order.
+    pub min_return_amount: u64,
+
+    /// The escrow token account of the order.
+    pub escrow_token_account: Pubkey,
+
+    /// Input token mint.
+    pub input_token_mint: Pubkey,
+
+    /// Output token mint.
+    pub output_token_mint: Pubkey,
+
+    /// Input token program.
+    pub input_token_program: Pubkey,
+
+    /// Output token program.
+    pub output_token_program: Pubkey,
+
+    /// The create timestamp of the order.
+    pub create_ts: u64,
+
+    /// The timestamp from which the order can be filled.
+    pub start_ts: u64,
+
+    /// The deadline of the order.
+    pub deadline: u64,
+
+    /// The receiver of the output token, default means the maker.
+    pub receiver: Pubkey,
+
+    /// Whether the commission terms below were fixed by the maker at placement.
+    pub commission_locked: bool,
+
+    /// The commission direction, true: from input token, false: from output token.
+    pub commission_direction: bool,
+
+    /// The commission rate, denominated in COMMISSION_DENOMINATOR_V2.
+    pub commission_rate: u32,
+
+    /// The commission token account.
+    pub commission_token_account: Pubkey,
+
+    /// The max fee in lamports a resolver can charge per fill or cancel, 0 means no cap.
+    pub max_fill_fee: u64,
+
+    /// The filled making amount of the order.
+    pub filled_making_amount: u64,
+
+    /// The filled taking amount of the order.
+    pub filled_taking_amount: u64,
+
+    /// padding
+    pub padding: [u8; 128],
+}
+
+impl Default for OrderV2 {
+    fn default() -> Self {
+        OrderV2 {
+            bump: 0,
+            version: OrderV2::VERSION,
+            flags: 0,
+            order_id: 0,
+            maker: Pubkey::default(),
+            making_amount: 0,
+            expect_taking_amount: 0,
+            min_return_amount: 0,
+            escrow_token_account: Pubkey::default(),
+            input_token_mint: Pubkey::default(),
+            output_token_mint: Pubkey::default(),
+            input_token_program: Pubkey::default(),
+            output_token_program: Pubkey::default(),
+            create_ts: 0,
+            start_ts: 0,
+            deadline: 0,
+            receiver: Pubkey::default(),
+            commission_locked: false,
+            commission_direction: false,
+            commission_rate: 0,
+            commission_token_account: Pubkey::default(),
+            max_fill_fee: 0,
+            filled_making_amount: 0,
+            filled_taking_amount: 0,
+            padding: [0u8; 128],
+        }
+    }
+}
+
+impl From<OrderV1> for OrderV2 {
+    fn from(order: OrderV1) -> Self {
+        OrderV2 {
+            bump: order.bump,
+            order_id: order.order_id,
+            maker: order.maker,
+            making_amount: order.making_amount,
+            expect_taking_amount: order.expect_taking_amount,
+            min_return_amount: order.min_return_amount,
+            escrow_token_account: order.escrow_token_account,
+            input_token_mint: order.input_token_mint,
+            output_token_mint: order.output_token_mint,
+            input_token_program: order.input_token_program,
+            output_token_program: order.output_token_program,
+            create_ts: order.create_ts,
+            start_ts: order.create_ts,
+            deadline: order.deadline,
+            receiver: order.receiver,
+            commission_locked: order.commission_locked,
+            commission_direction: order.commission_direction,
+            commission_rate: order.commission_rate,
+            commission_token_account: order.commission_token_account,
+            max_fill_fee: order.max_fill_fee,
+            ..Default::default()
+        }
+    }
+}
+
+impl OrderV2 {
+    pub const LEN: usize = 8 + std::mem::size_of::<OrderV2>();
+    pub const VERSION: u8 = 2;
+
+    /// The owner of the output token, orders placed without a receiver pay the maker.
+    pub fn receiver(&self) -> Pubkey {
+        if self.receiver == Pubkey::default() { self.maker } else { self.receiver }
+    }
+
+    /// The commission info packed the same way as the `commission_info` instruction argument.
+    pub fn commission_info(&self) -> u32 {
+        ((self.commission_direction as u32) << 31) | self.commission_rate
+    }
+
+    pub fn is_partial_fill(&self) -> bool {
+        self.flags & ORDER_FLAG_PARTIAL_FILL != 0
+    }
+
+    pub fn is_post_only(&self) -> bool {
+        self.flags & ORDER_FLAG_POST_ONLY != 0
+    }
+
+    /// The (expect taking amount, min return amount) for filling `amount` out of the
+    /// `remaining_amount` in escrow, rounded up in favor of the maker.
+    pub fn fill_terms(&self, amount: u64, remaining_amount: u64) -> Option<(u64, u64)> {
+        if amount == remaining_amount {
+            return Some((self.expect_taking_amount, self.min_return_amount));
+        }
+        let pro_rata = |total: u64| -> Option<u64> {
+            let numerator = u128::from(total).checked_mul(u128::from(amount))?;
+            let denominator = u128::from(remaining_amount);
+            u64::try_from(numerator.checked_add(denominator - 1)? / denominator).ok()
+        };
+        Some((pro_rata(self.expect_taking_amount)?, pro_rata(self.min_return_amount)?))
+    }
+
+    /// Records a partial fill, the expect and min return amounts always refer to the remaining order.
+    pub fn record_fill(
+        &mut self,
+        making_amount: u64,
+        taking_amount: u64,
+        expect_taking_amount: u64,
+        min_return_amount: u64,
+    ) -> Option<()> {
+        self.filled_making_amount = self.filled_making_amount.checked_add(making_amount)?;
+        self.filled_taking_amount = self.filled_taking_amount.checked_add(taking_amount)?;
+        self.expect_taking_amount =
+            self.expect_taking_amount.saturating_sub(expect_taking_amount).max(1);
+        self.min_return_amount = self.min_return_amount.saturating_sub(min_return_amount).max(1);
+        Some(())
+    }
+}
+
+impl OrderV2 {
+    /// Reads a V1 or V2 order, checking that it lives at the PDA of its own order id and maker.
+    pub fn read(order_info: &AccountInfo) -> Result<OrderV2> {
+        require_keys_eq!(*order_info.owner, crate::ID, ErrorCode::AccountOwnedByWrongProgram);
+        let data = order_info.try_borrow_data()?;
+        let order = if data.starts_with(OrderV1::DISCRIMINATOR) {
+            OrderV2::from(OrderV1::try_deserialize(&mut &data[..])?)
+        } else {
+            OrderV2::try_deserialize(&mut &data[..])?
+        };
+        let order_pda = Pubkey::create_program_address(
+            &[
+                ORDER_V1_SEED.as_bytes(),
+                &order.order_id.to_le_bytes(),
+                order.maker.as_ref(),
+                &[order.bump],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| ErrorCode::ConstraintSeeds)?;
+        require_keys_eq!(order_pda, order_info.key(), ErrorCode::ConstraintSeeds);
+        Ok(order)
+    }
+
+    /// Reads a V1 or V2 order at the order PDA of (order_id, maker).
+    pub fn read_pda(order_info: &AccountInfo, order_id: u64, maker: &Pubkey) -> Result<OrderV2> {
+        let order = OrderV2::read(order_info)?;
+        require!(order.order_id == order_id && order.maker == *maker, ErrorCode::ConstraintSeeds);
+        Ok(order)
+    }
+
+    /// Loads an order for writing, a V1 order is migrated to V2 in place. The payer funds the
+    /// rent of the extra space, the prepaid trade fee is left untouched.
+    ///
+    /// The order is not written back by Anchor, call `write` or `close_order` on it.
+    pub fn load_mut<'a>(
+        order_info: &AccountInfo<'a>,
+        payer: &AccountInfo<'a>,
+        order_id: u64,
+        maker: &Pubkey,
+    ) -> Result<OrderV2> {
+        let order = OrderV2::read_pda(order_info, order_id, maker)?;
+        if order_info.try_borrow_data()?.starts_with(OrderV1::DISCRIMINATOR) {
+            let rent = Rent::get()?;
+            let rent_diff = rent
+                .minimum_balance(OrderV2::LEN)
+                .saturating_sub(rent.minimum_balance(order_info.data_len()));
+            if rent_diff > 0 {
+                transfer_sol(payer.clone(), order_info.clone(), rent_diff, None)?;
+            }
+            order_info.realloc(OrderV2::LEN, true)?;
+
+            // Rewrite the account with the V2 discriminator and layout
+            let mut data = order_info.try_borrow_mut_data()?;
+            order.try_serialize(&mut &mut data[..])?;
+        }
+        Ok(order)
+    }
+
+    /// Writes the order back to its account, migrated to V2 by `load_mut`.
+    pub fn write(&self, order_info: &AccountInfo) -> Result<()> {
+        let mut data = order_info.try_borrow_mut_data()?;
+        self.try_serialize(&mut &mut data[..])
+    }
+}
+
+/// Closes a V1 or V2 order account, moving its lamports to the destination.
+pub fn close_order<'a>(
+    order_info: &AccountInfo<'a>,
+    sol_destination: &AccountInfo<'a>,
+) -> Result<()> {
+    let lamports = sol_destination
+        .lamports()
+        .checked_add(order_info.lamports())
+        .ok_or(LimitOrderError::MathOverflow)?;
+    **sol_destination.lamports.borrow_mut() = lamports;
+    **order_info.lamports.borrow_mut() = 0;
+    order_info.assign(&anchor_lang::system_program::ID);
+    order_info.realloc(0, false).map_err(Into::into)
+}
@@ -0,0 +1,240 @@
+use crate::constants::*;
+use crate::error::ErrorCode;
+use crate::utils::fee_policy::FeePolicy;
+use crate::utils::mint_policy::{MintPolicy, MintRiskAction};
+use anchor_lang::prelude::*;
+
+#[account(zero_copy(unsafe))]
+#[derive(Debug)]
+pub struct RouterConfig {
+    /// Bump to identify PDA.
+    pub bump: u8,
+
+    /// The default MintRiskAction of the swaps, stricter actions can be requested per swap.
+    pub mint_risk_action: u8,
+
+    /// Transfer fees above this rate are risky, denominated in basis points.
+    pub max_transfer_fee_bps: u16,
+
+    /// Whether the v3 and v4 swaps charging fees must be validated against an integrator.
+    pub integrator_required: bool,
+
+    /// Only these receivers can be claimed to by claim_batch.
+    pub claim_receivers: [Pubkey; MAX_CLAIM_RECEIVERS],
+
+    /// The signers allowed to claim from the SA, with their limits.
+    pub claimers: [Claimer; MAX_CLAIMERS],
+
+    /// padding for upgrade
+    pub padding: [u8; 255],
+}
+
+#[zero_copy(unsafe)]
+#[derive(Debug, Default)]
+pub struct Claimer {
+    /// The claim signer, the default pubkey marks an unused slot.
+    pub authority: Pubkey,
+
+    /// The only mint the claimer can claim, the SOL of the SA counts as the native mint, the
+    /// default pubkey allows any mint and requires a max_amount of 0.
+    pub mint: Pubkey,
+
+    /// The max amount claimed per CLAIM_PERIOD_SECONDS, 0 for no limit.
+    pub max_amount: u64,
+
+    /// The amount claimed since period_start.
+    pub claimed_amount: u64,
+
+    /// The start of the current claim period, unix timestamp.
+    pub period_start: i64,
+}
+
+impl Claimer {
+    /// The legacy claim_authority, claims any mint without limit
+    pub fn legacy(authority: &Pubkey) -> Result<Claimer> {
+        require_keys_eq!(*authority, claim_authority::id(), ErrorCode::InvalidSigner);
+        Ok(Claimer { authority: *authority, ..Claimer::default() })
+    }
+
+    /// The amount claimed in the period of now, 0 once the period has elapsed
+    fn period_claimed_amount(&self, now: i64) -> u64 {
+        if now >= self.period_start.saturating_add(CLAIM_PERIOD_SECONDS) {
+            0
+        } else {
+            self.claimed_amount
+        }
+    }
+
+    /// The amount of the balance the claimer can claim for the mint, within the max_amount left
+    /// in the current period
+    pub fn claimable_amount(&self, mint: &Pubkey, balance: u64, now: i64) -> Result<u64> {
+        require!(
+            self.mint == Pubkey::default() || self.mint == *mint,
+            ErrorCode::ClaimMintNotAllowed
+        );
+        if self.max_amount == 0 {
+            return Ok(balance);
+        }
+        let left = self.max_amount.saturating_sub(self.period_claimed_amount(now));
+        require!(left > 0, ErrorCode::ClaimAmountExceedsLimit);
+        Ok(balance.min(left))
+    }
+
+    /// Adds the claimed amount to the current period, starting a new one if it has elapsed
+    pub fn record_claim(&mut self, amount: u64, now: i64) -> Result<()> {
+        if self.max_amount == 0 {
+            return Ok(());
+        }
+        let claimed_amount = self.period_claimed_amount(now);
+        if claimed_amount == 0 {
+            self.period_start = now;
+        }
+        self.claimed_amount =
+            claimed_amount.checked_add(amount).ok_or(ErrorCode::CalculationError)?;
+        require!(self.claimed_amount <= self.max_amount, ErrorCode::ClaimAmountExceedsLimit);
+        Ok(())
+    }
+}
+
+impl Default for RouterConfig {
+    fn default() -> Self {
+        RouterConfig {
+            bump: 0,
+            mint_risk_action: MintRiskAction::Allow as u8,
+            max_transfer_fee_bps: DEFAULT_MAX_TRANSFER_FEE_BPS,
+            integrator_required: false,
+            claim_receivers: [Pubkey::default(); MAX_CLAIM_RECEIVERS],
+            claimers: [Claimer::default(); MAX_CLAIMERS],
+            padding: [0u8; 255],
+        }
+    }
+}
+
+impl RouterConfig {
+    pub const LEN: usize = 8 + std::mem::size_of::<RouterConfig>();
+
+    pub fn set_mint_policy(&mut self, mint_policy: &MintPolicy) -> Result<()> {
+        mint_policy.validate()?;
+        self.mint_risk_action = mint_policy.action as u8;
+        self.max_transfer_fee_bps = mint_policy.max_transfer_fee_bps;
+        Ok(())
+    }
+
+    pub fn mint_policy(&self) -> Result<MintPolicy> {
+        Ok(MintPolicy {
+            action: MintRiskAction::try_from(self.mint_risk_action)?,
+            max_transfer_fee_bps: self.max_transfer_fee_bps,
+        })
+    }
+
+    /// The policy of a swap, merged with the configured default
+    pub fn swap_mint_policy(&self, mint_policy: Option<MintPolicy>) -> Result<MintPolicy> {
+        let default = self.mint_policy()?;
+        match mint_policy {
+            Some(mint_policy) => {
+                mint_policy.validate()?;
+                Ok(default.merge(&mint_policy))
+            }
+            None => Ok(default),
+        }
+    }
+
+    pub fn set_integrator_required(&mut self, integrator_required: bool) -> Result<()> {
+        self.integrator_required = integrator_required;
+        Ok(())
+    }
+
+    /// Validates the fees of a swap without an integrator
+    pub fn validate_swap_without_integrator(&self, policy: &FeePolicy) -> Result<()> {
+        if self.integrator_required {
+            require!(
+                policy.commission_rate == 0 && policy.trim_rate == 0,
+                ErrorCode::IntegratorRequired
+            );
+        }
+        Ok(())
+    }
+
+    pub fn add_claim_receiver(&mut self, receiver: Pubkey) -> Result<()> {
+        require_keys_neq!(receiver, Pubkey::default(), ErrorCode::ClaimReceiverNotAllowed);
+        require!(!self.is_claim_receiver(receiver), ErrorCode::ClaimReceiverIsExist);
+        let slot = self
+            .claim_receivers
+            .iter_mut()
+            .find(|item| **item == Pubkey::default())
+            .ok_or(ErrorCode::ExceedClaimReceiverLimit)?;
+        *slot = receiver;
+        Ok(())
+    }
+
+    pub fn remove_claim_receiver(&mut self, receiver: Pubkey) -> Result<()> {
+        require_keys_neq!(receiver, Pubkey::default(), ErrorCode::ClaimReceiverNotAllowed);
+        let slot = self
+            .claim_receivers
+            .iter_mut()
+            .find(|item| **item == receiver)
+            .ok_or(ErrorCode::ClaimReceiverIsNotExist)?;
+        *slot = Pubkey::default();
+        Ok(())
+    }
+
+    pub fn is_claim_receiver(&self, receiver: Pubkey) -> bool {
+        receiver != Pubkey::default() && self.claim_receivers.contains(&receiver)
+    }
+
+    /// Add the claimer or update the limits of an existing one
+    pub fn set_claimer(&mut self, authority: Pubkey, mint: Pubkey, max_amount: u64) -> Result<()> {
+        require_keys_neq!(authority, Pubkey::default(), ErrorCode::InvalidSigner);
+        // A limit adds up raw amounts, so it only holds for a single mint
+        require!(max_amount == 0 || mint != Pubkey::default(), ErrorCode::ClaimerLimitRequiresMint);
+        let slot = match self.claimers.iter().position(|item| item.authority == authority) {
+            Some(index) => index,
+            None => self
+                .claimers
+                .iter()
+                .position(|item| item.authority == Pubkey::default())
+                .ok_or(ErrorCode::ExceedClaimerLimit)?,
+        };
+        // The claimed amount of the period is kept while the claimer and its mint are unchanged
+        let claimer = &mut self.claimers[slot];
+        if claimer.authority != authority || claimer.mint != mint {
+            *claimer = Claimer { authority, mint, ..Claimer::default() };
+        }
+        claimer.max_amount = max_amount;
+        Ok(())
+    }
+
+    pub fn remove_claimer(&mut self, authority: Pubkey) -> Result<()> {
+        require_keys_neq!(authority, Pubkey::default(), ErrorCode::InvalidSigner);
+        let slot = self
+            .claimers
+            .iter_mut()
+            .find(|item| item.authority == authority)
+            .ok_or(ErrorCode::ClaimerIsNotExist)?;
+        *slot = Claimer::default();
+        Ok(())
+    }
+
+    /// The claimer of the signer, fails if the signer can not claim. Until the first claimer is
+    /// set, the legacy claim_authority keeps claiming any mint without limit.
+    pub fn claimer(&self, authority: &Pubkey) -> Result<Claimer> {
+        require_keys_neq!(*authority, Pubkey::default(), ErrorCode::InvalidSigner);
+        if let Some(claimer) = self.claimers.iter().find(|item| item.authority == *authority) {
+            return Ok(*claimer);
+        }
+        require!(
+            self.claimers.iter().all(|item| item.authority == Pubkey::default()),
+            ErrorCode::InvalidSigner
+        );
+        Claimer::legacy(authority)
+    }
+
+    /// Stores the claimed amount of the claimer, the legacy claim_authority has no slot
+    pub fn save_claimer(&mut self, claimer: &Claimer) {
+        if let Some(slot) =
+            self.claimers.iter_mut().find(|item| item.authority == claimer.authority)
+        {
+            *slot = *claimer;
+        }
+    }
+}
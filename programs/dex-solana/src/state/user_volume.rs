@@ -0,0 +1,51 @@
+use crate::error::ErrorCode;
+use anchor_lang::prelude::*;
+
+/// The swap volume of a user under an integrator, in the integrator's reference mint.
+#[account(zero_copy(unsafe))]
+#[derive(Debug)]
+pub struct UserVolume {
+    /// Bump to identify PDA.
+    pub bump: u8,
+
+    /// The integrator id.
+    pub integrator_id: u64,
+
+    /// The user, the payer of the swaps.
+    pub user: Pubkey,
+
+    /// The accumulated volume in the reference mint.
+    pub volume: u64,
+
+    /// padding for upgrade
+    pub padding: [u8; 64],
+}
+
+impl Default for UserVolume {
+    fn default() -> Self {
+        UserVolume {
+            bump: 0,
+            integrator_id: 0,
+            user: Pubkey::default(),
+            volume: 0,
+            padding: [0u8; 64],
+        }
+    }
+}
+
+impl UserVolume {
+    pub const LEN: usize = 8 + std::mem::size_of::<UserVolume>();
+
+    pub fn validate(&self, integrator_id: u64, user: &Pubkey) -> Result<()> {
+        require!(
+            self.integrator_id == integrator_id && self.user == *user,
+            ErrorCode::InvalidUserVolume
+        );
+        Ok(())
+    }
+
+    pub fn accumulate(&mut self, amount: u64) -> Result<()> {
+        self.volume = self.volume.saturating_add(amount);
+        Ok(())
+    }
+}
@@ -1,41 +1,86 @@
 use crate::constants::*;
 use crate::error::{ErrorCode, LimitOrderError};
+use crate::state::event::TransferFeeEvent;
 use crate::utils::*;
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::sysvar::instructions::load_instruction_at_checked;
-use anchor_spl::token_interface::{Mint, TokenInterface};
+use anchor_spl::token_interface::Mint;
+
+/// Breakdown of the fees charged by a resolver.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CollectedFees {
+    pub priority_fee: u64,
+    pub tips: u64,
+    pub fee_multiplier: u8,
+    pub total_fee: u64,
+    pub charged_fee: u64,
+}
 
 pub fn collect_fees<'a>(
     tips: u64,
     fee_multiplier: u8,
+    max_fee: u64,
     escrow_fee_account: AccountInfo<'a>,
     payer: AccountInfo<'a>,
     instruction_sysvar_account_info: &AccountInfo<'a>,
     escrow_min_rent: u64,
-) -> Result<()> {
+) -> Result<CollectedFees> {
+    let mut fees = calculate_resolver_fees(tips, fee_multiplier, instruction_sysvar_account_info)?;
+    if fees.total_fee == 0 {
+        return Ok(fees);
+    }
+
+    // Cap the fees by the max fee set by the maker, 0 means no cap
+    if max_fee > 0 {
+        fees.charged_fee = fees.charged_fee.min(max_fee);
+    }
+    charge_fees(fees.charged_fee, escrow_fee_account, payer, escrow_min_rent)?;
+    Ok(fees)
+}
+
+// calculate the resolver fees: (priority fee + tips) * fee multiplier
+pub fn calculate_resolver_fees(
+    tips: u64,
+    fee_multiplier: u8,
+    instruction_sysvar_account_info: &AccountInfo,
+) -> Result<CollectedFees> {
     let priority_fee = compute_fees(instruction_sysvar_account_info)?;
     let mut fees = priority_fee.checked_add(tips).ok_or(LimitOrderError::MathOverflow)?;
-    if fees == 0 {
-        return Ok(());
-    }
 
     // Calculate the fees with fee multiplier
-    if fee_multiplier > 10 {
+    if fees > 0 && fee_multiplier > 10 {
         fees = fees
             .checked_mul(u64::from(fee_multiplier))
             .ok_or(LimitOrderError::MathOverflow)?
             .checked_div(FEE_MULTIPLIER_DENOMINATOR)
             .ok_or(LimitOrderError::MathOverflow)?;
     }
-
-    let escrow_fee_balance = escrow_fee_account.lamports();
     msg!(
-        "Escrow fee: {:?}, tips: {:?}, multiplier: {:?}, collecting fees: {:?}",
-        escrow_fee_balance - escrow_min_rent,
+        "Priority fee: {:?}, tips: {:?}, multiplier: {:?}, total fees: {:?}",
+        priority_fee,
         tips,
         fee_multiplier,
         fees
     );
+    Ok(CollectedFees { priority_fee, tips, fee_multiplier, total_fee: fees, charged_fee: fees })
+}
+
+// charge the fees from the escrow fee account to the payer
+pub fn charge_fees<'a>(
+    fees: u64,
+    escrow_fee_account: AccountInfo<'a>,
+    payer: AccountInfo<'a>,
+    escrow_min_rent: u64,
+) -> Result<()> {
+    if fees == 0 {
+        return Ok(());
+    }
+    let escrow_fee_balance = escrow_fee_account.lamports();
+    msg!(
+        "Escrow fee: {:?}, collecting fees: {:?}",
+        escrow_fee_balance.saturating_sub(escrow_min_rent),
+        fees
+    );
     if escrow_fee_balance < fees + escrow_min_rent {
         return Err(LimitOrderError::NotEnoughTradeFee.into());
     }
@@ -96,55 +141,35 @@ pub fn calculate_fee_amounts(
     commission_direction: bool,
     platform_fee_rate: Option<u16>,
 ) -> Result<(u64, u64)> {
-    if commission_rate == 0 {
-        return Ok((0, 0));
-    }
-    require!(commission_rate <= COMMISSION_RATE_LIMIT_V2, ErrorCode::InvalidCommissionRate);
+    let fees = FeePolicy::v3(commission_rate, commission_direction, platform_fee_rate)
+        .fee_amounts(amount)?;
+    Ok((fees.commission_amount, fees.platform_fee_amount))
+}
 
-    let commission_amount = if commission_direction {
-        u64::try_from(
-            u128::from(amount)
-                .checked_mul(commission_rate as u128)
-                .ok_or(ErrorCode::CalculationError)?
-                .checked_div(COMMISSION_DENOMINATOR_V2 as u128 - commission_rate as u128)
-                .ok_or(ErrorCode::CalculationError)?,
-        )
-        .unwrap()
-    } else {
-        u64::try_from(
-            u128::from(amount)
-                .checked_mul(commission_rate as u128)
-                .ok_or(ErrorCode::CalculationError)?
-                .checked_div(COMMISSION_DENOMINATOR_V2 as u128)
-                .ok_or(ErrorCode::CalculationError)?,
-        )
-        .unwrap()
-    };
+// split the commission amount by the recipient rates, the rounding remainder goes to the first recipient
+pub fn split_commission_amount(
+    commission_amount: u64,
+    commission_rates: &[u32],
+) -> Result<Vec<u64>> {
+    let total_rate: u128 = commission_rates.iter().map(|rate| *rate as u128).sum();
+    require!(total_rate > 0, ErrorCode::InvalidCommissionSplits);
 
-    let platform_fee_amount = if platform_fee_rate.is_some() && platform_fee_rate.unwrap() > 0 {
-        let platform_fee_rate = platform_fee_rate.unwrap();
-        require!(
-            platform_fee_rate as u64 <= PLATFORM_FEE_RATE_LIMIT_V3,
-            ErrorCode::InvalidPlatformFeeRate
-        );
-        u64::try_from(
-            u128::from(commission_amount)
-                .checked_mul(platform_fee_rate as u128)
-                .ok_or(ErrorCode::CalculationError)?
-                .checked_div(PLATFORM_FEE_DENOMINATOR_V3 as u128)
-                .ok_or(ErrorCode::CalculationError)?,
+    let mut amounts = Vec::with_capacity(commission_rates.len());
+    for rate in commission_rates {
+        let amount = u128::from(commission_amount)
+            .checked_mul(*rate as u128)
+            .ok_or(ErrorCode::CalculationError)?
+            .checked_div(total_rate)
+            .ok_or(ErrorCode::CalculationError)?;
+        amounts.push(u64::try_from(amount).unwrap());
+    }
+    let split_amount: u64 = amounts.iter().sum();
+    amounts[0] = amounts[0]
+        .checked_add(
+            commission_amount.checked_sub(split_amount).ok_or(ErrorCode::CalculationError)?,
         )
-        .unwrap()
-    } else {
-        0
-    };
-    require!(platform_fee_amount <= commission_amount, ErrorCode::InvalidPlatformFeeAmount);
-
-    // commission_amount - platform_fee_amount
-    let commission_amount =
-        commission_amount.checked_sub(platform_fee_amount).ok_or(ErrorCode::CalculationError)?;
-
-    Ok((commission_amount, platform_fee_amount))
+        .ok_or(ErrorCode::CalculationError)?;
+    Ok(amounts)
 }
 
 // calculate trim amount
@@ -157,60 +182,29 @@ pub fn calculate_trim_amount(
     trim_rate: Option<u8>,
     charge_rate: Option<u16>,
 ) -> Result<(u64, u64)> {
-    if trim_rate.is_none() || trim_rate.unwrap() == 0 {
-        return Ok((0, 0));
-    }
-    let trim_rate = trim_rate.unwrap();
-    require!(trim_rate <= TRIM_RATE_LIMIT_V2, ErrorCode::InvalidTrimRate);
-
-    let trim_limit = u64::try_from(
-        u128::from(amount)
-            .saturating_mul(trim_rate as u128)
-            .saturating_div(TRIM_DENOMINATOR_V2 as u128),
+    FeePolicy::v3(0, commission_direction, None).with_trim(trim_rate, charge_rate).trim_amounts(
+        amount,
+        expected_amount_out,
+        &FeeAmounts { commission_amount, platform_fee_amount },
     )
-    .unwrap();
-
-    let trim_amount = if commission_direction {
-        (amount.saturating_sub(expected_amount_out)).min(trim_limit)
-    } else {
-        (amount
-            .saturating_sub(commission_amount)
-            .saturating_sub(platform_fee_amount)
-            .saturating_sub(expected_amount_out))
-        .min(trim_limit)
-    };
-
-    if charge_rate.is_some() && charge_rate.unwrap() > 0 {
-        let charge_rate = charge_rate.unwrap();
-        require!(charge_rate <= TRIM_DENOMINATOR_V2, ErrorCode::InvalidChargeRate);
-
-        let charge_amount = u64::try_from(
-            u128::from(trim_amount)
-                .saturating_mul(charge_rate as u128)
-                .saturating_div(TRIM_DENOMINATOR_V2 as u128),
-        )
-        .unwrap();
-        return Ok((trim_amount.saturating_sub(charge_amount), charge_amount));
-    } else {
-        return Ok((trim_amount, 0));
-    }
 }
 
 pub fn transfer_token_fee<'a>(
     authority: &AccountInfo<'a>,
     token_account: &AccountInfo<'a>,
     token_mint: &InterfaceAccount<'a, Mint>,
-    token_program: &Interface<'a, TokenInterface>,
+    token_program: &AccountInfo<'a>,
     fee_account: &AccountInfo<'a>,
     fee_amount: u64,
     signer_seeds: Option<&[&[&[u8]]]>,
+    transfer_ctx: &TransferContext<'_, 'a>,
 ) -> Result<()> {
     if fee_amount == 0 {
         return Ok(());
     }
     let fee_to_token_account = associate_convert_token_account(fee_account)?;
     require!(fee_to_token_account.mint == token_mint.key(), ErrorCode::InvalidFeeTokenAccount);
-    transfer_token(
+    transfer_token_routed(
         authority.to_account_info(),
         token_account.to_account_info(),
         fee_to_token_account.to_account_info(),
@@ -219,7 +213,20 @@ pub fn transfer_token_fee<'a>(
         fee_amount,
         token_mint.decimals,
         signer_seeds,
-    )
+        transfer_ctx,
+    )?;
+
+    // The fee recipient bears the transfer fee of the mint
+    let transfer_fee = get_transfer_fee(&token_mint.to_account_info(), fee_amount)?;
+    if transfer_fee > 0 {
+        emit!(TransferFeeEvent {
+            mint: token_mint.key(),
+            account: fee_account.key(),
+            gross_amount: fee_amount,
+            net_amount: fee_amount.checked_sub(transfer_fee).ok_or(ErrorCode::CalculationError)?,
+        });
+    }
+    Ok(())
 }
 
 pub fn transfer_sol_fee<'a>(
@@ -262,7 +269,7 @@ pub fn is_charge_sol(
     platform_fee_account: &Option<AccountInfo>,
     token_mint: &InterfaceAccount<Mint>,
 ) -> bool {
-    if token_mint.key() != wsol_program::ID {
+    if !is_native_mint(&token_mint.key()) {
         return false;
     }
     if commission_account.is_some()
@@ -0,0 +1,746 @@
+use crate::constants::*;
+use crate::error::ErrorCode;
+use crate::utils::{
+    TransferContext, log_charge_fee_info, log_commission_info, log_platform_fee_info,
+    log_trim_fee_info, transfer_sol, transfer_sol_fee, transfer_token_fee,
+    transfer_token_with_hook,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+/// The fee schedule generation of an entrypoint, each one has its own denominators:
+/// - V1: commission in COMMISSION_DENOMINATOR, no platform fee
+/// - V2: commission in COMMISSION_DENOMINATOR_V2, platform fee in PLATFORM_FEE_DENOMINATOR_V2
+/// - V3: commission in COMMISSION_DENOMINATOR_V2, platform fee in PLATFORM_FEE_DENOMINATOR_V3
+/// - LimitOrder: fee in COMMISSION_DENOMINATOR_V2 taken out of the filled amount, no platform fee
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeeSchedule {
+    V1,
+    V2,
+    V3,
+    LimitOrder,
+}
+
+impl FeeSchedule {
+    pub fn commission_denominator(&self) -> u64 {
+        match self {
+            FeeSchedule::V1 => COMMISSION_DENOMINATOR,
+            FeeSchedule::V2 | FeeSchedule::V3 | FeeSchedule::LimitOrder => {
+                COMMISSION_DENOMINATOR_V2
+            }
+        }
+    }
+
+    pub fn commission_rate_limit(&self) -> u64 {
+        match self {
+            FeeSchedule::V1 => COMMISSION_RATE_LIMIT as u64,
+            FeeSchedule::V2 | FeeSchedule::V3 | FeeSchedule::LimitOrder => {
+                COMMISSION_RATE_LIMIT_V2 as u64
+            }
+        }
+    }
+
+    pub fn platform_fee_denominator(&self) -> u64 {
+        match self {
+            FeeSchedule::V1 | FeeSchedule::V2 | FeeSchedule::LimitOrder => {
+                PLATFORM_FEE_DENOMINATOR_V2
+            }
+            FeeSchedule::V3 => PLATFORM_FEE_DENOMINATOR_V3,
+        }
+    }
+
+    pub fn platform_fee_rate_limit(&self) -> u64 {
+        match self {
+            FeeSchedule::V1 | FeeSchedule::LimitOrder => 0,
+            FeeSchedule::V2 => PLATFORM_FEE_RATE_LIMIT_V2,
+            FeeSchedule::V3 => PLATFORM_FEE_RATE_LIMIT_V3,
+        }
+    }
+}
+
+/// The commission and platform fee charged on a swap, the commission is net of the platform fee.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeeAmounts {
+    pub commission_amount: u64,
+    pub platform_fee_amount: u64,
+}
+
+impl FeeAmounts {
+    /// The gross commission, including the platform fee
+    pub fn total(&self) -> Result<u64> {
+        Ok(self
+            .commission_amount
+            .checked_add(self.platform_fee_amount)
+            .ok_or(ErrorCode::CalculationError)?)
+    }
+}
+
+/// The fee settings of a swap, built by every entrypoint and executed by one engine.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeePolicy {
+    pub schedule: FeeSchedule,
+    /// Commission rate, denominated in the schedule's commission denominator
+    pub commission_rate: u32,
+    /// true: commission on the from amount, false: commission on the to amount
+    pub commission_direction: bool,
+    /// Platform fee share of the commission, denominated in the schedule's platform fee denominator
+    pub platform_fee_rate: u32,
+    /// Trim rate, denominated in TRIM_DENOMINATOR_V2
+    pub trim_rate: u8,
+    /// Charge share of the trim amount, denominated in TRIM_DENOMINATOR_V2
+    pub charge_rate: u16,
+    /// User share of the trimmed surplus, denominated in TRIM_DENOMINATOR_V2
+    pub user_share_rate: u16,
+}
+
+/// The split of the surplus above the expected amount out.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TrimAmounts {
+    pub trim_amount: u64,
+    pub charge_amount: u64,
+    /// Left to the user, not transferred out
+    pub user_share_amount: u64,
+}
+
+impl FeePolicy {
+    pub fn v1(commission_rate: u16, commission_direction: bool) -> Self {
+        FeePolicy {
+            schedule: FeeSchedule::V1,
+            commission_rate: commission_rate as u32,
+            commission_direction,
+            platform_fee_rate: 0,
+            trim_rate: 0,
+            charge_rate: 0,
+            user_share_rate: 0,
+        }
+    }
+
+    pub fn v2(commission_rate: u32, commission_direction: bool, platform_fee_rate: u32) -> Self {
+        FeePolicy {
+            schedule: FeeSchedule::V2,
+            commission_rate,
+            commission_direction,
+            platform_fee_rate,
+            trim_rate: 0,
+            charge_rate: 0,
+            user_share_rate: 0,
+        }
+    }
+
+    /// The fee of a limit order fill, commission_info bit31 is the direction, the rest the rate
+    pub fn limit_order(fee_rate: u32, fee_direction: bool) -> Self {
+        FeePolicy {
+            schedule: FeeSchedule::LimitOrder,
+            commission_rate: fee_rate,
+            commission_direction: fee_direction,
+            platform_fee_rate: 0,
+            trim_rate: 0,
+            charge_rate: 0,
+            user_share_rate: 0,
+        }
+    }
+
+    pub fn v3(
+        commission_rate: u32,
+        commission_direction: bool,
+        platform_fee_rate: Option<u16>,
+    ) -> Self {
+        FeePolicy {
+            schedule: FeeSchedule::V3,
+            commission_rate,
+            commission_direction,
+            platform_fee_rate: platform_fee_rate.unwrap_or(0) as u32,
+            trim_rate: 0,
+            charge_rate: 0,
+            user_share_rate: 0,
+        }
+    }
+
+    pub fn with_trim(mut self, trim_rate: Option<u8>, charge_rate: Option<u16>) -> Self {
+        self.trim_rate = trim_rate.unwrap_or(0);
+        self.charge_rate = charge_rate.unwrap_or(0);
+        self
+    }
+
+    pub fn with_user_share(mut self, user_share_rate: Option<u16>) -> Self {
+        self.user_share_rate = user_share_rate.unwrap_or(0);
+        self
+    }
+
+    /// The platform fee share of the commission, denominated in PLATFORM_FEE_DENOMINATOR_V3
+    pub fn platform_fee_share(&self) -> Result<u64> {
+        (self.platform_fee_rate as u64)
+            .checked_mul(PLATFORM_FEE_DENOMINATOR_V3)
+            .ok_or(ErrorCode::CalculationError)?
+            .checked_div(self.schedule.platform_fee_denominator())
+            .ok_or(ErrorCode::CalculationError.into())
+    }
+
+    /// The gross commission on the from amount if commission_direction, otherwise on the to amount,
+    /// the limit order fee is taken out of the amount in both directions
+    pub fn commission_amount(&self, amount: u64) -> Result<u64> {
+        let denominator = self.schedule.commission_denominator();
+        let divisor = if self.commission_direction && self.schedule != FeeSchedule::LimitOrder {
+            denominator
+                .checked_sub(self.commission_rate as u64)
+                .ok_or(ErrorCode::CalculationError)?
+        } else {
+            denominator
+        };
+        match self.schedule {
+            // V1 and LimitOrder are calculated in u64, the overflow is an error
+            FeeSchedule::V1 | FeeSchedule::LimitOrder => Ok(amount
+                .checked_mul(self.commission_rate as u64)
+                .ok_or(ErrorCode::CalculationError)?
+                .checked_div(divisor)
+                .ok_or(ErrorCode::CalculationError)?),
+            FeeSchedule::V2 | FeeSchedule::V3 => Ok(u64::try_from(
+                u128::from(amount)
+                    .checked_mul(self.commission_rate as u128)
+                    .ok_or(ErrorCode::CalculationError)?
+                    .checked_div(divisor as u128)
+                    .ok_or(ErrorCode::CalculationError)?,
+            )
+            .unwrap()),
+        }
+    }
+
+    /// The platform fee taken out of the gross commission
+    pub fn platform_fee_amount(&self, commission_amount: u64) -> Result<u64> {
+        if self.platform_fee_rate == 0 {
+            return Ok(0);
+        }
+        Ok(u64::try_from(
+            u128::from(commission_amount)
+                .checked_mul(self.platform_fee_rate as u128)
+                .ok_or(ErrorCode::CalculationError)?
+                .checked_div(self.schedule.platform_fee_denominator() as u128)
+                .ok_or(ErrorCode::CalculationError)?,
+        )
+        .unwrap())
+    }
+
+    /// Calculate the commission and platform fee amounts
+    pub fn fee_amounts(&self, amount: u64) -> Result<FeeAmounts> {
+        if self.commission_rate == 0 {
+            return Ok(FeeAmounts::default());
+        }
+        require!(
+            self.commission_rate as u64 <= self.schedule.commission_rate_limit(),
+            ErrorCode::InvalidCommissionRate
+        );
+        require!(
+            self.platform_fee_rate as u64 <= self.schedule.platform_fee_rate_limit(),
+            ErrorCode::InvalidPlatformFeeRate
+        );
+
+        let commission_amount = self.commission_amount(amount)?;
+        let platform_fee_amount = self.platform_fee_amount(commission_amount)?;
+        require!(platform_fee_amount <= commission_amount, ErrorCode::InvalidPlatformFeeAmount);
+
+        Ok(FeeAmounts {
+            commission_amount: commission_amount
+                .checked_sub(platform_fee_amount)
+                .ok_or(ErrorCode::CalculationError)?,
+            platform_fee_amount,
+        })
+    }
+
+    /// Calculate the trim and charge amounts of the to amount
+    pub fn trim_amounts(
+        &self,
+        amount: u64,
+        expected_amount_out: u64,
+        fees: &FeeAmounts,
+    ) -> Result<(u64, u64)> {
+        let trim = self.trim_breakdown(amount, expected_amount_out, fees)?;
+        Ok((trim.trim_amount, trim.charge_amount))
+    }
+
+    /// Split the surplus above the expected amount out, up to the trim limit,
+    /// into the user share, the charge and the trim
+    pub fn trim_breakdown(
+        &self,
+        amount: u64,
+        expected_amount_out: u64,
+        fees: &FeeAmounts,
+    ) -> Result<TrimAmounts> {
+        if self.trim_rate == 0 {
+            return Ok(TrimAmounts::default());
+        }
+        require!(self.trim_rate <= TRIM_RATE_LIMIT_V2, ErrorCode::InvalidTrimRate);
+
+        let trim_limit = u64::try_from(
+            u128::from(amount)
+                .saturating_mul(self.trim_rate as u128)
+                .saturating_div(TRIM_DENOMINATOR_V2 as u128),
+        )
+        .unwrap();
+
+        let surplus = if self.commission_direction {
+            (amount.saturating_sub(expected_amount_out)).min(trim_limit)
+        } else {
+            (amount
+                .saturating_sub(fees.commission_amount)
+                .saturating_sub(fees.platform_fee_amount)
+                .saturating_sub(expected_amount_out))
+            .min(trim_limit)
+        };
+
+        require!(self.user_share_rate <= TRIM_DENOMINATOR_V2, ErrorCode::InvalidUserShareRate);
+        let user_share_amount = share_of(surplus, self.user_share_rate);
+        let trim_amount = surplus.saturating_sub(user_share_amount);
+
+        require!(self.charge_rate <= TRIM_DENOMINATOR_V2, ErrorCode::InvalidChargeRate);
+        let charge_amount = share_of(trim_amount, self.charge_rate);
+        Ok(TrimAmounts {
+            trim_amount: trim_amount.saturating_sub(charge_amount),
+            charge_amount,
+            user_share_amount,
+        })
+    }
+}
+
+/// The account the fees of a swap are paid from, the payer or the SA
+pub struct FeeSource<'a, 'info> {
+    /// The owner of the token account, also pays the fees in SOL
+    pub authority: &'a AccountInfo<'info>,
+    pub token_account: Option<&'a AccountInfo<'info>>,
+    pub token_mint: &'a InterfaceAccount<'info, Mint>,
+    pub token_program: Option<&'a AccountInfo<'info>>,
+    pub signer_seeds: Option<&'a [&'a [&'a [u8]]]>,
+    /// The fees are paid in SOL out of the authority instead of the token account
+    pub is_charge_sol: bool,
+    /// The fee accounts are paid as the V1/V2 and limit order entrypoints always did, with no
+    /// rent exemption top up of SOL fee accounts and no check of the fee token accounts
+    pub legacy_transfer: bool,
+    pub transfer_ctx: TransferContext<'a, 'info>,
+}
+
+impl<'info> FeeSource<'_, 'info> {
+    /// Pays one fee, returns the SOL added to reach the rent exemption
+    pub fn pay(&self, fee_account: &AccountInfo<'info>, fee_amount: u64) -> Result<u64> {
+        if self.is_charge_sol {
+            if self.legacy_transfer {
+                transfer_sol(
+                    self.authority.clone(),
+                    fee_account.clone(),
+                    fee_amount,
+                    self.signer_seeds,
+                )?;
+                return Ok(0);
+            }
+            return transfer_sol_fee(self.authority, fee_account, fee_amount, self.signer_seeds);
+        }
+        let token_account = self.token_account.ok_or(ErrorCode::InvalidTokenAccount)?;
+        let token_program = self.token_program.ok_or(ErrorCode::InvalidTokenProgram)?;
+        if self.legacy_transfer {
+            transfer_token_with_hook(
+                self.authority.clone(),
+                token_account.clone(),
+                fee_account.clone(),
+                self.token_mint.to_account_info(),
+                token_program.to_account_info(),
+                fee_amount,
+                self.token_mint.decimals,
+                self.signer_seeds,
+                self.transfer_ctx.hook_accounts,
+            )?;
+            return Ok(0);
+        }
+        transfer_token_fee(
+            self.authority,
+            token_account,
+            self.token_mint,
+            token_program,
+            fee_account,
+            fee_amount,
+            self.signer_seeds,
+            &self.transfer_ctx,
+        )?;
+        Ok(0)
+    }
+}
+
+/// The accounts receiving the fees of a swap
+#[derive(Default)]
+pub struct FeeRecipients<'a, 'info> {
+    pub commission_account: Option<&'a AccountInfo<'info>>,
+    pub platform_fee_account: Option<&'a AccountInfo<'info>>,
+    pub trim_account: Option<&'a AccountInfo<'info>>,
+    pub charge_account: Option<&'a AccountInfo<'info>>,
+}
+
+impl<'info> FeeRecipients<'_, 'info> {
+    /// Pays the commission and the platform fee out of the source and logs them
+    pub fn pay_fees(
+        &self,
+        source: &FeeSource<'_, 'info>,
+        fees: &FeeAmounts,
+        commission_direction: bool,
+    ) -> Result<()> {
+        if fees.commission_amount > 0 {
+            let commission_account =
+                self.commission_account.ok_or(ErrorCode::CommissionAccountIsNone)?;
+            let adjust_amount = source.pay(commission_account, fees.commission_amount)?;
+            log_commission_info(commission_direction, fees.commission_amount, adjust_amount);
+            commission_account.key().log();
+        }
+        if fees.platform_fee_amount > 0 {
+            let platform_fee_account =
+                self.platform_fee_account.ok_or(ErrorCode::PlatformFeeAccountIsNone)?;
+            let adjust_amount = source.pay(platform_fee_account, fees.platform_fee_amount)?;
+            log_platform_fee_info(
+                fees.platform_fee_amount,
+                adjust_amount,
+                &platform_fee_account.key(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Pays the trim and the charge out of the source and logs them
+    pub fn pay_trim(&self, source: &FeeSource<'_, 'info>, trim: &TrimAmounts) -> Result<()> {
+        if trim.trim_amount > 0 {
+            let trim_account = self.trim_account.ok_or(ErrorCode::TrimAccountIsNone)?;
+            let adjust_amount = source.pay(trim_account, trim.trim_amount)?;
+            log_trim_fee_info(trim.trim_amount, adjust_amount, &trim_account.key());
+        }
+        if trim.charge_amount > 0 {
+            let charge_account = self.charge_account.ok_or(ErrorCode::ChargeAccountIsNone)?;
+            let adjust_amount = source.pay(charge_account, trim.charge_amount)?;
+            log_charge_fee_info(trim.charge_amount, adjust_amount, &charge_account.key());
+        }
+        Ok(())
+    }
+}
+
+/// The share of an amount, rate denominated in TRIM_DENOMINATOR_V2
+fn share_of(amount: u64, rate: u16) -> u64 {
+    u64::try_from(
+        u128::from(amount).saturating_mul(rate as u128).saturating_div(TRIM_DENOMINATOR_V2 as u128),
+    )
+    .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fees(commission_amount: u64, platform_fee_amount: u64) -> FeeAmounts {
+        FeeAmounts { commission_amount, platform_fee_amount }
+    }
+
+    /// The fee math of the entrypoints before FeePolicy, copied verbatim as the golden reference
+    mod legacy {
+        use crate::constants::*;
+        use crate::error::ErrorCode;
+        use anchor_lang::prelude::*;
+
+        // commission_swap.rs
+        pub fn v1_commission(amount: u64, commission_rate: u16, direction: bool) -> Result<u64> {
+            if direction {
+                Ok(amount
+                    .checked_mul(commission_rate as u64)
+                    .ok_or(ErrorCode::CalculationError)?
+                    .checked_div(COMMISSION_DENOMINATOR - commission_rate as u64)
+                    .ok_or(ErrorCode::CalculationError)?)
+            } else {
+                Ok(amount
+                    .checked_mul(commission_rate as u64)
+                    .ok_or(ErrorCode::CalculationError)?
+                    .checked_div(COMMISSION_DENOMINATOR)
+                    .ok_or(ErrorCode::CalculationError)?)
+            }
+        }
+
+        // platform_fee_proxy_swap_v2.rs, the commission is gross of the platform fee
+        pub fn v2_fees(
+            amount: u64,
+            commission_rate: u32,
+            direction: bool,
+            platform_fee_rate: u32,
+        ) -> Result<(u64, u64)> {
+            let commission_amount = if direction {
+                u64::try_from(
+                    u128::from(amount)
+                        .checked_mul(commission_rate as u128)
+                        .ok_or(ErrorCode::CalculationError)?
+                        .checked_div(COMMISSION_DENOMINATOR_V2 as u128 - commission_rate as u128)
+                        .ok_or(ErrorCode::CalculationError)?,
+                )
+                .unwrap()
+            } else {
+                u64::try_from(
+                    u128::from(amount)
+                        .checked_mul(commission_rate as u128)
+                        .ok_or(ErrorCode::CalculationError)?
+                        .checked_div(COMMISSION_DENOMINATOR_V2 as u128)
+                        .ok_or(ErrorCode::CalculationError)?,
+                )
+                .unwrap()
+            };
+            let platform_fee_amount = u64::try_from(
+                u128::from(commission_amount)
+                    .checked_mul(platform_fee_rate as u128)
+                    .ok_or(ErrorCode::CalculationError)?
+                    .checked_div(PLATFORM_FEE_DENOMINATOR_V2 as u128)
+                    .ok_or(ErrorCode::CalculationError)?,
+            )
+            .unwrap();
+            Ok((commission_amount, platform_fee_amount))
+        }
+
+        // commission_fill_order.rs and swap.rs FillOrderSwapProcessor
+        pub fn limit_order_fee(amount: u64, fee_rate: u32) -> Result<u64> {
+            Ok(amount
+                .checked_mul(fee_rate as u64)
+                .ok_or(ErrorCode::CalculationError)?
+                .checked_div(COMMISSION_DENOMINATOR_V2)
+                .ok_or(ErrorCode::CalculationError)?)
+        }
+
+        // fee.rs calculate_fee_amounts
+        pub fn v3_fees(
+            amount: u64,
+            commission_rate: u32,
+            commission_direction: bool,
+            platform_fee_rate: Option<u16>,
+        ) -> Result<(u64, u64)> {
+            if commission_rate == 0 {
+                return Ok((0, 0));
+            }
+            require!(commission_rate <= COMMISSION_RATE_LIMIT_V2, ErrorCode::InvalidCommissionRate);
+
+            let commission_amount = if commission_direction {
+                u64::try_from(
+                    u128::from(amount)
+                        .checked_mul(commission_rate as u128)
+                        .ok_or(ErrorCode::CalculationError)?
+                        .checked_div(COMMISSION_DENOMINATOR_V2 as u128 - commission_rate as u128)
+                        .ok_or(ErrorCode::CalculationError)?,
+                )
+                .unwrap()
+            } else {
+                u64::try_from(
+                    u128::from(amount)
+                        .checked_mul(commission_rate as u128)
+                        .ok_or(ErrorCode::CalculationError)?
+                        .checked_div(COMMISSION_DENOMINATOR_V2 as u128)
+                        .ok_or(ErrorCode::CalculationError)?,
+                )
+                .unwrap()
+            };
+
+            let platform_fee_amount =
+                if platform_fee_rate.is_some() && platform_fee_rate.unwrap() > 0 {
+                    let platform_fee_rate = platform_fee_rate.unwrap();
+                    require!(
+                        platform_fee_rate as u64 <= PLATFORM_FEE_RATE_LIMIT_V3,
+                        ErrorCode::InvalidPlatformFeeRate
+                    );
+                    u64::try_from(
+                        u128::from(commission_amount)
+                            .checked_mul(platform_fee_rate as u128)
+                            .ok_or(ErrorCode::CalculationError)?
+                            .checked_div(PLATFORM_FEE_DENOMINATOR_V3 as u128)
+                            .ok_or(ErrorCode::CalculationError)?,
+                    )
+                    .unwrap()
+                } else {
+                    0
+                };
+            require!(platform_fee_amount <= commission_amount, ErrorCode::InvalidPlatformFeeAmount);
+
+            // commission_amount - platform_fee_amount
+            let commission_amount = commission_amount
+                .checked_sub(platform_fee_amount)
+                .ok_or(ErrorCode::CalculationError)?;
+
+            Ok((commission_amount, platform_fee_amount))
+        }
+
+        // fee.rs calculate_trim_amount
+        pub fn v3_trim(
+            amount: u64,
+            expected_amount_out: u64,
+            commission_amount: u64,
+            platform_fee_amount: u64,
+            commission_direction: bool,
+            trim_rate: Option<u8>,
+            charge_rate: Option<u16>,
+        ) -> Result<(u64, u64)> {
+            if trim_rate.is_none() || trim_rate.unwrap() == 0 {
+                return Ok((0, 0));
+            }
+            let trim_rate = trim_rate.unwrap();
+            require!(trim_rate <= TRIM_RATE_LIMIT_V2, ErrorCode::InvalidTrimRate);
+
+            let trim_limit = u64::try_from(
+                u128::from(amount)
+                    .saturating_mul(trim_rate as u128)
+                    .saturating_div(TRIM_DENOMINATOR_V2 as u128),
+            )
+            .unwrap();
+
+            let trim_amount = if commission_direction {
+                (amount.saturating_sub(expected_amount_out)).min(trim_limit)
+            } else {
+                (amount
+                    .saturating_sub(commission_amount)
+                    .saturating_sub(platform_fee_amount)
+                    .saturating_sub(expected_amount_out))
+                .min(trim_limit)
+            };
+
+            if charge_rate.is_some() && charge_rate.unwrap() > 0 {
+                let charge_rate = charge_rate.unwrap();
+                require!(charge_rate <= TRIM_DENOMINATOR_V2, ErrorCode::InvalidChargeRate);
+
+                let charge_amount = u64::try_from(
+                    u128::from(trim_amount)
+                        .saturating_mul(charge_rate as u128)
+                        .saturating_div(TRIM_DENOMINATOR_V2 as u128),
+                )
+                .unwrap();
+                Ok((trim_amount.saturating_sub(charge_amount), charge_amount))
+            } else {
+                Ok((trim_amount, 0))
+            }
+        }
+    }
+
+    const AMOUNTS: [u64; 8] =
+        [0, 1, 99, 1_000_000, 123_456_789, 1_000_000_007, u64::MAX / 3, u64::MAX];
+
+    #[test]
+    pub fn test_v1_matches_legacy() {
+        for amount in AMOUNTS {
+            for rate in [0, 1, 30, 100, COMMISSION_RATE_LIMIT] {
+                for direction in [true, false] {
+                    let fees = FeePolicy::v1(rate, direction).fee_amounts(amount);
+                    assert_eq!(
+                        fees.ok().map(|fees| fees.commission_amount),
+                        legacy::v1_commission(amount, rate, direction).ok(),
+                        "amount {amount} rate {rate} direction {direction}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_v2_matches_legacy() {
+        for amount in AMOUNTS {
+            for rate in [0, 1, 3_000_000, 10_000_000, COMMISSION_RATE_LIMIT_V2] {
+                for platform_fee_rate in [0, 1, 200_000_000, PLATFORM_FEE_RATE_LIMIT_V2 as u32] {
+                    for direction in [true, false] {
+                        let fees = FeePolicy::v2(rate, direction, platform_fee_rate)
+                            .fee_amounts(amount)
+                            .unwrap();
+                        // The legacy commission is gross of the platform fee
+                        assert_eq!(
+                            (fees.total().unwrap(), fees.platform_fee_amount),
+                            legacy::v2_fees(amount, rate, direction, platform_fee_rate).unwrap(),
+                            "amount {amount} rate {rate} platform {platform_fee_rate}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_limit_order_matches_legacy() {
+        for amount in AMOUNTS {
+            for fee_rate in [0, 1, 3_000_000, COMMISSION_RATE_LIMIT_V2, (1 << 31) - 1] {
+                for direction in [true, false] {
+                    assert_eq!(
+                        FeePolicy::limit_order(fee_rate, direction).commission_amount(amount).ok(),
+                        legacy::limit_order_fee(amount, fee_rate).ok(),
+                        "amount {amount} rate {fee_rate} direction {direction}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_v3_matches_legacy() {
+        for amount in AMOUNTS {
+            for rate in [0, 1, 3_000_000, COMMISSION_RATE_LIMIT_V2, COMMISSION_RATE_LIMIT_V2 + 1] {
+                for platform_fee_rate in [None, Some(0), Some(2_500), Some(10_000), Some(10_001)] {
+                    for direction in [true, false] {
+                        let fees = FeePolicy::v3(rate, direction, platform_fee_rate)
+                            .fee_amounts(amount)
+                            .ok()
+                            .map(|fees| (fees.commission_amount, fees.platform_fee_amount));
+                        assert_eq!(
+                            fees,
+                            legacy::v3_fees(amount, rate, direction, platform_fee_rate).ok(),
+                            "amount {amount} rate {rate} platform {platform_fee_rate:?}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_trim_matches_legacy() {
+        for amount in AMOUNTS {
+            for expected_amount_out in [0, amount / 2, amount.saturating_sub(amount / 100), amount]
+            {
+                for trim_rate in [None, Some(0), Some(10), Some(TRIM_RATE_LIMIT_V2), Some(101)] {
+                    for charge_rate in [None, Some(0), Some(300), Some(1_000), Some(1_001)] {
+                        for direction in [true, false] {
+                            let fees = fees(amount / 400, amount / 1_000);
+                            let trim = FeePolicy::v3(0, direction, None)
+                                .with_trim(trim_rate, charge_rate)
+                                .trim_amounts(amount, expected_amount_out, &fees)
+                                .ok();
+                            let legacy = legacy::v3_trim(
+                                amount,
+                                expected_amount_out,
+                                fees.commission_amount,
+                                fees.platform_fee_amount,
+                                direction,
+                                trim_rate,
+                                charge_rate,
+                            )
+                            .ok();
+                            assert_eq!(trim, legacy, "amount {amount} trim {trim_rate:?}");
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_v3_rate_limits() {
+        assert!(
+            FeePolicy::v3(COMMISSION_RATE_LIMIT_V2 + 1, false, None).fee_amounts(1_000).is_err()
+        );
+        assert!(FeePolicy::v3(3_000_000, false, Some(10_001)).fee_amounts(1_000).is_err());
+    }
+
+    #[test]
+    pub fn test_trim_user_share() {
+        // Half of the surplus goes back to the user, the charge is taken from the trim
+        let to = FeePolicy::v3(2_500_000, false, Some(2_000))
+            .with_trim(Some(10), Some(300))
+            .with_user_share(Some(500));
+        assert_eq!(
+            to.trim_breakdown(1_000_000, 990_000, &fees(2_000, 500)).unwrap(),
+            TrimAmounts { trim_amount: 2_625, charge_amount: 1_125, user_share_amount: 3_750 }
+        );
+        assert_eq!(to.trim_amounts(1_000_000, 990_000, &fees(2_000, 500)).unwrap(), (2_625, 1_125));
+
+        assert!(
+            to.with_user_share(Some(TRIM_DENOMINATOR_V2 + 1))
+                .trim_breakdown(1_000_000, 990_000, &fees(2_000, 500))
+                .is_err()
+        );
+    }
+}
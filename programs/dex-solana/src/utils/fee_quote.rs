@@ -0,0 +1,206 @@
+use crate::constants::COMMISSION_FLAG_NATIVE_SOL;
+use crate::error::ErrorCode;
+use crate::utils::fee_policy::{FeeAmounts, FeePolicy, TrimAmounts};
+use anchor_lang::prelude::*;
+
+/// How the fees are collected:
+/// - ToC: the fees are transferred from the user's token accounts
+/// - ToB: the fees are transferred from the SA token accounts, trim and charge are supported
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwapMode {
+    ToC,
+    ToB,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct QuoteFeesArgs {
+    pub mode: SwapMode,
+    pub amount_in: u64,
+    /// The swap output before the fees
+    pub amount_out: u64,
+    pub expected_amount_out: u64,
+    pub commission_info: u32,
+    pub platform_fee_rate: Option<u16>,
+    pub trim_rate: Option<u8>,
+    pub charge_rate: Option<u16>,
+    pub user_share_rate: Option<u16>,
+    /// The commission mint is wSOL
+    pub is_wsol_fee_mint: bool,
+    /// The commission or platform fee account is a wallet
+    pub is_wallet_fee_account: bool,
+    /// The trim or charge account is a wallet
+    pub is_wallet_trim_account: bool,
+}
+
+/// The fee breakdown of a swap, as charged by the ToC and ToB processors.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeeQuote {
+    pub commission_amount: u64,
+    pub platform_fee_amount: u64,
+    pub trim_amount: u64,
+    pub charge_amount: u64,
+    /// The surplus left to the user
+    pub user_share_amount: u64,
+    /// Debited from the user's source account, including the from fees
+    pub actual_amount_in: u64,
+    /// Credited to the user, net of the to fees, trim and charge
+    pub actual_amount_out: u64,
+    /// The commission and platform fee are paid in SOL
+    pub is_charge_sol: bool,
+    /// The destination wSOL is unwrapped and the user receives SOL
+    pub is_unwrap_wsol_to_sa: bool,
+}
+
+/// Calculate the fees if commission is applied to the from amount
+pub fn quote_from_fees(policy: &FeePolicy, amount_in: u64) -> Result<FeeAmounts> {
+    if !policy.commission_direction || policy.commission_rate == 0 {
+        return Ok(FeeAmounts::default());
+    }
+    policy.fee_amounts(amount_in)
+}
+
+/// Calculate the fees if commission is applied to the to amount, with the trim and charge,
+/// returns the fees, trim amounts and actual amount out
+pub fn quote_to_fees(
+    policy: &FeePolicy,
+    amount_out: u64,
+    expected_amount_out: u64,
+) -> Result<(FeeAmounts, TrimAmounts, u64)> {
+    let fees = if !policy.commission_direction && policy.commission_rate > 0 {
+        policy.fee_amounts(amount_out)?
+    } else {
+        FeeAmounts::default()
+    };
+    let trim = policy.trim_breakdown(amount_out, expected_amount_out, &fees)?;
+
+    let actual_amount_out = amount_out
+        .checked_sub(fees.total()?)
+        .ok_or(ErrorCode::CalculationError)?
+        .checked_sub(trim.trim_amount)
+        .ok_or(ErrorCode::CalculationError)?
+        .checked_sub(trim.charge_amount)
+        .ok_or(ErrorCode::CalculationError)?;
+    Ok((fees, trim, actual_amount_out))
+}
+
+/// Quote the full fee breakdown of a v3 swap
+pub fn quote_fees(args: &QuoteFeesArgs) -> Result<FeeQuote> {
+    let commission_direction = args.commission_info >> 31 == 1;
+    let acc_close_flag = ((args.commission_info & (1 << 30)) >> 30) == 1;
+    // The native SOL flag of swap_v3 does not change the fees
+    let commission_rate = args.commission_info & (COMMISSION_FLAG_NATIVE_SOL - 1);
+
+    let mut policy = FeePolicy::v3(commission_rate, commission_direction, args.platform_fee_rate);
+    if args.mode == SwapMode::ToB {
+        policy = policy
+            .with_trim(args.trim_rate, args.charge_rate)
+            .with_user_share(args.user_share_rate);
+    }
+
+    let from_fees = quote_from_fees(&policy, args.amount_in)?;
+    let (to_fees, trim, actual_amount_out) =
+        quote_to_fees(&policy, args.amount_out, args.expected_amount_out)?;
+    let (trim_amount, charge_amount) = (trim.trim_amount, trim.charge_amount);
+    let fees = if commission_direction { from_fees } else { to_fees };
+
+    let is_charge_sol = args.is_wsol_fee_mint && args.is_wallet_fee_account && fees.total()? > 0;
+    let is_unwrap_wsol_to_sa = args.mode == SwapMode::ToB
+        && !commission_direction
+        && (is_charge_sol
+            || (args.is_wsol_fee_mint
+                && args.is_wallet_trim_account
+                && trim_amount.checked_add(charge_amount).ok_or(ErrorCode::CalculationError)? > 0));
+    if acc_close_flag && args.mode == SwapMode::ToB {
+        // Closing the destination wSOL account requires the fees to be paid to wallets
+        require!(
+            fees.total()? == 0 || args.is_wallet_fee_account,
+            ErrorCode::InvalidCommissionAccount
+        );
+    }
+
+    Ok(FeeQuote {
+        commission_amount: fees.commission_amount,
+        platform_fee_amount: fees.platform_fee_amount,
+        trim_amount,
+        charge_amount,
+        user_share_amount: trim.user_share_amount,
+        actual_amount_in: args
+            .amount_in
+            .checked_add(from_fees.total()?)
+            .ok_or(ErrorCode::CalculationError)?,
+        actual_amount_out,
+        is_charge_sol,
+        is_unwrap_wsol_to_sa,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(mode: SwapMode, commission_info: u32) -> QuoteFeesArgs {
+        QuoteFeesArgs {
+            mode,
+            amount_in: 1_000_000,
+            amount_out: 2_000_000,
+            expected_amount_out: 1_950_000,
+            commission_info,
+            platform_fee_rate: Some(2_000),
+            trim_rate: Some(10),
+            charge_rate: None,
+            user_share_rate: None,
+            is_wsol_fee_mint: false,
+            is_wallet_fee_account: false,
+            is_wallet_trim_account: false,
+        }
+    }
+
+    #[test]
+    pub fn test_quote_from_fees() {
+        // 1% commission on the from amount
+        let quote = quote_fees(&args(SwapMode::ToC, 1 << 31 | 10_000_000)).unwrap();
+        assert_eq!(quote.commission_amount + quote.platform_fee_amount, 10_101);
+        assert_eq!(quote.actual_amount_in, 1_010_101);
+        assert_eq!(quote.actual_amount_out, 2_000_000);
+        assert_eq!(quote.trim_amount, 0);
+    }
+
+    #[test]
+    pub fn test_quote_to_fees_with_trim() {
+        // 1% commission on the to amount, ToC has no trim
+        let quote = quote_fees(&args(SwapMode::ToC, 10_000_000)).unwrap();
+        assert_eq!((quote.commission_amount, quote.platform_fee_amount), (16_000, 4_000));
+        assert_eq!((quote.trim_amount, quote.actual_amount_out), (0, 1_980_000));
+
+        // ToB trims above the expected amount out, up to 1%
+        let quote = quote_fees(&args(SwapMode::ToB, 10_000_000)).unwrap();
+        assert_eq!((quote.trim_amount, quote.actual_amount_out), (20_000, 1_960_000));
+        assert_eq!(quote.actual_amount_in, 1_000_000);
+
+        // Half of the trimmed surplus is left to the user
+        let mut args = args(SwapMode::ToB, 10_000_000);
+        args.user_share_rate = Some(500);
+        let quote = quote_fees(&args).unwrap();
+        assert_eq!((quote.trim_amount, quote.user_share_amount), (10_000, 10_000));
+        assert_eq!(quote.actual_amount_out, 1_970_000);
+    }
+
+    #[test]
+    pub fn test_quote_sol_fees() {
+        let mut args = args(SwapMode::ToB, 10_000_000);
+        args.is_wsol_fee_mint = true;
+        args.is_wallet_fee_account = true;
+        let quote = quote_fees(&args).unwrap();
+        assert!(quote.is_charge_sol && quote.is_unwrap_wsol_to_sa);
+
+        // Only the trim paid in SOL unwraps too
+        args.is_wallet_fee_account = false;
+        args.is_wallet_trim_account = true;
+        let quote = quote_fees(&args).unwrap();
+        assert!(!quote.is_charge_sol && quote.is_unwrap_wsol_to_sa);
+
+        // Closing the destination account requires wallet fee accounts
+        args.commission_info |= 1 << 30;
+        assert!(quote_fees(&args).is_err());
+    }
+}
@@ -72,6 +72,10 @@ pub fn log_charge_fee_info(amount: u64, adjust_amount: u64, fee_account: &Pubkey
     fee_account.log();
 }
 
+pub fn log_user_share_info(amount: u64) {
+    msg!("user_share_amount: {:?}", amount);
+}
+
 pub fn log_rate_info(commission_rate: u32, platform_fee_rate: u32, trim_rate: Option<u8>) {
     if let Some(trim_rate) = trim_rate {
         msg!(
@@ -124,30 +128,6 @@ pub fn log_rate_info_v3_enhanced(
     );
 }
 
-pub fn log_claim_info_before(source_balance: u64, destination_balance: u64, amount: u64) {
-    msg!(
-        "before_source_balance: {:?}, before_destination_balance: {:?}, amount: {:?}",
-        source_balance,
-        destination_balance,
-        amount
-    );
-}
-
-pub fn log_claim_info_after(
-    source_balance: u64,
-    destination_balance: u64,
-    source_token_change: u64,
-    destination_token_change: u64,
-) {
-    msg!(
-        "after_source_balance: {:?}, after_destination_balance: {:?}, source_token_change: {:?}, destination_token_change: {:?}",
-        source_balance,
-        destination_balance,
-        source_token_change,
-        destination_token_change
-    );
-}
-
 pub fn log_sa_lamports_info(
     before_sa_lamports: u64,
     after_sa_lamports: u64,
@@ -0,0 +1,145 @@
+use crate::constants::*;
+use crate::error::ErrorCode;
+use crate::state::event::MintRiskEvent;
+use crate::utils::token_state::{MintState, unpack_mint};
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::extension::ExtensionType;
+
+/// What to do when a mint of the swap carries a risky extension
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord,
+)]
+pub enum MintRiskAction {
+    #[default]
+    Allow,
+    /// Emit a MintRiskEvent and continue
+    Flag,
+    /// Fail the swap
+    Reject,
+}
+
+impl TryFrom<u8> for MintRiskAction {
+    type Error = anchor_lang::error::Error;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(MintRiskAction::Allow),
+            1 => Ok(MintRiskAction::Flag),
+            2 => Ok(MintRiskAction::Reject),
+            _ => Err(ErrorCode::InvalidMintPolicy.into()),
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MintPolicy {
+    pub action: MintRiskAction,
+    /// Transfer fees above this rate are risky, denominated in basis points
+    pub max_transfer_fee_bps: u16,
+}
+
+impl MintPolicy {
+    pub fn validate(&self) -> Result<()> {
+        require!(self.max_transfer_fee_bps <= MAX_BASIS_POINTS, ErrorCode::InvalidMintPolicy);
+        Ok(())
+    }
+
+    /// The stricter of the two policies, a swap can not loosen the configured default
+    pub fn merge(&self, other: &MintPolicy) -> MintPolicy {
+        MintPolicy {
+            action: self.action.max(other.action),
+            max_transfer_fee_bps: self.max_transfer_fee_bps.min(other.max_transfer_fee_bps),
+        }
+    }
+
+    /// The MINT_RISK_* flags of a mint
+    pub fn mint_risks(&self, mint: &MintState) -> u8 {
+        let mut risks = 0;
+        if mint.permanent_delegate.is_some() {
+            risks |= MINT_RISK_PERMANENT_DELEGATE;
+        }
+        if mint.has_extension(ExtensionType::NonTransferable) {
+            risks |= MINT_RISK_NON_TRANSFERABLE;
+        }
+        if mint.default_frozen {
+            risks |= MINT_RISK_DEFAULT_FROZEN;
+        }
+        if mint.transfer_fee_basis_points > self.max_transfer_fee_bps {
+            risks |= MINT_RISK_HIGH_TRANSFER_FEE;
+        }
+        risks
+    }
+
+    /// Inspect the mint and apply the action if it is risky
+    pub fn enforce(&self, mint_info: &AccountInfo) -> Result<()> {
+        if self.action == MintRiskAction::Allow {
+            return Ok(());
+        }
+        let mint = unpack_mint(mint_info)?;
+        let risks = self.mint_risks(&mint);
+        if risks == 0 {
+            return Ok(());
+        }
+        emit!(MintRiskEvent {
+            mint: mint_info.key(),
+            risks,
+            rejected: self.action == MintRiskAction::Reject
+        });
+        require!(self.action != MintRiskAction::Reject, ErrorCode::MintRiskRejected);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mint(extensions: Vec<ExtensionType>) -> MintState {
+        MintState {
+            token_program: anchor_spl::token_2022::Token2022::id(),
+            mint_authority: None,
+            freeze_authority: None,
+            supply: 0,
+            decimals: 6,
+            extensions,
+            permanent_delegate: None,
+            default_frozen: false,
+            transfer_fee_basis_points: 0,
+        }
+    }
+
+    #[test]
+    pub fn test_mint_risks() {
+        let policy = MintPolicy { action: MintRiskAction::Reject, max_transfer_fee_bps: 100 };
+        assert_eq!(policy.mint_risks(&mint(vec![])), 0);
+
+        let mut state = mint(vec![ExtensionType::NonTransferable]);
+        state.permanent_delegate = Some(Pubkey::new_unique());
+        assert_eq!(
+            policy.mint_risks(&state),
+            MINT_RISK_PERMANENT_DELEGATE | MINT_RISK_NON_TRANSFERABLE
+        );
+
+        let mut state = mint(vec![ExtensionType::TransferFeeConfig]);
+        state.transfer_fee_basis_points = 100;
+        assert_eq!(policy.mint_risks(&state), 0);
+        state.transfer_fee_basis_points = 101;
+        state.default_frozen = true;
+        assert_eq!(
+            policy.mint_risks(&state),
+            MINT_RISK_HIGH_TRANSFER_FEE | MINT_RISK_DEFAULT_FROZEN
+        );
+    }
+
+    #[test]
+    pub fn test_merge_policies() {
+        let config = MintPolicy { action: MintRiskAction::Flag, max_transfer_fee_bps: 500 };
+        let swap = MintPolicy { action: MintRiskAction::Allow, max_transfer_fee_bps: 100 };
+        assert_eq!(
+            config.merge(&swap),
+            MintPolicy { action: MintRiskAction::Flag, max_transfer_fee_bps: 100 }
+        );
+        assert_eq!(MintRiskAction::try_from(2).unwrap(), MintRiskAction::Reject);
+        assert!(MintRiskAction::try_from(3).is_err());
+    }
+}
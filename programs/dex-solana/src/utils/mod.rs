@@ -1,9 +1,19 @@
 pub mod fee;
+pub mod fee_policy;
+pub mod fee_quote;
 pub mod logging;
+pub mod mint_policy;
 pub mod swap;
 pub mod token;
+pub mod token_state;
+pub mod wsol;
 
 pub use fee::*;
+pub use fee_policy::*;
+pub use fee_quote::*;
 pub use logging::*;
+pub use mint_policy::*;
 pub use swap::*;
 pub use token::*;
+pub use token_state::*;
+pub use wsol::*;
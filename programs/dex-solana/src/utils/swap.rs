@@ -1,13 +1,35 @@
-use crate::constants::*;
 use crate::error::LimitOrderError;
 use crate::processor::common_processor::CommonSwapProcessor;
-use crate::utils::{create_sa_if_needed, transfer_token};
+use crate::utils::{
+    FeeAmounts, FeePolicy, FeeRecipients, FeeSource, TransferContext, create_sa_if_needed,
+    transfer_token,
+};
 use anchor_lang::prelude::*;
 use anchor_spl::associated_token::AssociatedToken;
 use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
 pub struct FillOrderSwapProcessor;
 
+/// Pays the fee of a limit order fill out of the amount to the fee token account,
+/// returns the fee amount
+pub fn pay_limit_order_fee<'info>(
+    source: &FeeSource<'_, 'info>,
+    fee_token_account: &AccountInfo<'info>,
+    amount: u64,
+    fee_rate: u32,
+    fee_direction: bool,
+) -> Result<u64> {
+    let commission_amount = FeePolicy::limit_order(fee_rate, fee_direction)
+        .commission_amount(amount)
+        .map_err(|_| LimitOrderError::MathOverflow)?;
+    FeeRecipients { commission_account: Some(fee_token_account), ..Default::default() }.pay_fees(
+        source,
+        &FeeAmounts { commission_amount, platform_fee_amount: 0 },
+        fee_direction,
+    )?;
+    Ok(commission_amount)
+}
+
 impl<'info> CommonSwapProcessor<'info> for FillOrderSwapProcessor {
     fn get_swap_accounts(
         &self,
@@ -79,29 +101,24 @@ impl<'info> CommonSwapProcessor<'info> for FillOrderSwapProcessor {
             let fee_rate = fee_rate.unwrap();
             let fee_token_account = fee_token_account.unwrap();
 
-            let fee_amount = amount_in
-                .checked_mul(fee_rate as u64)
-                .ok_or(LimitOrderError::MathOverflow)?
-                .checked_div(COMMISSION_DENOMINATOR_V2)
-                .ok_or(LimitOrderError::MathOverflow)?;
-
-            msg!(
-                "fee_direction: {:?}, fee_rate: {:?}, fee_amount: {:?}",
-                true,
-                fee_rate,
-                fee_amount
-            );
-
             // Transfer token to fee token account
-            transfer_token(
-                owner.to_account_info(),
-                source_token_account.to_account_info(),
-                fee_token_account.to_account_info(),
-                source_mint.to_account_info(),
-                source_token_program.to_account_info(),
-                fee_amount,
-                source_mint.decimals,
-                owner_seeds,
+            let token_account = source_token_account.to_account_info();
+            let source = FeeSource {
+                authority: owner,
+                token_account: Some(&token_account),
+                token_mint: source_mint,
+                token_program: Some(source_token_program),
+                signer_seeds: owner_seeds,
+                is_charge_sol: false,
+                legacy_transfer: true,
+                transfer_ctx: TransferContext::default(),
+            };
+            let fee_amount = pay_limit_order_fee(
+                &source,
+                &fee_token_account.to_account_info(),
+                amount_in,
+                fee_rate,
+                true,
             )?;
 
             real_amount_in =
@@ -155,29 +172,23 @@ impl<'info> CommonSwapProcessor<'info> for FillOrderSwapProcessor {
             let fee_rate = fee_rate.unwrap();
             let fee_token_account = fee_token_account.unwrap();
 
-            let fee_amount = amount_out
-                .checked_mul(fee_rate as u64)
-                .ok_or(LimitOrderError::MathOverflow)?
-                .checked_div(COMMISSION_DENOMINATOR_V2)
-                .ok_or(LimitOrderError::MathOverflow)?;
-
-            msg!(
-                "fee_direction: {:?}, fee_rate: {:?}, fee_amount: {:?}",
-                false,
-                fee_rate,
-                fee_amount
-            );
-
             // Transfer token to fee token account
-            transfer_token(
-                sa_authority.to_account_info(),
-                destination_token_sa.to_account_info(),
-                fee_token_account.to_account_info(),
-                destination_mint.to_account_info(),
-                destination_token_program.to_account_info(),
-                fee_amount,
-                destination_mint.decimals,
-                owner_seeds,
+            let source = FeeSource {
+                authority: sa_authority,
+                token_account: Some(destination_token_sa),
+                token_mint: destination_mint,
+                token_program: Some(destination_token_program),
+                signer_seeds: owner_seeds,
+                is_charge_sol: false,
+                legacy_transfer: true,
+                transfer_ctx: TransferContext::default(),
+            };
+            let fee_amount = pay_limit_order_fee(
+                &source,
+                &fee_token_account.to_account_info(),
+                amount_out,
+                fee_rate,
+                false,
             )?;
 
             real_amount_out =
@@ -1,9 +1,13 @@
 use crate::constants::MIN_SOL_ACCOUNT_RENT;
 use crate::error::ErrorCode;
+use crate::utils::{inspect_token_account, unpack_token_account};
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
 use anchor_lang::solana_program::program::{invoke, invoke_signed};
 use anchor_lang::solana_program::system_instruction::transfer;
-use anchor_spl::associated_token::{AssociatedToken, create};
+use anchor_spl::associated_token::{
+    AssociatedToken, create, get_associated_token_address_with_program_id,
+};
 use anchor_spl::token::Token;
 use anchor_spl::token_2022::spl_token_2022::extension::BaseStateWithExtensions;
 use anchor_spl::token_2022::spl_token_2022::{
@@ -23,10 +27,126 @@ pub fn transfer_token<'a>(
     amount: u64,
     mint_decimals: u8,
     signer_seeds: Option<&[&[&[u8]]]>,
+) -> Result<()> {
+    transfer_token_with_hook(
+        authority,
+        from,
+        to,
+        mint,
+        token_program,
+        amount,
+        mint_decimals,
+        signer_seeds,
+        &[],
+    )
+}
+
+/// The extra accounts and data of the router transfers into user and fee accounts
+#[derive(Clone, Copy, Default)]
+pub struct TransferContext<'a, 'info> {
+    /// The accounts resolving the transfer hooks of the mints, and the memo program
+    pub hook_accounts: &'a [AccountInfo<'info>],
+    /// Written before the transfers into accounts requiring incoming memos
+    pub memo: Option<TransferMemo>,
+}
+
+/// The memo of a swap, only formatted when a destination requires incoming memos
+#[derive(Clone, Copy, Debug)]
+pub struct TransferMemo {
+    pub order_id: u64,
+    pub integrator_id: Option<u64>,
+}
+
+impl std::fmt::Display for TransferMemo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.integrator_id {
+            Some(integrator_id) => {
+                write!(f, "order_id:{},integrator:{}", self.order_id, integrator_id)
+            }
+            None => write!(f, "order_id:{}", self.order_id),
+        }
+    }
+}
+
+/// Transfer token to a user or fee account, writing a memo first if the destination is a
+/// token-2022 account requiring incoming memos
+pub fn transfer_token_routed<'a>(
+    authority: AccountInfo<'a>,
+    from: AccountInfo<'a>,
+    to: AccountInfo<'a>,
+    mint: AccountInfo<'a>,
+    token_program: AccountInfo<'a>,
+    amount: u64,
+    mint_decimals: u8,
+    signer_seeds: Option<&[&[&[u8]]]>,
+    transfer_ctx: &TransferContext<'_, 'a>,
 ) -> Result<()> {
     if amount == 0 {
         return Ok(());
     }
+    if token_program.key() == Token2022::id()
+        && inspect_token_account(&to).is_some_and(|account| account.memo_required)
+    {
+        let memo = transfer_ctx.memo.map(|memo| memo.to_string()).unwrap_or_default();
+        invoke_memo(transfer_ctx.hook_accounts, memo.as_bytes())?;
+    }
+    transfer_token_with_hook(
+        authority,
+        from,
+        to,
+        mint,
+        token_program,
+        amount,
+        mint_decimals,
+        signer_seeds,
+        transfer_ctx.hook_accounts,
+    )
+}
+
+/// Write a memo, the memo program must be passed in accounts. Token-2022 checks that the memo
+/// is the instruction processed right before the transfer.
+pub fn invoke_memo(accounts: &[AccountInfo], memo: &[u8]) -> Result<()> {
+    let memo_program = accounts
+        .iter()
+        .find(|account| account.key() == crate::memo_program::ID)
+        .ok_or(ErrorCode::MemoProgramRequired)?;
+    invoke(
+        &Instruction { program_id: crate::memo_program::ID, accounts: vec![], data: memo.to_vec() },
+        std::slice::from_ref(memo_program),
+    )?;
+    Ok(())
+}
+
+/// Transfer tokens, resolving the extra accounts of the mint's transfer hook from hook_accounts:
+/// the hook program, its ExtraAccountMetaList PDA and the extra accounts it lists
+pub fn transfer_token_with_hook<'a>(
+    authority: AccountInfo<'a>,
+    from: AccountInfo<'a>,
+    to: AccountInfo<'a>,
+    mint: AccountInfo<'a>,
+    token_program: AccountInfo<'a>,
+    amount: u64,
+    mint_decimals: u8,
+    signer_seeds: Option<&[&[&[u8]]]>,
+    hook_accounts: &[AccountInfo<'a>],
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    if !hook_accounts.is_empty() && token_program.key() == Token2022::id() {
+        spl_token_2022::onchain::invoke_transfer_checked(
+            token_program.key,
+            from,
+            mint,
+            to,
+            authority,
+            hook_accounts,
+            amount,
+            mint_decimals,
+            signer_seeds.unwrap_or(&[]),
+        )?;
+        return Ok(());
+    }
     if let Some(signer_seeds) = signer_seeds {
         token_2022::transfer_checked(
             CpiContext::new_with_signer(
@@ -49,6 +169,19 @@ pub fn transfer_token<'a>(
     }
 }
 
+/// Split the trailing hook_accounts_len accounts off the remaining accounts,
+/// returns the route accounts and the transfer hook accounts
+pub fn split_hook_accounts<'a, 'info>(
+    remaining_accounts: &'a [AccountInfo<'info>],
+    hook_accounts_len: u8,
+) -> Result<(&'a [AccountInfo<'info>], &'a [AccountInfo<'info>])> {
+    let route_accounts_len = remaining_accounts
+        .len()
+        .checked_sub(hook_accounts_len as usize)
+        .ok_or(ErrorCode::InvalidHookAccounts)?;
+    Ok(remaining_accounts.split_at(route_accounts_len))
+}
+
 pub fn transfer_sol<'a>(
     from: AccountInfo<'a>,
     to: AccountInfo<'a>,
@@ -223,6 +356,11 @@ pub fn create_sa_if_needed<'info>(
             },
         ))?;
     }
+    let token_sa_state = unpack_token_account(token_sa)?;
+    require!(
+        token_sa_state.is_held_by(&sa_authority.key(), &mint.key()),
+        ErrorCode::InvalidTokenAccount
+    );
     let token_sa_box = Box::leak(Box::new(token_sa.clone()));
     Ok(Some(InterfaceAccount::<TokenAccount>::try_from(token_sa_box)?))
 }
@@ -233,11 +371,8 @@ pub fn is_token_account_initialized(account: &AccountInfo) -> bool {
     if account.lamports() == 0 || account.data_is_empty() {
         return false;
     }
-    // Check if the account owner is the Token program
-    if *account.owner != Token::id() && *account.owner != Token2022::id() {
-        return false;
-    }
-    true
+    // Check if the account is an initialized token account of the Token programs
+    inspect_token_account(account).is_some()
 }
 
 /// Calculate the fee for input amount
@@ -256,6 +391,31 @@ pub fn get_transfer_fee(mint_info: &AccountInfo, pre_fee_amount: u64) -> Result<
     Ok(fee)
 }
 
+/// Calculate the fee to add on top of a transfer so that post_fee_amount is received
+pub fn get_transfer_inverse_fee(mint_info: &AccountInfo, post_fee_amount: u64) -> Result<u64> {
+    if *mint_info.owner == Token::id() || post_fee_amount == 0 {
+        return Ok(0);
+    }
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+
+    let fee = if let Ok(transfer_fee_config) = mint.get_extension::<TransferFeeConfig>() {
+        transfer_fee_config
+            .calculate_inverse_epoch_fee(Clock::get()?.epoch, post_fee_amount)
+            .ok_or(ErrorCode::CalculationError)?
+    } else {
+        0
+    };
+    Ok(fee)
+}
+
+/// Calculate the amount to transfer so that net_amount is received
+pub fn get_transfer_gross_amount(mint_info: &AccountInfo, net_amount: u64) -> Result<u64> {
+    Ok(net_amount
+        .checked_add(get_transfer_inverse_fee(mint_info, net_amount)?)
+        .ok_or(ErrorCode::CalculationError)?)
+}
+
 pub fn harvest_withheld_tokens_to_mint<'a>(
     token_program: AccountInfo<'a>,
     token_mint: AccountInfo<'a>,
@@ -305,6 +465,20 @@ pub fn is_ata(account: &AccountInfo) -> bool {
         || account.as_ref().owner == &crate::token_2022_program::ID
 }
 
+/// Check if the mint is the native SOL mint of spl-token or token-2022
+pub fn is_native_mint(mint: &Pubkey) -> bool {
+    *mint == crate::wsol_program::ID || *mint == crate::wsol_2022_program::ID
+}
+
+/// The SA token account of a native SOL mint
+pub fn native_mint_sa(mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    if *mint == crate::wsol_program::ID {
+        crate::wsol_sa::ID
+    } else {
+        get_associated_token_address_with_program_id(&crate::authority_pda::ID, mint, token_program)
+    }
+}
+
 pub fn is_system_account(account: &AccountInfo) -> bool {
     account.as_ref().owner == &crate::system_program::ID
 }
@@ -0,0 +1,245 @@
+use crate::error::ErrorCode;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::token::Token;
+use anchor_spl::token_2022::Token2022;
+use anchor_spl::token_2022::spl_token_2022::extension::{
+    BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+    default_account_state::DefaultAccountState, memo_transfer::MemoTransfer,
+    permanent_delegate::PermanentDelegate, transfer_fee::TransferFeeConfig,
+};
+use anchor_spl::token_2022::spl_token_2022::state::{Account, AccountState, Mint, Multisig};
+
+/// A token account of spl-token or token-2022, decoded with its extensions
+#[derive(Clone, Debug, PartialEq)]
+pub struct TokenAccountState {
+    pub token_program: Pubkey,
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub state: AccountState,
+    pub delegate: Option<Pubkey>,
+    pub delegated_amount: u64,
+    pub close_authority: Option<Pubkey>,
+    pub is_native: bool,
+    pub extensions: Vec<ExtensionType>,
+    /// The extensions could not be decoded, extensions is empty and memo_required is false
+    pub extensions_unknown: bool,
+    /// Incoming transfers must be preceded by a memo, by the MemoTransfer extension
+    pub memo_required: bool,
+}
+
+impl TokenAccountState {
+    pub fn is_frozen(&self) -> bool {
+        self.state == AccountState::Frozen
+    }
+
+    pub fn has_extension(&self, extension: ExtensionType) -> bool {
+        self.extensions.contains(&extension)
+    }
+
+    /// The account is held by owner for mint and can be debited by the owner alone
+    pub fn is_held_by(&self, owner: &Pubkey, mint: &Pubkey) -> bool {
+        self.owner == *owner && self.mint == *mint && !self.is_frozen()
+    }
+}
+
+/// A mint of spl-token or token-2022, decoded with its extensions
+#[derive(Clone, Debug, PartialEq)]
+pub struct MintState {
+    pub token_program: Pubkey,
+    pub mint_authority: Option<Pubkey>,
+    pub freeze_authority: Option<Pubkey>,
+    pub supply: u64,
+    pub decimals: u8,
+    pub extensions: Vec<ExtensionType>,
+    /// The authority of the PermanentDelegate extension
+    pub permanent_delegate: Option<Pubkey>,
+    /// New token accounts are frozen by the DefaultAccountState extension
+    pub default_frozen: bool,
+    /// The highest of the current and scheduled transfer fees, in basis points
+    pub transfer_fee_basis_points: u16,
+}
+
+impl MintState {
+    pub fn has_extension(&self, extension: ExtensionType) -> bool {
+        self.extensions.contains(&extension)
+    }
+}
+
+fn is_token_program(program_id: &Pubkey) -> bool {
+    *program_id == Token::id() || *program_id == Token2022::id()
+}
+
+/// Inspect an initialized token account, returns None if it is not one, the token-2022 layout
+/// is a superset of the spl-token one, so both programs are decoded the same way. The base
+/// account is decoded first, an account whose extensions fail to parse is still a token account.
+pub fn inspect_token_account(account: &AccountInfo) -> Option<TokenAccountState> {
+    if !is_token_program(account.owner) || account.data_is_empty() {
+        return None;
+    }
+    let data = account.try_borrow_data().ok()?;
+    if data.len() < Account::LEN || data.len() == Multisig::LEN {
+        return None;
+    }
+    let base = Account::unpack(&data[..Account::LEN]).ok()?;
+    let extensions = StateWithExtensions::<Account>::unpack(&data).ok().and_then(|token_account| {
+        let memo_required = token_account
+            .get_extension::<MemoTransfer>()
+            .is_ok_and(|extension| bool::from(extension.require_incoming_transfer_memos));
+        Some((token_account.get_extension_types().ok()?, memo_required))
+    });
+    let extensions_unknown = extensions.is_none();
+    let (extensions, memo_required) = extensions.unwrap_or_default();
+    Some(TokenAccountState {
+        token_program: *account.owner,
+        owner: base.owner,
+        mint: base.mint,
+        amount: base.amount,
+        state: base.state,
+        delegate: base.delegate.into(),
+        delegated_amount: base.delegated_amount,
+        close_authority: base.close_authority.into(),
+        is_native: base.is_native.is_some(),
+        extensions,
+        extensions_unknown,
+        memo_required,
+    })
+}
+
+/// Inspect an initialized mint, returns None if it is not one
+pub fn inspect_mint(account: &AccountInfo) -> Option<MintState> {
+    if !is_token_program(account.owner) || account.data_is_empty() {
+        return None;
+    }
+    let data = account.try_borrow_data().ok()?;
+    let mint = StateWithExtensions::<Mint>::unpack(&data).ok()?;
+    let permanent_delegate = mint
+        .get_extension::<PermanentDelegate>()
+        .ok()
+        .and_then(|extension| Option::<Pubkey>::from(extension.delegate));
+    let default_frozen = mint
+        .get_extension::<DefaultAccountState>()
+        .is_ok_and(|extension| extension.state == AccountState::Frozen as u8);
+    let transfer_fee_basis_points =
+        mint.get_extension::<TransferFeeConfig>().map_or(0, |extension| {
+            u16::from(extension.older_transfer_fee.transfer_fee_basis_points)
+                .max(u16::from(extension.newer_transfer_fee.transfer_fee_basis_points))
+        });
+    Some(MintState {
+        token_program: *account.owner,
+        mint_authority: mint.base.mint_authority.into(),
+        freeze_authority: mint.base.freeze_authority.into(),
+        supply: mint.base.supply,
+        decimals: mint.base.decimals,
+        extensions: mint.get_extension_types().ok()?,
+        permanent_delegate,
+        default_frozen,
+        transfer_fee_basis_points,
+    })
+}
+
+pub fn unpack_token_account(account: &AccountInfo) -> Result<TokenAccountState> {
+    inspect_token_account(account).ok_or(ErrorCode::InvalidTokenAccount.into())
+}
+
+pub fn unpack_mint(account: &AccountInfo) -> Result<MintState> {
+    inspect_mint(account).ok_or(ErrorCode::InvalidTokenMint.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::program_option::COption;
+    use anchor_spl::token_2022::spl_token_2022::extension::immutable_owner::ImmutableOwner;
+    use anchor_spl::token_2022::spl_token_2022::extension::{
+        BaseStateWithExtensionsMut, StateWithExtensionsMut,
+    };
+
+    fn token_account(owner: Pubkey, mint: Pubkey, state: AccountState) -> Account {
+        Account {
+            mint,
+            owner,
+            amount: 100,
+            delegate: COption::None,
+            state,
+            is_native: COption::None,
+            delegated_amount: 0,
+            close_authority: COption::Some(owner),
+        }
+    }
+
+    fn inspect(program_id: &Pubkey, data: &mut [u8]) -> Option<TokenAccountState> {
+        let key = Pubkey::new_unique();
+        let mut lamports = 1_000_000;
+        let account =
+            AccountInfo::new(&key, false, true, &mut lamports, data, program_id, false, 0);
+        inspect_token_account(&account)
+    }
+
+    #[test]
+    pub fn test_inspect_legacy_account() {
+        let (owner, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut data = vec![0u8; Account::LEN];
+        Account::pack(token_account(owner, mint, AccountState::Initialized), &mut data).unwrap();
+
+        let state = inspect(&Token::id(), &mut data).unwrap();
+        assert_eq!((state.owner, state.mint, state.amount), (owner, mint, 100));
+        assert_eq!(state.close_authority, Some(owner));
+        assert!(state.is_held_by(&owner, &mint) && state.extensions.is_empty());
+        assert!(!state.memo_required && !state.extensions_unknown);
+
+        // Owned by another program or not initialized
+        assert!(inspect(&Pubkey::new_unique(), &mut data).is_none());
+        assert!(inspect(&Token::id(), &mut [0u8; Account::LEN]).is_none());
+
+        // A multisig is not a token account, even if its signers decode to an initialized one
+        let mut multisig = vec![0u8; Multisig::LEN];
+        multisig[..Account::LEN].copy_from_slice(&data);
+        assert!(inspect(&Token::id(), &mut multisig).is_none());
+    }
+
+    #[test]
+    pub fn test_inspect_account_with_extensions() {
+        let (owner, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let len = ExtensionType::try_calculate_account_len::<Account>(&[
+            ExtensionType::ImmutableOwner,
+            ExtensionType::MemoTransfer,
+        ])
+        .unwrap();
+        let mut data = vec![0u8; len];
+        let mut state = StateWithExtensionsMut::<Account>::unpack_uninitialized(&mut data).unwrap();
+        state.init_extension::<ImmutableOwner>(true).unwrap();
+        state.init_extension::<MemoTransfer>(true).unwrap().require_incoming_transfer_memos =
+            true.into();
+        state.base = token_account(owner, mint, AccountState::Frozen);
+        state.pack_base();
+        state.init_account_type().unwrap();
+
+        let state = inspect(&Token2022::id(), &mut data).unwrap();
+        assert!(state.has_extension(ExtensionType::ImmutableOwner));
+        assert!(state.memo_required);
+        assert!(state.is_frozen() && !state.is_held_by(&owner, &mint));
+    }
+
+    #[test]
+    pub fn test_inspect_account_with_malformed_extensions() {
+        let (owner, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let len =
+            ExtensionType::try_calculate_account_len::<Account>(&[ExtensionType::ImmutableOwner])
+                .unwrap();
+        let mut data = vec![0u8; len];
+        let mut state = StateWithExtensionsMut::<Account>::unpack_uninitialized(&mut data).unwrap();
+        state.init_extension::<ImmutableOwner>(true).unwrap();
+        state.base = token_account(owner, mint, AccountState::Initialized);
+        state.pack_base();
+        state.init_account_type().unwrap();
+        // Overflow the length of the first TLV entry
+        let tlv_len = Account::LEN + 1 + 2;
+        data[tlv_len..tlv_len + 2].copy_from_slice(&u16::MAX.to_le_bytes());
+
+        let state = inspect(&Token2022::id(), &mut data).unwrap();
+        assert_eq!((state.owner, state.mint), (owner, mint));
+        assert!(state.extensions_unknown && state.extensions.is_empty());
+    }
+}
@@ -0,0 +1,164 @@
+use crate::constants::*;
+use crate::error::ErrorCode;
+use crate::utils::{
+    close_token_account, is_native_mint, is_token_account_initialized, sync_wsol_account,
+    transfer_sol, unpack_token_account,
+};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::solana_program::system_instruction;
+use anchor_spl::token_interface;
+
+/// A wSOL account living for a single instruction, created at the address derived from
+/// (mint, owner, seed) so the accounts can be pooled and reused by the owner
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EphemeralWsolArgs {
+    pub seed: u32,
+    pub bump: u8,
+    /// The lamports wrapped into the account when it is the swap source
+    pub lamports: u64,
+}
+
+/// The address of the ephemeral wSOL account of the owner, None if the bump is not valid
+pub fn ephemeral_wsol_address(
+    wsol_mint: &Pubkey,
+    owner: &Pubkey,
+    args: &EphemeralWsolArgs,
+) -> Option<Pubkey> {
+    Pubkey::create_program_address(
+        &[wsol_mint.as_ref(), owner.as_ref(), &args.seed.to_le_bytes(), &[args.bump]],
+        &crate::ID,
+    )
+    .ok()
+}
+
+/// Allocate, assign and initialize the wSOL account derived from (mint, owner, seed), the rent
+/// must already be funded
+pub fn init_wsol_account_with_seed<'a>(
+    wsol_account: &AccountInfo<'a>,
+    owner: &AccountInfo<'a>,
+    wsol_mint: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    seed: u32,
+    bump: u8,
+) -> Result<()> {
+    let seed_bytes = seed.to_le_bytes();
+    let seeds: &[&[u8]] = &[wsol_mint.key.as_ref(), owner.key.as_ref(), &seed_bytes, &[bump]];
+    let signer_seeds: &[&[&[u8]]] = &[seeds];
+    let space = anchor_spl::token::TokenAccount::LEN as u64;
+
+    // 1) allocate space for the account (allocate)
+    let ix_allocate = system_instruction::allocate(wsol_account.key, space);
+    invoke_signed(&ix_allocate, std::slice::from_ref(wsol_account), signer_seeds)?;
+
+    // 2) assign ownership to the token program (assign)
+    let ix_assign = system_instruction::assign(wsol_account.key, token_program.key);
+    invoke_signed(&ix_assign, std::slice::from_ref(wsol_account), signer_seeds)?;
+
+    // 3) initialize as token account (initializeAccount3)
+    token_interface::initialize_account3(CpiContext::new(
+        token_program.to_account_info(),
+        token_interface::InitializeAccount3 {
+            account: wsol_account.to_account_info(),
+            mint: wsol_mint.to_account_info(),
+            authority: owner.to_account_info(),
+        },
+    ))
+}
+
+/// Create the ephemeral wSOL account of the owner if needed and wrap lamports into it, the payer
+/// funds the rent and the wrapped lamports
+pub fn open_ephemeral_wsol<'a>(
+    payer: &AccountInfo<'a>,
+    wsol_account: &AccountInfo<'a>,
+    owner: &AccountInfo<'a>,
+    wsol_mint: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    args: &EphemeralWsolArgs,
+) -> Result<()> {
+    require!(is_native_mint(wsol_mint.key), ErrorCode::InvalidMint);
+    let expected = ephemeral_wsol_address(wsol_mint.key, owner.key, args)
+        .ok_or(ErrorCode::InvalidTokenAccount)?;
+    require_keys_eq!(wsol_account.key(), expected, ErrorCode::InvalidTokenAccount);
+
+    if is_token_account_initialized(wsol_account) {
+        let state = unpack_token_account(wsol_account)?;
+        require!(state.is_held_by(owner.key, wsol_mint.key), ErrorCode::InvalidTokenAccount);
+        if args.lamports > 0 {
+            transfer_sol(payer.clone(), wsol_account.clone(), args.lamports, None)?;
+            sync_wsol_account(wsol_account.clone(), token_program.clone(), None)?;
+        }
+        return Ok(());
+    }
+
+    // The native amount is set from the lamports above the rent on initialization
+    let rent = Rent::get()?.minimum_balance(anchor_spl::token::TokenAccount::LEN);
+    let lamports = rent
+        .saturating_sub(wsol_account.lamports())
+        .checked_add(args.lamports)
+        .ok_or(ErrorCode::CalculationError)?;
+    transfer_sol(payer.clone(), wsol_account.clone(), lamports, None)?;
+    init_wsol_account_with_seed(wsol_account, owner, wsol_mint, token_program, args.seed, args.bump)
+}
+
+/// Close a wSOL account back to its authority, signing for the SA, and move the rent on to
+/// rent_receiver. Returns the lamports of the SA before the close, or 0 for other authorities.
+pub fn close_ephemeral_wsol<'a>(
+    wsol_account: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    rent_receiver: Option<&AccountInfo<'a>>,
+) -> Result<u64> {
+    let is_sa = authority.key() == authority_pda::ID;
+    if is_sa {
+        require!(wsol_account.key() != wsol_sa::ID, ErrorCode::InvalidSourceTokenAccount);
+    }
+    let signer_seeds = if is_sa { Some(SA_AUTHORITY_SEED) } else { None };
+    let before_authority_lamports = authority.lamports();
+    let rent = match rent_receiver {
+        Some(_) => {
+            let amount = unpack_token_account(wsol_account)?.amount;
+            wsol_account.lamports().saturating_sub(amount)
+        }
+        None => 0,
+    };
+
+    close_token_account(
+        wsol_account.clone(),
+        authority.clone(),
+        authority.clone(),
+        token_program.clone(),
+        signer_seeds,
+    )?;
+    if let Some(rent_receiver) = rent_receiver {
+        if rent_receiver.key != authority.key && rent > 0 {
+            transfer_sol(authority.clone(), rent_receiver.clone(), rent, signer_seeds)?;
+        }
+    }
+    Ok(if is_sa { before_authority_lamports } else { 0 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_ephemeral_wsol_address() {
+        let owner = Pubkey::new_unique();
+        let seed = 7u32;
+        let (expected, bump) = Pubkey::find_program_address(
+            &[wsol_program::ID.as_ref(), owner.as_ref(), &seed.to_le_bytes()],
+            &crate::ID,
+        );
+        let args = EphemeralWsolArgs { seed, bump, lamports: 0 };
+        assert_eq!(ephemeral_wsol_address(&wsol_program::ID, &owner, &args), Some(expected));
+
+        // Another seed or owner derives another account
+        let other = EphemeralWsolArgs { seed: seed + 1, ..args };
+        assert_ne!(ephemeral_wsol_address(&wsol_program::ID, &owner, &other), Some(expected));
+        assert_ne!(
+            ephemeral_wsol_address(&wsol_program::ID, &Pubkey::new_unique(), &args),
+            Some(expected)
+        );
+    }
+}